@@ -3,11 +3,11 @@ use crate::{
     query::{build_query, FilterValue},
     query_sink::{AsyncQueryResultStream, AsyncQueryResultStreamInner, QuerySink},
     result_enumerator::IWbemClassWrapper,
-    WMIResult,
+    WMIError, WMIResult,
 };
-use futures::stream::{Stream, StreamExt, TryStreamExt};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use serde::de;
-use std::collections::HashMap;
+use std::{collections::HashMap, future::Future};
 use windows::core::BSTR;
 use windows::Win32::System::Wmi::{IWbemObjectSink, WBEM_FLAG_BIDIRECTIONAL};
 
@@ -22,7 +22,7 @@ impl WMIConnection {
     pub fn exec_query_async_native_wrapper(
         &self,
         query: impl AsRef<str>,
-    ) -> WMIResult<impl Stream<Item = WMIResult<IWbemClassWrapper>>> {
+    ) -> WMIResult<AsyncQueryResultStream> {
         let query_language = BSTR::from("WQL");
         let query = BSTR::from(query.as_ref());
 
@@ -52,6 +52,62 @@ impl WMIConnection {
         ))
     }
 
+    /// Async version of [`get_raw_by_path`](WMIConnection#method.get_raw_by_path), using WMI's
+    /// [GetObjectAsync](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-getobjectasync) function.
+    ///
+    /// Useful for fetching class definitions (or instances) from slow connections without blocking.
+    /// The call is cancelled if the returned future is dropped before it completes.
+    ///
+    /// ```edition2021
+    /// # use wmi::*;
+    /// # use futures::executor::block_on;
+    /// # fn main() -> WMIResult<()> {
+    /// #   block_on(get_object_async())
+    /// # }
+    /// # async fn get_object_async() -> WMIResult<()> {
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let class = con.async_get_object("Win32_Process").await?;
+    /// println!("{:?}", class.list_methods()?);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub async fn async_get_object(
+        &self,
+        object_path: impl AsRef<str>,
+    ) -> WMIResult<IWbemClassWrapper> {
+        let object_path = BSTR::from(object_path.as_ref());
+
+        let stream = AsyncQueryResultStreamInner::new();
+        // The internal RefCount has initial value = 1.
+        let p_sink = QuerySink {
+            stream: stream.clone(),
+        };
+        let p_sink_handle: IWbemObjectSink = p_sink.into();
+
+        unsafe {
+            self.svc.GetObjectAsync(
+                &object_path,
+                Default::default(),
+                &self.ctx.0,
+                &p_sink_handle,
+            )?;
+        }
+
+        // The stream cancels the call when dropped.
+        let mut results = AsyncQueryResultStream::new(stream.clone(), self.clone(), p_sink_handle);
+
+        let object = results.next().await.transpose()?;
+
+        // The status of the call is only available once the stream is done.
+        while let Some(item) = results.next().await {
+            item?;
+        }
+
+        stream.status().ok()?;
+
+        object.ok_or(WMIError::ResultEmpty)
+    }
+
     /// Async version of [`raw_query`](WMIConnection#method.raw_query)
     /// Execute a free-text query and deserialize the results.
     /// Can be used either with a struct (like `query` and `filtered_query`),
@@ -133,14 +189,48 @@ impl WMIConnection {
     }
 }
 
+/// Run `futures` (such as calls to [`WMIConnection::async_get_object`]) concurrently,
+/// with at most `limit` of them running at the same time, and return their results in the original order.
+///
+/// Running too many calls at once can overload WMI, which then fails them with `WBEM_E_SERVER_TOO_BUSY`.
+///
+/// ```edition2021
+/// # use wmi::*;
+/// # use futures::executor::block_on;
+/// # fn main() -> WMIResult<()> {
+/// #   block_on(get_objects_async())
+/// # }
+/// # async fn get_objects_async() -> WMIResult<()> {
+/// # let con = WMIConnection::new(COMLibrary::new()?)?;
+/// let paths = ["Win32_Process", "Win32_Service", "Win32_OperatingSystem"];
+///
+/// let classes = join_all_limited(paths.iter().map(|path| con.async_get_object(path)), 2).await;
+///
+/// for class in classes {
+///     println!("{}", class?.class()?);
+/// }
+/// #   Ok(())
+/// # }
+/// ```
+pub async fn join_all_limited<F>(
+    futures: impl IntoIterator<Item = F>,
+    limit: usize,
+) -> Vec<F::Output>
+where
+    F: Future,
+{
+    stream::iter(futures).buffered(limit.max(1)).collect().await
+}
+
 #[allow(non_snake_case)]
 #[allow(non_camel_case_types)]
 #[cfg(test)]
 mod tests {
+    use super::join_all_limited;
     use crate::{tests::fixtures::*, Variant};
     use futures::stream::{self, StreamExt};
     use serde::Deserialize;
-    use std::collections::HashMap;
+    use std::{cell::Cell, collections::HashMap};
 
     #[async_std::test]
     async fn async_it_works_async() {
@@ -192,17 +282,38 @@ mod tests {
 
         for res in results {
             match res.get("GroupComponent") {
-                Some(Variant::String(s)) => assert_ne!(s, ""),
+                Some(Variant::Reference(s)) => assert_ne!(s, ""),
                 _ => assert!(false),
             }
 
             match res.get("PartComponent") {
-                Some(Variant::String(s)) => assert_ne!(s, ""),
+                Some(Variant::Reference(s)) => assert_ne!(s, ""),
                 _ => assert!(false),
             }
         }
     }
 
+    #[async_std::test]
+    async fn async_it_gets_an_object() {
+        let wmi_con = wmi_con();
+
+        let class = wmi_con.async_get_object("Win32_Process").await.unwrap();
+        assert_eq!(class.class().unwrap(), "Win32_Process");
+        assert!(class
+            .list_methods()
+            .unwrap()
+            .contains(&"Create".to_string()));
+
+        let os = wmi_con
+            .async_get_object(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .await
+            .unwrap();
+        assert_eq!(os.class().unwrap(), "Win32_OperatingSystem");
+
+        let result = wmi_con.async_get_object("Win32_NoSuchClass").await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     async fn async_it_works_async_tokio_concurrent() {
         let wmi_con = wmi_con();
@@ -231,4 +342,45 @@ mod tests {
 
         assert_eq!(results.len(), 150);
     }
+
+    #[tokio::test]
+    async fn async_it_limits_concurrent_calls() {
+        let wmi_con = &wmi_con();
+
+        let running = &Cell::new(0);
+        let max_running = &Cell::new(0);
+
+        let paths = [
+            "Win32_Process",
+            "Win32_Service",
+            "Win32_OperatingSystem",
+            "Win32_ComputerSystem",
+            "Win32_LogicalDisk",
+            "Win32_Process",
+        ];
+
+        let classes = join_all_limited(
+            paths.iter().map(|path| async move {
+                running.set(running.get() + 1);
+                max_running.set(max_running.get().max(running.get()));
+
+                let class = wmi_con.async_get_object(path).await;
+
+                running.set(running.get() - 1);
+
+                class
+            }),
+            2,
+        )
+        .await;
+
+        assert!(max_running.get() <= 2);
+
+        let class_names: Vec<_> = classes
+            .into_iter()
+            .map(|class| class.unwrap().class().unwrap())
+            .collect();
+
+        assert_eq!(class_names, paths);
+    }
 }
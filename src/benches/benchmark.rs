@@ -3,6 +3,7 @@
 use criterion::{criterion_group, Criterion};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use windows::core::{BSTR, VARIANT};
 use wmi::{COMLibrary, Variant, WMIConnection};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -107,9 +108,29 @@ fn get_services(con: &WMIConnection) {
     let _services: Vec<Service> = con.query().unwrap();
 }
 
+fn convert_string_variants(variants: &[VARIANT]) {
+    for variant in variants {
+        let _string = Variant::from_variant(variant).unwrap();
+    }
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let com = COMLibrary::new().unwrap();
 
+    // Measures the conversion of string properties, which used to copy each `BSTR` twice
+    // (through a `PROPVARIANT`) before decoding it, and now decode it in place.
+    c.bench_function("convert_string_variants", |b| {
+        let variants: Vec<VARIANT> = (0..1000)
+            .map(|i| {
+                VARIANT::from(BSTR::from(format!(
+                    r"C:\Windows\System32\svchost-{}.exe",
+                    i
+                )))
+            })
+            .collect();
+        b.iter(|| convert_string_variants(&variants))
+    });
+
     // baseline: 41ms
     c.bench_function("get_accounts", |b| {
         let wmi_con = WMIConnection::new(com).unwrap();
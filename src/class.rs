@@ -0,0 +1,421 @@
+use crate::{result_enumerator::IWbemClassWrapper, Variant, WMIConnection, WMIError, WMIResult};
+use serde::Deserialize;
+use std::{collections::HashMap, ptr};
+use windows::core::{w, BSTR, HRESULT, HSTRING, PCWSTR, VARIANT};
+use windows::Win32::System::Wmi::{
+    CIMTYPE_ENUMERATION, WBEM_E_CLASS_HAS_CHILDREN, WBEM_E_CLASS_HAS_INSTANCES, WBEM_E_NOT_FOUND,
+    WBEM_FLAG_CREATE_OR_UPDATE, WBEM_FLAG_USE_AMENDED_QUALIFIERS,
+};
+
+/// A builder for a new WMI class, created using [`WMIConnection::define_class`].
+///
+/// ```edition2021
+/// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+/// # use windows::Win32::System::Wmi::{CIM_STRING, CIM_UINT32};
+/// # #[cfg(not(feature = "test"))]
+/// # fn main() {}
+/// # #[cfg(feature = "test")]
+/// # fn main() -> WMIResult<()> {
+/// #   wmi::tests::ignore_access_denied(run())
+/// # }
+/// # fn run() -> WMIResult<()> {
+/// let wmi_con = WMIConnection::with_namespace_path("ROOT\\default", COMLibrary::new()?)?;
+///
+/// wmi_con
+///     .define_class("MyApp_Setting")
+///     .key_property("Name", CIM_STRING)
+///     .property("Value", CIM_UINT32)
+///     .create()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClassDefinition<'a> {
+    wmi_con: &'a WMIConnection,
+    class_name: String,
+    superclass: Option<String>,
+    properties: Vec<PropertyDefinition>,
+}
+
+#[derive(Debug, Clone)]
+struct PropertyDefinition {
+    name: String,
+    cim_type: CIMTYPE_ENUMERATION,
+    key: bool,
+}
+
+impl<'a> ClassDefinition<'a> {
+    /// Derive the new class from an existing class, inheriting its properties.
+    pub fn superclass(mut self, superclass: impl Into<String>) -> Self {
+        self.superclass = Some(superclass.into());
+        self
+    }
+
+    /// Add a property of the given CIM type (like `CIM_STRING` or `CIM_UINT32`).
+    ///
+    /// Array properties can be defined by combining the type with `CIM_FLAG_ARRAY`.
+    pub fn property(mut self, name: impl Into<String>, cim_type: CIMTYPE_ENUMERATION) -> Self {
+        self.properties.push(PropertyDefinition {
+            name: name.into(),
+            cim_type,
+            key: false,
+        });
+        self
+    }
+
+    /// Add a property which is marked with the `key` qualifier, and is used in the paths of the class's instances.
+    pub fn key_property(mut self, name: impl Into<String>, cim_type: CIMTYPE_ENUMERATION) -> Self {
+        self.properties.push(PropertyDefinition {
+            name: name.into(),
+            cim_type,
+            key: true,
+        });
+        self
+    }
+
+    /// Build the class definition object, without storing it in the repository.
+    ///
+    /// This can be used to further customize the class before calling [`WMIConnection::put_class`].
+    pub fn build(&self) -> WMIResult<IWbemClassWrapper> {
+        let class = match &self.superclass {
+            Some(superclass) => {
+                let superclass = self.wmi_con.get_raw_by_path(superclass)?;
+                IWbemClassWrapper::new(unsafe { superclass.inner.SpawnDerivedClass(0)? })
+            }
+            // An empty path returns a new, empty class.
+            None => self.wmi_con.get_raw_by_path("")?,
+        };
+
+        class.put_property("__CLASS", Variant::String(self.class_name.clone()))?;
+
+        for property in &self.properties {
+            let name = HSTRING::from(property.name.as_str());
+            let name = PCWSTR::from_raw(name.as_ptr());
+
+            unsafe {
+                // A NULL value is used, so the type must be specified.
+                class
+                    .inner
+                    .Put(name, 0, std::ptr::null(), property.cim_type.0)?;
+            }
+
+            if property.key {
+                let key = VARIANT::try_from(Variant::Bool(true))?;
+
+                unsafe {
+                    let qualifiers = class.inner.GetPropertyQualifierSet(name)?;
+                    qualifiers.Put(w!("key"), &key, 0)?;
+                }
+            }
+        }
+
+        Ok(class)
+    }
+
+    /// Build the class definition and store it in the repository using [`WMIConnection::put_class`].
+    pub fn create(self) -> WMIResult<()> {
+        let class = self.build()?;
+
+        self.wmi_con.put_class(&class)
+    }
+}
+
+///
+/// ### Class definition methods
+///
+impl WMIConnection {
+    /// Wrapper for WMI's [PutClass](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-putclass) function,
+    /// which creates or updates a class in the connection's namespace.
+    ///
+    /// Classes are persisted in the WMI repository, so this usually requires running as an administrator
+    /// (otherwise, a `WBEM_E_ACCESS_DENIED` error is returned).
+    /// See [`WMIConnection::define_class`] for a simple way to create a class definition.
+    pub fn put_class(&self, class_def: &IWbemClassWrapper) -> WMIResult<()> {
+        unsafe {
+            self.svc.PutClass(
+                &class_def.inner,
+                WBEM_FLAG_CREATE_OR_UPDATE,
+                &self.ctx.0,
+                None,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Wrapper for WMI's [DeleteClass](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-deleteclass) function,
+    /// which removes a class from the connection's namespace.
+    ///
+    /// For classes stored in the repository (like the ones created by [`WMIConnection::put_class`]),
+    /// the class's instances and subclasses are deleted as well. When the class's provider refuses to do so,
+    /// [`WMIError::ClassHasInstancesError`] or [`WMIError::ClassHasChildrenError`] is returned.
+    ///
+    /// Like [`WMIConnection::put_class`], this modifies the repository and usually requires running as an administrator.
+    pub fn delete_class(&self, class: &str) -> WMIResult<()> {
+        let res = unsafe {
+            self.svc
+                .DeleteClass(&BSTR::from(class), Default::default(), &self.ctx.0, None)
+        };
+
+        match res {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == HRESULT(WBEM_E_CLASS_HAS_INSTANCES.0) => {
+                Err(WMIError::ClassHasInstancesError(class.to_string()))
+            }
+            Err(e) if e.code() == HRESULT(WBEM_E_CLASS_HAS_CHILDREN.0) => {
+                Err(WMIError::ClassHasChildrenError(class.to_string()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get the `Description` qualifier of each property of a class, which documents the property.
+    ///
+    /// `Description` is an amended (localized) qualifier, so the class is fetched with `WBEM_FLAG_USE_AMENDED_QUALIFIERS`,
+    /// using the locale of the connection (see [`WMIConnection::with_namespace_and_locale`]).
+    /// Properties without a description are not included in the result.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let descriptions = wmi_con.property_descriptions("Win32_OperatingSystem")?;
+    ///
+    /// for (property, description) in descriptions {
+    ///     println!("/// {description}\n{property}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn property_descriptions(&self, class: &str) -> WMIResult<HashMap<String, String>> {
+        let mut class_obj = None;
+
+        unsafe {
+            self.svc.GetObject(
+                &BSTR::from(class),
+                WBEM_FLAG_USE_AMENDED_QUALIFIERS,
+                &self.ctx.0,
+                Some(&mut class_obj),
+                None,
+            )?;
+        }
+
+        let class = IWbemClassWrapper::new(class_obj.ok_or(WMIError::NullPointerResult)?);
+
+        let mut descriptions = HashMap::new();
+
+        for property in class.list_properties()? {
+            let name = HSTRING::from(&property);
+            let mut value = VARIANT::default();
+
+            let res = unsafe {
+                let qualifiers = class
+                    .inner
+                    .GetPropertyQualifierSet(PCWSTR::from_raw(name.as_ptr()))?;
+
+                qualifiers.Get(w!("Description"), 0, &mut value, ptr::null_mut())
+            };
+
+            match res {
+                Ok(()) => {}
+                Err(e) if e.code() == HRESULT(WBEM_E_NOT_FOUND.0) => continue,
+                Err(e) => return Err(e.into()),
+            }
+
+            if let Variant::String(description) = Variant::from_variant(&value)? {
+                descriptions.insert(property, description);
+            }
+        }
+
+        Ok(descriptions)
+    }
+
+    /// Get the locales in which the amended qualifiers of `class` (like the `Description` of its properties) are available,
+    /// as LCIDs (for example, `0x409` for English (United States)).
+    ///
+    /// The localized class definitions are stored in child namespaces named `MS_xxx` (where `xxx` is the hex LCID),
+    /// so each of these namespaces is checked for the class. Use [`WMIConnection::with_namespace_and_locale`]
+    /// to read the qualifiers in one of the returned locales.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// for lcid in wmi_con.class_locales("Win32_OperatingSystem")? {
+    ///     println!("MS_{:X}", lcid);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn class_locales(&self, class: &str) -> WMIResult<Vec<u32>> {
+        #[derive(Deserialize)]
+        struct __NAMESPACE {
+            Name: String,
+        }
+
+        let mut locales = vec![];
+
+        for namespace in self.query::<__NAMESPACE>()? {
+            let Some(lcid) = namespace_locale(&namespace.Name) else {
+                continue;
+            };
+
+            match self.open_namespace(&namespace.Name)?.get_raw_by_path(class) {
+                Ok(_) => locales.push(lcid),
+                Err(e) if e.is_not_found() => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        locales.sort_unstable();
+
+        Ok(locales)
+    }
+
+    /// Start defining a new class named `class_name`, which is created using [`ClassDefinition::create`].
+    ///
+    /// Like [`WMIConnection::put_class`], this modifies the repository and usually requires running as an administrator.
+    pub fn define_class(&self, class_name: impl Into<String>) -> ClassDefinition<'_> {
+        ClassDefinition {
+            wmi_con: self,
+            class_name: class_name.into(),
+            superclass: None,
+            properties: vec![],
+        }
+    }
+}
+
+/// Parse the LCID of a localized namespace name (like `ms_409`).
+fn namespace_locale(name: &str) -> Option<u32> {
+    if !name.get(..3)?.eq_ignore_ascii_case("ms_") {
+        return None;
+    }
+
+    u32::from_str_radix(&name[3..], 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::PutInstanceMode;
+    use crate::tests::fixtures::wmi_con;
+    use crate::tests::ignore_access_denied;
+    use crate::COMLibrary;
+    use serde::Serialize;
+    use windows::Win32::System::Wmi::{CIM_STRING, CIM_UINT32};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct WMIRS_TestClass {
+        Name: String,
+        Value: u32,
+    }
+
+    fn define_and_delete_class() -> WMIResult<()> {
+        let wmi_con =
+            WMIConnection::with_namespace_path("ROOT\\default", COMLibrary::without_security()?)?;
+
+        wmi_con
+            .define_class("WMIRS_TestClass")
+            .key_property("Name", CIM_STRING)
+            .property("Value", CIM_UINT32)
+            .create()?;
+
+        let class = wmi_con.get_raw_by_path("WMIRS_TestClass")?;
+        assert_eq!(class.class()?, "WMIRS_TestClass");
+        assert_eq!(class.list_properties()?, vec!["Name", "Value"]);
+
+        let instance = WMIRS_TestClass {
+            Name: "test".to_string(),
+            Value: 42,
+        };
+
+        let path = wmi_con.put_instance(&instance, PutInstanceMode::CreateOnly)?;
+        assert_eq!(path, r#"WMIRS_TestClass.Name="test""#);
+
+        let fetched: WMIRS_TestClass = wmi_con.get_by_path(&path)?;
+        assert_eq!(fetched, instance);
+
+        wmi_con
+            .define_class("WMIRS_TestSubclass")
+            .superclass("WMIRS_TestClass")
+            .property("Extra", CIM_STRING)
+            .create()?;
+
+        let subclass = wmi_con.get_raw_by_path("WMIRS_TestSubclass")?;
+        assert_eq!(subclass.list_properties()?.len(), 3);
+
+        match wmi_con.delete_class("WMIRS_TestClass") {
+            // Deleting a class in the repository also deletes its instances and subclasses.
+            Ok(()) => {}
+            Err(WMIError::ClassHasInstancesError(class)) => {
+                assert_eq!(class, "WMIRS_TestClass");
+
+                wmi_con.delete_instance(&path)?;
+                wmi_con.delete_class("WMIRS_TestSubclass")?;
+                wmi_con.delete_class("WMIRS_TestClass")?;
+            }
+            Err(e) => return Err(e),
+        }
+
+        for class in ["WMIRS_TestClass", "WMIRS_TestSubclass"] {
+            match wmi_con.get_raw_by_path(class) {
+                Err(WMIError::HResultError { hres, .. }) => assert_eq!(hres, WBEM_E_NOT_FOUND.0),
+                res => panic!("Unexpected result {:?}", res),
+            }
+        }
+
+        match wmi_con.delete_class("WMIRS_TestClass") {
+            Err(WMIError::HResultError { hres, .. }) => assert_eq!(hres, WBEM_E_NOT_FOUND.0),
+            res => panic!("Unexpected result {:?}", res),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_can_define_and_delete_a_class() {
+        // Modifying the repository requires running as admin.
+        ignore_access_denied(define_and_delete_class()).unwrap();
+    }
+
+    #[test]
+    fn it_can_get_property_descriptions() {
+        let wmi_con = wmi_con();
+
+        let descriptions = wmi_con
+            .property_descriptions("Win32_OperatingSystem")
+            .unwrap();
+
+        assert!(!descriptions["Caption"].is_empty());
+        assert!(!descriptions["FreePhysicalMemory"].is_empty());
+    }
+
+    #[test]
+    fn it_parses_namespace_locales() {
+        assert_eq!(namespace_locale("ms_409"), Some(0x409));
+        assert_eq!(namespace_locale("MS_40C"), Some(0x40c));
+        assert_eq!(namespace_locale("ms_"), None);
+        assert_eq!(namespace_locale("ms_xyz"), None);
+        assert_eq!(namespace_locale("Security"), None);
+    }
+
+    #[test]
+    fn it_can_get_class_locales() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize)]
+        struct Win32_OperatingSystem {
+            OSLanguage: u32,
+        }
+
+        let os: Win32_OperatingSystem = wmi_con.get().unwrap();
+
+        let locales = wmi_con.class_locales("Win32_OperatingSystem").unwrap();
+
+        assert!(locales.contains(&os.OSLanguage));
+
+        let locales = wmi_con.class_locales("Win32_NoSuchClass").unwrap();
+
+        assert!(locales.is_empty());
+    }
+}
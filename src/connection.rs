@@ -2,15 +2,21 @@ use crate::context::WMIContext;
 use crate::utils::WMIResult;
 use crate::WMIError;
 use log::debug;
+use std::cell::OnceCell;
+use std::fmt;
 use std::marker::PhantomData;
+use std::rc::Rc;
 use windows::core::BSTR;
 use windows::Win32::Foundation::RPC_E_TOO_LATE;
 use windows::Win32::System::Com::{
-    CoCreateInstance, CoSetProxyBlanket, CLSCTX_INPROC_SERVER, RPC_C_AUTHN_LEVEL_CALL,
+    CoCreateInstance, CoQueryProxyBlanket, CoSetProxyBlanket, CLSCTX_INPROC_SERVER,
+    RPC_C_AUTHN_LEVEL, RPC_C_AUTHN_LEVEL_CALL, RPC_C_AUTHN_LEVEL_CONNECT, RPC_C_AUTHN_LEVEL_NONE,
+    RPC_C_AUTHN_LEVEL_PKT, RPC_C_AUTHN_LEVEL_PKT_INTEGRITY, RPC_C_AUTHN_LEVEL_PKT_PRIVACY,
+    RPC_C_IMP_LEVEL, RPC_C_IMP_LEVEL_ANONYMOUS, RPC_C_IMP_LEVEL_DELEGATE, RPC_C_IMP_LEVEL_IDENTIFY,
 };
 use windows::Win32::System::Com::{
-    CoInitializeEx, CoInitializeSecurity, COINIT_MULTITHREADED, EOAC_NONE,
-    RPC_C_AUTHN_LEVEL_DEFAULT, RPC_C_IMP_LEVEL_IMPERSONATE,
+    CoInitializeEx, CoInitializeSecurity, COINIT, COINIT_APARTMENTTHREADED, COINIT_MULTITHREADED,
+    EOAC_NONE, RPC_C_AUTHN_LEVEL_DEFAULT, RPC_C_IMP_LEVEL_IMPERSONATE,
 };
 use windows::Win32::System::Rpc::{RPC_C_AUTHN_WINNT, RPC_C_AUTHZ_NONE};
 use windows::Win32::System::Wmi::{
@@ -34,12 +40,35 @@ use windows::Win32::System::Wmi::{
 ///     WMIConnection::new(com_lib).unwrap()
 /// }
 /// ```
+///
+/// [`WMIThreadContext`] does this for you.
 #[derive(Clone, Copy, Debug)]
 pub struct COMLibrary {
     // Force the type to be `!Send`, as each thread must be initialized separately.
     _phantom: PhantomData<*mut ()>,
 }
 
+/// The COM apartment model of a thread, used by [`COMLibrary::new_with_apartment`].
+///
+/// See [Processes, Threads, and Apartments](https://learn.microsoft.com/en-us/windows/win32/com/processes--threads--and-apartments).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ApartmentModel {
+    /// A multithreaded apartment (MTA), using `COINIT_MULTITHREADED`. This is the default.
+    #[default]
+    MultiThreaded,
+    /// A single-threaded apartment (STA), using `COINIT_APARTMENTTHREADED`.
+    SingleThreaded,
+}
+
+impl From<ApartmentModel> for COINIT {
+    fn from(model: ApartmentModel) -> Self {
+        match model {
+            ApartmentModel::MultiThreaded => COINIT_MULTITHREADED,
+            ApartmentModel::SingleThreaded => COINIT_APARTMENTTHREADED,
+        }
+    }
+}
+
 /// Initialize COM.
 ///
 /// `CoUninitialize` will NOT be called when dropped.
@@ -49,12 +78,36 @@ impl COMLibrary {
     /// `CoInitialize`s the COM library for use by the calling thread.
     ///
     pub fn new() -> WMIResult<Self> {
-        let instance = Self::without_security()?;
+        Self::new_with_apartment(ApartmentModel::MultiThreaded)
+    }
+
+    /// `CoInitialize`s the COM library for use by the calling thread, using the given apartment model.
+    ///
+    /// This is useful in hosts which already run an STA (like GUI applications), since a thread cannot
+    /// change its apartment model once initialized.
+    ///
+    /// Note that in an STA, the sinks used by the async query and notification methods
+    /// are called back on the thread that created them, which requires that thread to pump window messages.
+    /// If it doesn't, async results will never arrive, so prefer the (default) MTA when possible.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # std::thread::spawn(|| -> WMIResult<()> {
+    /// let com_lib = COMLibrary::new_with_apartment(ApartmentModel::SingleThreaded)?;
+    /// let wmi_con = WMIConnection::new(com_lib)?;
+    /// # Ok(())
+    /// # }).join().unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_with_apartment(model: ApartmentModel) -> WMIResult<Self> {
+        let instance = Self::init(model)?;
 
         match instance.init_security() {
             Ok(()) => {}
             // Security was already initialized, this is fine
-            Err(WMIError::HResultError { hres }) if hres == RPC_E_TOO_LATE.0 => {}
+            Err(WMIError::HResultError { hres, .. }) if hres == RPC_E_TOO_LATE.0 => {}
             Err(err) => return Err(err),
         }
 
@@ -64,7 +117,37 @@ impl COMLibrary {
     /// `CoInitialize`s the COM library for use by the calling thread, but without setting the security context.
     ///
     pub fn without_security() -> WMIResult<Self> {
-        unsafe { CoInitializeEx(None, COINIT_MULTITHREADED).ok()? }
+        Self::init(ApartmentModel::MultiThreaded)
+    }
+
+    /// `CoInitialize`s the COM library for use by the calling thread, for processes which already called `CoInitializeSecurity`.
+    ///
+    /// `CoInitializeSecurity` can only be called once per process, so when embedding in a larger application
+    /// which sets up its own security context, this skips it instead of silently keeping the existing one.
+    /// Note that the existing security context must allow impersonation, or WMI calls will fail with `WBEM_E_ACCESS_DENIED`.
+    ///
+    /// Unlike [`COMLibrary::assume_initialized`], this still calls `CoInitializeEx`, so it is safe to use
+    /// on threads which were not initialized yet (and it is fine to call it multiple times on the same thread).
+    /// This is the same as [`COMLibrary::without_security`], so it initializes the thread as an MTA, and fails with
+    /// `RPC_E_CHANGED_MODE` on threads which the host already initialized as an STA (like the UI thread of a GUI application).
+    /// On such threads, use [`COMLibrary::assume_initialized`] instead.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # let _host_com = COMLibrary::new()?;
+    /// // The host application already initialized COM security.
+    /// let com_lib = COMLibrary::with_security_already_initialized()?;
+    /// let wmi_con = WMIConnection::new(com_lib)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_security_already_initialized() -> WMIResult<Self> {
+        Self::without_security()
+    }
+
+    fn init(model: ApartmentModel) -> WMIResult<Self> {
+        unsafe { CoInitializeEx(None, model.into()).ok()? }
 
         let instance = Self {
             _phantom: PhantomData,
@@ -123,11 +206,91 @@ impl COMLibrary {
 /// ```
 fn _test_com_lib_not_send(_s: impl Send) {}
 
+thread_local! {
+    static THREAD_COM_LIB: OnceCell<COMLibrary> = const { OnceCell::new() };
+}
+
+/// Hands out connections for the current thread, initializing COM (using [`COMLibrary::new`]) the first time it is used on each thread.
+///
+/// This replaces the `thread_local!` pattern shown in the docs of [`COMLibrary`].
+/// Like `COMLibrary`, it is `!Send`, so it cannot be moved to a thread which was not initialized.
+///
+/// ```edition2018
+/// # fn main() -> wmi::WMIResult<()> {
+/// # use wmi::*;
+/// let context = WMIThreadContext::current()?;
+///
+/// let cimv2_con = context.connection()?;
+/// let storage_con = context.connection_with_namespace_path("ROOT\\Microsoft\\Windows\\Storage")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct WMIThreadContext {
+    com_lib: COMLibrary,
+}
+
+impl WMIThreadContext {
+    /// Get the context of the current thread, initializing COM if this is the first time it is used on this thread.
+    ///
+    /// COM is initialized as a multithreaded apartment (MTA), so this fails with `RPC_E_CHANGED_MODE` on threads
+    /// which were already initialized as a single-threaded apartment (like the UI thread of a GUI application).
+    /// On such threads, use [`COMLibrary::new_with_apartment`] with [`ApartmentModel::SingleThreaded`] instead.
+    pub fn current() -> WMIResult<Self> {
+        let com_lib = THREAD_COM_LIB.with(|cell| -> WMIResult<_> {
+            if let Some(com_lib) = cell.get() {
+                return Ok(*com_lib);
+            }
+
+            let com_lib = COMLibrary::new()?;
+            let _ = cell.set(com_lib);
+
+            Ok(com_lib)
+        })?;
+
+        Ok(Self { com_lib })
+    }
+
+    /// The `COMLibrary` of the current thread.
+    pub fn com_library(&self) -> COMLibrary {
+        self.com_lib
+    }
+
+    /// Creates a connection with a default `CIMV2` namespace path (see [`WMIConnection::new`]).
+    pub fn connection(&self) -> WMIResult<WMIConnection> {
+        WMIConnection::new(self.com_lib)
+    }
+
+    /// Creates a connection with the given namespace path (see [`WMIConnection::with_namespace_path`]).
+    pub fn connection_with_namespace_path(&self, namespace_path: &str) -> WMIResult<WMIConnection> {
+        WMIConnection::with_namespace_path(namespace_path, self.com_lib)
+    }
+}
+
+/// A function which is called with the text of every query executed by a connection, and returns the query to execute.
+/// See [`WMIConnection::set_query_hook`].
+#[derive(Clone)]
+pub(crate) struct QueryHook(Rc<dyn Fn(&str) -> String>);
+
+impl QueryHook {
+    pub(crate) fn call(&self, query: &str) -> String {
+        (self.0)(query)
+    }
+}
+
+impl fmt::Debug for QueryHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QueryHook")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct WMIConnection {
     _com_con: COMLibrary,
+    loc: IWbemLocator,
     pub svc: IWbemServices,
     pub(crate) ctx: WMIContext,
+    pub(crate) query_hook: Option<QueryHook>,
 }
 
 /// A connection to the local WMI provider, which provides querying capabilities.
@@ -151,21 +314,248 @@ impl WMIConnection {
     /// # }
     /// ```
     pub fn with_namespace_path(namespace_path: &str, com_lib: COMLibrary) -> WMIResult<Self> {
+        Self::connect(namespace_path, None, com_lib)
+    }
+
+    /// Creates a connection with the given namespace path and locale.
+    ///
+    /// The locale is passed to `ConnectServer` and uses the `MS_xxx` format,
+    /// where `xxx` is the hex LCID of the locale (for example, `MS_409` for English (United States)).
+    ///
+    /// The locale only affects localized data, such as amended qualifiers (class and property descriptions).
+    /// Instance values are not translated.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// let wmi_con = WMIConnection::with_namespace_and_locale("ROOT\\CIMV2", "MS_409", COMLibrary::new()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_namespace_and_locale(
+        namespace_path: &str,
+        locale: &str,
+        com_lib: COMLibrary,
+    ) -> WMIResult<Self> {
+        Self::connect(namespace_path, Some(locale), com_lib)
+    }
+
+    fn connect(namespace_path: &str, locale: Option<&str>, com_lib: COMLibrary) -> WMIResult<Self> {
         let loc = create_locator()?;
-        let svc = create_services(&loc, namespace_path)?;
+        let svc = create_services(&loc, namespace_path, locale)?;
         let ctx = WMIContext::new()?;
 
         let this = Self {
             _com_con: com_lib,
+            loc,
             svc,
             ctx,
+            query_hook: None,
         };
 
         this.set_proxy()?;
         Ok(this)
     }
 
+    /// Creates a connection using a WMI moniker, like `winmgmts:\\.\root\cimv2`
+    /// or `winmgmts:{impersonationLevel=impersonate}!\\.\root\cimv2`.
+    ///
+    /// The moniker has the format `winmgmts:[{security_settings}][[locale=localeID]][!][namespace_path]`
+    /// (see [Constructing a Moniker String](https://learn.microsoft.com/en-us/windows/win32/wmisdk/constructing-a-moniker-string)),
+    /// where the security settings can be `impersonationLevel` and `authenticationLevel`.
+    /// If the namespace is omitted, `ROOT\CIMV2` is used.
+    ///
+    /// The moniker is parsed by this function, and the connection is created like [`WMIConnection::with_namespace_and_locale`] (using `ConnectServer`)
+    /// instead of `CoGetObject`, since binding a moniker with `CoGetObject` returns the scripting `SWbemServices` object
+    /// and not the `IWbemServices` interface used by this crate.
+    ///
+    /// Monikers of object paths (like `winmgmts:Win32_Process.Handle="0"`) are not supported,
+    /// and monikers with privileges (like `{(Shutdown)}`) or an `authority` are rejected with a [`WMIError::InvalidMonikerError`].
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// let wmi_con = WMIConnection::from_moniker(
+    ///     r"winmgmts:{impersonationLevel=impersonate}!\\.\root\cimv2",
+    ///     COMLibrary::new()?,
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_moniker(moniker: &str, com_lib: COMLibrary) -> WMIResult<Self> {
+        let moniker = Moniker::parse(moniker)?;
+
+        let this = Self::connect(&moniker.namespace_path, moniker.locale.as_deref(), com_lib)?;
+
+        this.set_proxy_with_levels(moniker.authentication_level, moniker.impersonation_level)?;
+        Ok(this)
+    }
+
+    /// Opens a namespace relative to the namespace of this connection,
+    /// and returns a new connection to it.
+    ///
+    /// The new connection starts with a copy of the context values of this connection
+    /// (see [`WMIConnection::set_context_value`]), and uses the same authentication and impersonation levels.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// let root_con = WMIConnection::with_namespace_path("ROOT", COMLibrary::new()?)?;
+    /// let cimv2_con = root_con.open_namespace("CIMV2")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open_namespace(&self, namespace_path: &str) -> WMIResult<Self> {
+        let namespace_path = BSTR::from(namespace_path);
+        let mut svc = None;
+
+        unsafe {
+            self.svc.OpenNamespace(
+                &namespace_path,
+                Default::default(),
+                &self.ctx.0,
+                Some(&mut svc),
+                None,
+            )?;
+        }
+
+        let svc = svc.ok_or(WMIError::NullPointerResult)?;
+
+        let this = Self {
+            _com_con: self._com_con,
+            loc: self.loc.clone(),
+            svc,
+            ctx: self.ctx.copy()?,
+            query_hook: None,
+        };
+
+        let (authentication_level, impersonation_level) = self.proxy_levels()?;
+        this.set_proxy_with_levels(authentication_level, impersonation_level)?;
+        Ok(this)
+    }
+
+    /// Creates a connection to another namespace (given as a full path),
+    /// reusing the COM library and the locator of this connection.
+    ///
+    /// This is cheaper than creating a new connection with [`WMIConnection::with_namespace_path`].
+    /// The new connection uses the default locale, starts with a copy of the context values of this connection
+    /// (see [`WMIConnection::set_context_value`]), and uses the same authentication and impersonation levels.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// let cimv2_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let std_cimv2_con = cimv2_con.with_namespace("ROOT\\StandardCimv2")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_namespace(&self, namespace_path: &str) -> WMIResult<Self> {
+        let svc = create_services(&self.loc, namespace_path, None)?;
+
+        let this = Self {
+            _com_con: self._com_con,
+            loc: self.loc.clone(),
+            svc,
+            ctx: self.ctx.copy()?,
+            query_hook: None,
+        };
+
+        let (authentication_level, impersonation_level) = self.proxy_levels()?;
+        this.set_proxy_with_levels(authentication_level, impersonation_level)?;
+        Ok(this)
+    }
+
+    /// The underlying [`IWbemServices`] of this connection, for calling WMI functions which are not wrapped by this crate.
+    ///
+    /// This is an escape hatch: the interface is used as-is, so callers are responsible for passing valid arguments
+    /// and for not changing the state of the connection (e.g. by calling `CoSetProxyBlanket` with different settings).
+    ///
+    /// ```edition2021
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use windows::core::BSTR;
+    /// # use windows::Win32::System::Wmi::WBEM_FLAG_RETURN_WBEM_COMPLETE;
+    /// let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// let mut class = None;
+    /// unsafe {
+    ///     wmi_con.services().GetObject(
+    ///         &BSTR::from("Win32_OperatingSystem"),
+    ///         WBEM_FLAG_RETURN_WBEM_COMPLETE,
+    ///         None,
+    ///         Some(&mut class),
+    ///         None,
+    ///     )?;
+    /// }
+    /// assert!(class.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn services(&self) -> &IWbemServices {
+        &self.svc
+    }
+
+    /// Registers a hook which is called before every query executed by this connection
+    /// (by [`WMIConnection::exec_query_native_wrapper`] and the functions using it, like `raw_query` and `filtered_query`).
+    ///
+    /// The hook is called with the text of the query, and returns the query which will be executed,
+    /// so it can be used for logging, metrics or rewriting queries.
+    /// Registering a hook replaces the previous one.
+    ///
+    /// The hook is not copied to connections created by [`WMIConnection::open_namespace`] or [`WMIConnection::with_namespace`].
+    ///
+    /// ```edition2021
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use std::collections::HashMap;
+    /// let mut wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// wmi_con.set_query_hook(Box::new(|query| {
+    ///     println!("Executing: {}", query);
+    ///     query.to_string()
+    /// }));
+    ///
+    /// let results: Vec<HashMap<String, Variant>> = wmi_con.raw_query("SELECT Caption FROM Win32_OperatingSystem")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_query_hook(&mut self, hook: Box<dyn Fn(&str) -> String>) {
+        self.query_hook = Some(QueryHook(Rc::from(hook)));
+    }
+
     fn set_proxy(&self) -> WMIResult<()> {
+        self.set_proxy_with_levels(RPC_C_AUTHN_LEVEL_CALL, RPC_C_IMP_LEVEL_IMPERSONATE)
+    }
+
+    /// The authentication and impersonation levels of the proxy of this connection.
+    fn proxy_levels(&self) -> WMIResult<(RPC_C_AUTHN_LEVEL, RPC_C_IMP_LEVEL)> {
+        let mut authentication_level = 0;
+        let mut impersonation_level = 0;
+
+        unsafe {
+            CoQueryProxyBlanket(
+                &self.svc,
+                None,
+                None,
+                None,
+                Some(&mut authentication_level as *mut u32),
+                Some(&mut impersonation_level as *mut u32),
+                None,
+                None,
+            )?;
+        }
+
+        Ok((
+            RPC_C_AUTHN_LEVEL(authentication_level),
+            RPC_C_IMP_LEVEL(impersonation_level),
+        ))
+    }
+
+    fn set_proxy_with_levels(
+        &self,
+        authentication_level: RPC_C_AUTHN_LEVEL,
+        impersonation_level: RPC_C_IMP_LEVEL,
+    ) -> WMIResult<()> {
         debug!("Calling CoSetProxyBlanket");
 
         unsafe {
@@ -174,10 +564,10 @@ impl WMIConnection {
                 RPC_C_AUTHN_WINNT, // RPC_C_AUTHN_xxx
                 RPC_C_AUTHZ_NONE,  // RPC_C_AUTHZ_xxx
                 None,
-                RPC_C_AUTHN_LEVEL_CALL,      // RPC_C_AUTHN_LEVEL_xxx
-                RPC_C_IMP_LEVEL_IMPERSONATE, // RPC_C_IMP_LEVEL_xxx
-                None,                        // client identity
-                EOAC_NONE,                   // proxy capabilities
+                authentication_level, // RPC_C_AUTHN_LEVEL_xxx
+                impersonation_level,  // RPC_C_IMP_LEVEL_xxx
+                None,                 // client identity
+                EOAC_NONE,            // proxy capabilities
             )?;
         }
 
@@ -185,6 +575,117 @@ impl WMIConnection {
     }
 }
 
+/// The parts of a `winmgmts:` moniker which are supported by [`WMIConnection::from_moniker`].
+#[derive(Debug, PartialEq)]
+struct Moniker {
+    namespace_path: String,
+    locale: Option<String>,
+    authentication_level: RPC_C_AUTHN_LEVEL,
+    impersonation_level: RPC_C_IMP_LEVEL,
+}
+
+impl Moniker {
+    const PREFIX: &'static str = "winmgmts:";
+
+    fn parse(moniker: &str) -> WMIResult<Self> {
+        let invalid =
+            |reason: &str| WMIError::InvalidMonikerError(format!("{:?}: {}", moniker, reason));
+
+        let mut rest = match moniker.get(..Self::PREFIX.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(Self::PREFIX) => {
+                &moniker[Self::PREFIX.len()..]
+            }
+            _ => return Err(invalid("expected the moniker to start with `winmgmts:`")),
+        };
+
+        let mut parsed = Moniker {
+            namespace_path: "ROOT\\CIMV2".to_string(),
+            locale: None,
+            authentication_level: RPC_C_AUTHN_LEVEL_CALL,
+            impersonation_level: RPC_C_IMP_LEVEL_IMPERSONATE,
+        };
+
+        if let Some(settings) = rest.strip_prefix('{') {
+            let end = settings
+                .find('}')
+                .ok_or_else(|| invalid("missing `}` after the security settings"))?;
+
+            for setting in settings[..end].split(',').map(str::trim) {
+                let (key, value) = setting.split_once('=').ok_or_else(|| {
+                    invalid(&format!("unsupported security setting `{}`", setting))
+                })?;
+                let (key, value) = (key.trim(), value.trim());
+
+                if key.eq_ignore_ascii_case("impersonationLevel") {
+                    parsed.impersonation_level = match value.to_ascii_lowercase().as_str() {
+                        "anonymous" => RPC_C_IMP_LEVEL_ANONYMOUS,
+                        "identify" => RPC_C_IMP_LEVEL_IDENTIFY,
+                        "impersonate" => RPC_C_IMP_LEVEL_IMPERSONATE,
+                        "delegate" => RPC_C_IMP_LEVEL_DELEGATE,
+                        _ => {
+                            return Err(invalid(&format!(
+                                "unknown impersonation level `{}`",
+                                value
+                            )))
+                        }
+                    };
+                } else if key.eq_ignore_ascii_case("authenticationLevel") {
+                    parsed.authentication_level = match value.to_ascii_lowercase().as_str() {
+                        "default" => RPC_C_AUTHN_LEVEL_DEFAULT,
+                        "none" => RPC_C_AUTHN_LEVEL_NONE,
+                        "connect" => RPC_C_AUTHN_LEVEL_CONNECT,
+                        "call" => RPC_C_AUTHN_LEVEL_CALL,
+                        "pkt" => RPC_C_AUTHN_LEVEL_PKT,
+                        "pktintegrity" => RPC_C_AUTHN_LEVEL_PKT_INTEGRITY,
+                        "pktprivacy" => RPC_C_AUTHN_LEVEL_PKT_PRIVACY,
+                        _ => {
+                            return Err(invalid(&format!(
+                                "unknown authentication level `{}`",
+                                value
+                            )))
+                        }
+                    };
+                } else {
+                    return Err(invalid(&format!(
+                        "unsupported security setting `{}`",
+                        setting
+                    )));
+                }
+            }
+
+            rest = &settings[end + 1..];
+        }
+
+        if let Some(locale) = rest.strip_prefix('[') {
+            let end = locale
+                .find(']')
+                .ok_or_else(|| invalid("missing `]` after the locale"))?;
+
+            let (key, value) = locale[..end]
+                .split_once('=')
+                .filter(|(key, _)| key.trim().eq_ignore_ascii_case("locale"))
+                .ok_or_else(|| invalid("expected `[locale=localeID]`"))?;
+
+            parsed.locale = Some(value.trim().to_string());
+            rest = &locale[end + 1..];
+        }
+
+        let rest = rest.strip_prefix('!').unwrap_or(rest);
+
+        if rest.contains(':') {
+            return Err(invalid(
+                "object paths are not supported, only namespace paths",
+            ));
+        }
+
+        if !rest.is_empty() {
+            parsed.namespace_path = rest.replace('/', "\\");
+        }
+
+        Ok(parsed)
+    }
+}
+
 fn create_locator() -> WMIResult<IWbemLocator> {
     debug!("Calling CoCreateInstance for CLSID_WbemLocator");
 
@@ -195,17 +696,22 @@ fn create_locator() -> WMIResult<IWbemLocator> {
     Ok(loc)
 }
 
-fn create_services(loc: &IWbemLocator, path: &str) -> WMIResult<IWbemServices> {
+fn create_services(
+    loc: &IWbemLocator,
+    path: &str,
+    locale: Option<&str>,
+) -> WMIResult<IWbemServices> {
     debug!("Calling ConnectServer");
 
     let object_path_bstr = BSTR::from(path);
+    let locale_bstr = locale.map(BSTR::from).unwrap_or_default();
 
     let svc = unsafe {
         loc.ConnectServer(
             &object_path_bstr,
             &BSTR::new(),
             &BSTR::new(),
-            &BSTR::new(),
+            &locale_bstr,
             WBEM_FLAG_CONNECT_USE_MAX_WAIT.0,
             &BSTR::new(),
             None,
@@ -222,6 +728,56 @@ fn create_services(loc: &IWbemLocator, path: &str) -> WMIResult<IWbemServices> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use windows::Win32::System::Com::{
+        CoGetApartmentType, APTTYPE, APTTYPEQUALIFIER, APTTYPE_MAINSTA, APTTYPE_MTA, APTTYPE_STA,
+    };
+
+    /// Assert that `wmi_con` is connected to `namespace`, using the `__NAMESPACE` system property of a class in it.
+    fn assert_connected_to(wmi_con: &WMIConnection, namespace: &str) {
+        let class = wmi_con.get_raw_by_path("__SystemClass").unwrap();
+        let connected_namespace: String = class
+            .get_property("__NAMESPACE")
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        assert!(
+            connected_namespace.eq_ignore_ascii_case(namespace),
+            "Connected to {} instead of {}",
+            connected_namespace,
+            namespace
+        );
+    }
+
+    #[test]
+    fn it_can_get_connections_from_the_thread_context() {
+        let context = WMIThreadContext::current().unwrap();
+
+        let first = context.connection().unwrap();
+        let second = WMIThreadContext::current()
+            .unwrap()
+            .connection_with_namespace_path("ROOT\\CIMV2")
+            .unwrap();
+
+        for wmi_con in [first, second] {
+            let os = wmi_con
+                .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+                .unwrap();
+
+            assert_eq!(os.class().unwrap(), "Win32_OperatingSystem");
+        }
+
+        // Other threads are initialized separately.
+        std::thread::spawn(|| {
+            let wmi_con = WMIThreadContext::current().unwrap().connection().unwrap();
+
+            wmi_con
+                .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+                .unwrap();
+        })
+        .join()
+        .unwrap();
+    }
 
     #[test]
     fn it_can_create_multiple_connections() {
@@ -234,4 +790,161 @@ mod tests {
             let _ = WMIConnection::new(com_lib);
         }
     }
+
+    #[test]
+    fn it_can_reuse_an_existing_security_initialization() {
+        let _host_com_lib = COMLibrary::new().unwrap();
+
+        for _ in 0..2 {
+            let com_lib = COMLibrary::with_security_already_initialized().unwrap();
+            let wmi_con = WMIConnection::new(com_lib).unwrap();
+
+            assert_connected_to(&wmi_con, "ROOT\\CIMV2");
+
+            // The proxy of the connection is still set up, even though `CoInitializeSecurity` was skipped.
+            let (_, impersonation_level) = wmi_con.proxy_levels().unwrap();
+            assert_eq!(impersonation_level, RPC_C_IMP_LEVEL_IMPERSONATE);
+        }
+    }
+
+    #[test]
+    fn it_can_connect_with_apartment_model() {
+        for model in [
+            ApartmentModel::MultiThreaded,
+            ApartmentModel::SingleThreaded,
+        ] {
+            // Each model is used in a new thread, since a thread's apartment model cannot be changed.
+            std::thread::spawn(move || {
+                let com_lib = COMLibrary::new_with_apartment(model).unwrap();
+
+                let mut apartment_type = APTTYPE::default();
+                let mut apartment_qualifier = APTTYPEQUALIFIER::default();
+                unsafe {
+                    CoGetApartmentType(&mut apartment_type, &mut apartment_qualifier).unwrap();
+                }
+
+                match model {
+                    ApartmentModel::MultiThreaded => assert_eq!(apartment_type, APTTYPE_MTA),
+                    ApartmentModel::SingleThreaded => {
+                        assert!(apartment_type == APTTYPE_STA || apartment_type == APTTYPE_MAINSTA)
+                    }
+                }
+
+                let wmi_con = WMIConnection::new(com_lib).unwrap();
+                assert_connected_to(&wmi_con, "ROOT\\CIMV2");
+            })
+            .join()
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn it_can_open_a_child_namespace() {
+        let com_lib = COMLibrary::new().unwrap();
+        let root_con = WMIConnection::with_namespace_path("ROOT", com_lib).unwrap();
+
+        let cimv2_con = root_con.open_namespace("CIMV2").unwrap();
+
+        assert_connected_to(&root_con, "ROOT");
+        assert_connected_to(&cimv2_con, "ROOT\\CIMV2");
+    }
+
+    #[test]
+    fn it_can_switch_namespaces() {
+        let com_lib = COMLibrary::new().unwrap();
+        let cimv2_con = WMIConnection::with_namespace_path("ROOT\\CIMV2", com_lib).unwrap();
+
+        let std_cimv2_con = cimv2_con.with_namespace("ROOT\\StandardCimv2").unwrap();
+        assert_connected_to(&std_cimv2_con, "ROOT\\StandardCimv2");
+
+        // The original connection is not affected.
+        assert_connected_to(&cimv2_con, "ROOT\\CIMV2");
+
+        let cimv2_again_con = std_cimv2_con.with_namespace("ROOT\\CIMV2").unwrap();
+        assert_connected_to(&cimv2_again_con, "ROOT\\CIMV2");
+    }
+
+    #[test]
+    fn it_parses_monikers() {
+        let moniker = Moniker::parse(r"winmgmts:\\.\root\cimv2").unwrap();
+        assert_eq!(moniker.namespace_path, r"\\.\root\cimv2");
+        assert_eq!(moniker.locale, None);
+        assert_eq!(moniker.authentication_level, RPC_C_AUTHN_LEVEL_CALL);
+        assert_eq!(moniker.impersonation_level, RPC_C_IMP_LEVEL_IMPERSONATE);
+
+        let moniker = Moniker::parse(
+            "WinMgmts:{impersonationLevel=identify, authenticationLevel=pktPrivacy}[locale=ms_409]!//./root/StandardCimv2",
+        )
+        .unwrap();
+        assert_eq!(moniker.namespace_path, r"\\.\root\StandardCimv2");
+        assert_eq!(moniker.locale.as_deref(), Some("ms_409"));
+        assert_eq!(moniker.authentication_level, RPC_C_AUTHN_LEVEL_PKT_PRIVACY);
+        assert_eq!(moniker.impersonation_level, RPC_C_IMP_LEVEL_IDENTIFY);
+
+        let moniker = Moniker::parse("winmgmts:{impersonationLevel=impersonate}").unwrap();
+        assert_eq!(moniker.namespace_path, "ROOT\\CIMV2");
+
+        for invalid in [
+            r"\\.\root\cimv2",
+            r"winmgmt:\\.\root\cimv2",
+            "winmgmts:{impersonationLevel=impersonate",
+            "winmgmts:{impersonationLevel=everything}",
+            "winmgmts:{(Security)}",
+            "winmgmts:{authority=kerberos:server}",
+            "winmgmts:{impersonationLevel=impersonate,(Shutdown)}",
+            r"winmgmts:{impersonationLevel=impersonate,authority=ntlmdomain:DOMAIN}!\\server\root\cimv2",
+            "winmgmts:[ms_409]",
+            r#"winmgmts:Win32_Process.Handle="0""#,
+        ] {
+            assert!(
+                matches!(
+                    Moniker::parse(invalid),
+                    Err(WMIError::InvalidMonikerError(_))
+                ),
+                "{} should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn it_can_create_connection_from_moniker() {
+        for (moniker, namespace) in [
+            ("winmgmts:", "ROOT\\CIMV2"),
+            (
+                r"winmgmts:{impersonationLevel=impersonate}!\\.\root\StandardCimv2",
+                "ROOT\\StandardCimv2",
+            ),
+        ] {
+            let com_lib = COMLibrary::new().unwrap();
+            let wmi_con = WMIConnection::from_moniker(moniker, com_lib).unwrap();
+
+            assert_connected_to(&wmi_con, namespace);
+        }
+
+        // The security settings of the moniker are used for the proxy of the connection.
+        let com_lib = COMLibrary::new().unwrap();
+        let wmi_con =
+            WMIConnection::from_moniker(r"winmgmts:{impersonationLevel=identify}", com_lib)
+                .unwrap();
+
+        let (_, impersonation_level) = wmi_con.proxy_levels().unwrap();
+        assert_eq!(impersonation_level, RPC_C_IMP_LEVEL_IDENTIFY);
+    }
+
+    #[test]
+    fn it_can_create_connection_with_locale() {
+        let com_lib = COMLibrary::new().unwrap();
+        let wmi_con =
+            WMIConnection::with_namespace_and_locale("ROOT\\CIMV2", "MS_409", com_lib).unwrap();
+
+        assert_connected_to(&wmi_con, "ROOT\\CIMV2");
+
+        // Amended qualifiers (like property descriptions) are read using the locale of the connection,
+        // so they are in English regardless of the locale of the machine.
+        let descriptions = wmi_con
+            .property_descriptions("Win32_OperatingSystem")
+            .unwrap();
+        assert!(descriptions["Caption"].starts_with("The Caption property"));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{WMIConnection, WMIResult};
+use crate::{Variant, WMIConnection, WMIResult};
 use log::debug;
 use windows::Win32::System::{
     Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
@@ -41,6 +41,13 @@ impl WMIContext {
         Ok(WMIContext(ctx))
     }
 
+    /// Creates a new context with a copy of the values of this context.
+    ///
+    /// Unlike `clone`, changing the values of the new context does not affect this one.
+    pub(crate) fn copy(&self) -> WMIResult<WMIContext> {
+        Ok(WMIContext(unsafe { self.0.Clone()? }))
+    }
+
     /// Sets the specified named context value for use in providing additional context information to queries.
     ///
     /// Note the context values will persist across subsequent queries until [`WMIConnection::delete_all`] is called.
@@ -64,6 +71,31 @@ impl WMIConnection {
     pub fn ctx(&mut self) -> &mut WMIContext {
         &mut self.ctx
     }
+
+    /// Sets a named context value, which is passed to the provider in subsequent calls (queries, method executions, etc.).
+    ///
+    /// Unlike [`WMIContext::set_value`], any [`Variant`] which can be converted to a `VARIANT` can be used
+    /// (`Variant::Null` cannot be used, use [`WMIContext::delete_all`] to clear values).
+    ///
+    /// For example, `__ProviderArchitecture` (with `__RequiredArchitecture`) can be used to access the 32-bit
+    /// provider from a 64-bit process, which is needed to read the 32-bit view of the registry using `StdRegProv`.
+    ///
+    /// ```edition2021
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// let mut wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// wmi_con.set_context_value("__ProviderArchitecture", Variant::I4(32))?;
+    /// wmi_con.set_context_value("__RequiredArchitecture", Variant::Bool(true))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_context_value(&mut self, name: &str, value: impl Into<Variant>) -> WMIResult<()> {
+        let value = VARIANT::try_from(value.into())?;
+        unsafe { self.ctx.0.SetValue(&BSTR::from(name), 0, &value)? };
+
+        Ok(())
+    }
 }
 
 macro_rules! impl_from_type {
@@ -88,7 +120,7 @@ impl_from_type!(bool, Bool);
 mod tests {
     use super::*;
     use crate::COMLibrary;
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
 
     #[test]
     fn verify_ctx_values_used() {
@@ -116,6 +148,91 @@ mod tests {
         assert_eq!(adapters, orig_adapters);
     }
 
+    #[test]
+    fn it_copies_ctx_values_to_other_namespaces() {
+        #[derive(Deserialize, Debug)]
+        struct MSFT_NetAdapter {
+            InterfaceName: String,
+        }
+
+        let com_con = COMLibrary::new().unwrap();
+        let mut hidden_con =
+            WMIConnection::with_namespace_path("ROOT\\StandardCimv2", com_con).unwrap();
+        hidden_con.ctx().set_value("IncludeHidden", true).unwrap();
+        let all_adapters = hidden_con.query::<MSFT_NetAdapter>().unwrap();
+
+        let mut wmi_con = WMIConnection::new(com_con).unwrap();
+        wmi_con.ctx().set_value("IncludeHidden", true).unwrap();
+
+        let std_cimv2_con = wmi_con.with_namespace("ROOT\\StandardCimv2").unwrap();
+        let adapters = std_cimv2_con.query::<MSFT_NetAdapter>().unwrap();
+        assert_eq!(adapters.len(), all_adapters.len());
+
+        let mut root_con = WMIConnection::with_namespace_path("ROOT", com_con).unwrap();
+        root_con.ctx().set_value("IncludeHidden", true).unwrap();
+
+        let std_cimv2_con = root_con.open_namespace("StandardCimv2").unwrap();
+        let adapters = std_cimv2_con.query::<MSFT_NetAdapter>().unwrap();
+        assert_eq!(adapters.len(), all_adapters.len());
+    }
+
+    #[derive(Deserialize)]
+    struct StdRegProv;
+
+    #[derive(Serialize)]
+    struct GetStringValueParams {
+        hDefKey: u32,
+        sSubKeyName: String,
+        sValueName: String,
+    }
+
+    #[derive(Deserialize)]
+    struct GetStringValueOutput {
+        ReturnValue: u32,
+        sValue: Option<String>,
+    }
+
+    fn program_files_dir(wmi_con: &WMIConnection) -> String {
+        const HKEY_LOCAL_MACHINE: u32 = 0x80000002;
+
+        let out: GetStringValueOutput = wmi_con
+            .exec_class_method::<StdRegProv, _, _>(
+                "GetStringValue",
+                GetStringValueParams {
+                    hDefKey: HKEY_LOCAL_MACHINE,
+                    sSubKeyName: r"SOFTWARE\Microsoft\Windows\CurrentVersion".to_string(),
+                    sValueName: "ProgramFilesDir".to_string(),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(out.ReturnValue, 0);
+        out.sValue.unwrap()
+    }
+
+    #[test]
+    fn it_can_use_the_32_bit_provider() {
+        let com_con = COMLibrary::new().unwrap();
+        let mut wmi_con = WMIConnection::new(com_con).unwrap();
+
+        let native_dir = program_files_dir(&wmi_con);
+        assert!(!native_dir.ends_with("(x86)"));
+
+        wmi_con
+            .set_context_value("__ProviderArchitecture", Variant::I4(32))
+            .unwrap();
+        wmi_con
+            .set_context_value("__RequiredArchitecture", true)
+            .unwrap();
+
+        // The 32-bit provider reads the 32-bit view of the registry.
+        let wow64_dir = program_files_dir(&wmi_con);
+        assert!(wow64_dir.ends_with("(x86)"));
+
+        wmi_con.ctx().delete_all().unwrap();
+        assert_eq!(program_files_dir(&wmi_con), native_dir);
+    }
+
     #[tokio::test]
     async fn async_verify_ctx_values_used() {
         let com_con = COMLibrary::new().unwrap();
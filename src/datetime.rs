@@ -1,4 +1,4 @@
-use crate::WMIError;
+use crate::{FilterValue, WMIError};
 use chrono::prelude::*;
 use serde::{de, ser};
 use std::{fmt, str::FromStr};
@@ -18,7 +18,7 @@ impl FromStr for WMIDateTime {
         let (datetime_part, tz_part) = s.split_at(21);
         let tz_min: i32 = tz_part.parse()?;
         let tz = FixedOffset::east_opt(tz_min * 60).unwrap();
-        let dt = NaiveDateTime::parse_from_str(datetime_part, "%Y%m%d%H%M%S.%f")?
+        let dt = NaiveDateTime::parse_from_str(datetime_part, "%Y%m%d%H%M%S%.f")?
             .and_local_timezone(tz)
             .single()
             .ok_or(WMIError::ParseDatetimeLocalError)?;
@@ -27,6 +27,29 @@ impl FromStr for WMIDateTime {
     }
 }
 
+impl WMIDateTime {
+    /// Format the datetime as a [CIM_DATETIME](https://learn.microsoft.com/en-us/windows/win32/wmisdk/cim-datetime) string,
+    /// which can be used in queries or when setting properties.
+    ///
+    /// The sub-second part is truncated to microseconds, and the offset is encoded in minutes.
+    ///
+    /// ```edition2021
+    /// # use wmi::WMIDateTime;
+    /// let dt: WMIDateTime = "20190113200517.500000-180".parse().unwrap();
+    ///
+    /// assert_eq!(dt.to_cim_datetime_string(), "20190113200517.500000-180");
+    /// ```
+    pub fn to_cim_datetime_string(&self) -> String {
+        let offset_minutes = self.0.offset().local_minus_utc() / 60;
+
+        format!(
+            "{}{:+04}",
+            self.0.format("%Y%m%d%H%M%S.%6f"),
+            offset_minutes
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 struct DateTimeVisitor;
 
@@ -59,16 +82,75 @@ impl ser::Serialize for WMIDateTime {
     where
         S: ser::Serializer,
     {
-        let formatted = self.0.to_rfc3339();
+        serializer.serialize_str(&self.to_cim_datetime_string())
+    }
+}
+
+/// Serialize and deserialize a [`WMIDateTime`] as an RFC 3339 string, instead of a CIM_DATETIME string.
+///
+/// Use it with `#[serde(with = "wmi::datetime::rfc3339")]`, for example to keep the format used by older versions of this crate:
+///
+/// ```edition2021
+/// # use serde::{Deserialize, Serialize};
+/// # use wmi::WMIDateTime;
+/// #[derive(Serialize, Deserialize)]
+/// struct Event {
+///     #[serde(with = "wmi::datetime::rfc3339")]
+///     time: WMIDateTime,
+/// }
+///
+/// let event = Event { time: "20190113200517.500000-180".parse().unwrap() };
+///
+/// assert_eq!(
+///     serde_json::to_string(&event).unwrap(),
+///     r#"{"time":"2019-01-13T20:05:17.500-03:00"}"#
+/// );
+/// ```
+pub mod rfc3339 {
+    use super::WMIDateTime;
+    use chrono::DateTime;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &WMIDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.0.to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<WMIDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        DateTime::parse_from_rfc3339(&s)
+            .map(WMIDateTime)
+            .map_err(de::Error::custom)
+    }
+}
 
-        serializer.serialize_str(&formatted)
+/// Filter on a datetime property, which is matched as a quoted CIM_DATETIME string.
+impl From<WMIDateTime> for FilterValue {
+    fn from(value: WMIDateTime) -> Self {
+        FilterValue::String(value.to_cim_datetime_string())
+    }
+}
+
+impl From<DateTime<FixedOffset>> for FilterValue {
+    fn from(value: DateTime<FixedOffset>) -> Self {
+        WMIDateTime(value).into()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::WMIDateTime;
+    use crate::{build_query, FilterValue};
+    use chrono::prelude::*;
+    use serde::{Deserialize, Serialize};
     use serde_json;
+    use std::collections::HashMap;
 
     #[test]
     fn it_works_with_negative_offset() {
@@ -76,7 +158,7 @@ mod tests {
 
         let formatted = dt.0.to_rfc3339();
 
-        assert_eq!(formatted, "2019-01-13T20:05:17.000500-03:00");
+        assert_eq!(formatted, "2019-01-13T20:05:17.500-03:00");
     }
 
     #[test]
@@ -85,7 +167,19 @@ mod tests {
 
         let formatted = dt.0.to_rfc3339();
 
-        assert_eq!(formatted, "2019-01-13T20:05:17.000500+01:00");
+        assert_eq!(formatted, "2019-01-13T20:05:17.500+01:00");
+    }
+
+    #[test]
+    fn it_preserves_microseconds() {
+        let dt: WMIDateTime = "19980401135809.123456+000".parse().unwrap();
+
+        assert_eq!(dt.0.timestamp_subsec_micros(), 123456);
+        assert_eq!(dt.0.timestamp_subsec_nanos(), 123456000);
+
+        let dt: WMIDateTime = "19980401135809.000042+000".parse().unwrap();
+
+        assert_eq!(dt.0.timestamp_subsec_micros(), 42);
     }
 
     #[test]
@@ -103,10 +197,72 @@ mod tests {
     }
 
     #[test]
-    fn it_serializes_to_rfc() {
+    fn it_serializes_to_cim_datetime() {
         let dt: WMIDateTime = "20190113200517.500000+060".parse().unwrap();
 
         let v = serde_json::to_string(&dt).unwrap();
-        assert_eq!(v, "\"2019-01-13T20:05:17.000500+01:00\"");
+        assert_eq!(v, "\"20190113200517.500000+060\"");
+    }
+
+    #[test]
+    fn it_round_trips_cim_datetime() {
+        for s in [
+            "20190113200517.500000+060",
+            "20190113200517.000001-180",
+            "19980401135809.123456+000",
+            "20231231235959.999999+330",
+            "20000101000000.000000-720",
+        ] {
+            let dt: WMIDateTime = s.parse().unwrap();
+
+            assert_eq!(dt.to_cim_datetime_string(), s);
+
+            let v = serde_json::to_string(&dt).unwrap();
+            let dt_from_json: WMIDateTime = serde_json::from_str(&v).unwrap();
+            assert_eq!(dt_from_json, dt);
+        }
+    }
+
+    #[test]
+    fn it_serializes_to_rfc3339_with_helper() {
+        #[derive(Serialize, Deserialize)]
+        struct Event {
+            #[serde(with = "crate::datetime::rfc3339")]
+            time: WMIDateTime,
+        }
+
+        let event = Event {
+            time: "20190113200517.500000+060".parse().unwrap(),
+        };
+
+        let v = serde_json::to_string(&event).unwrap();
+        assert_eq!(v, r#"{"time":"2019-01-13T20:05:17.500+01:00"}"#);
+
+        let event_from_json: Event = serde_json::from_str(&v).unwrap();
+        assert_eq!(event_from_json.time, event.time);
+    }
+
+    #[test]
+    fn it_builds_a_filter_from_a_datetime() {
+        #[derive(Deserialize)]
+        struct Win32_NTLogEvent {
+            #[allow(dead_code)]
+            Message: String,
+        }
+
+        let dt = FixedOffset::east_opt(60 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2019, 1, 13, 20, 5, 17)
+            .unwrap();
+
+        let mut filters = HashMap::<String, FilterValue>::new();
+        filters.insert("TimeGenerated".to_owned(), dt.into());
+
+        let query = build_query::<Win32_NTLogEvent>(Some(&filters)).unwrap();
+
+        assert_eq!(
+            query,
+            r#"SELECT Message FROM Win32_NTLogEvent WHERE TimeGenerated = "20190113200517.000000+060""#
+        );
     }
 }
@@ -1,4 +1,4 @@
-use crate::WMIError;
+use crate::{FilterValue, WMIError};
 use serde::{de, ser};
 use std::{fmt, str::FromStr};
 use time::{
@@ -35,12 +35,6 @@ impl FromStr for WMIOffsetDateTime {
         parser
             .parse_items(naive_date_time.as_bytes(), TIME_FORMAT)
             .map_err(time::Error::from)?;
-        // Microsoft thinks it is okay to return a subsecond value in microseconds but not put the zeros before it
-        // so 1.1 is 1 second and 100 microsecond, ergo 1.000100 ...
-        parser
-            .set_subsecond(parser.subsecond().unwrap_or(0) / 1000)
-            .ok_or_else(|| time::error::Format::InvalidComponent("subsecond"))
-            .map_err(time::Error::from)?;
 
         let naive_year = s[..4].parse::<i32>()?;
         parser
@@ -55,6 +49,22 @@ impl FromStr for WMIOffsetDateTime {
     }
 }
 
+impl WMIOffsetDateTime {
+    /// Format the datetime as a [CIM_DATETIME](https://learn.microsoft.com/en-us/windows/win32/wmisdk/cim-datetime) string,
+    /// which can be used in queries or when setting properties.
+    ///
+    /// The sub-second part is truncated to microseconds, and the offset is encoded in minutes.
+    pub fn to_cim_datetime_string(&self) -> String {
+        const CIM_DATETIME_FORMAT: &[FormatItem<'static>] =
+            format_description!("[year][month][day][hour][minute][second].[subsecond digits:6]");
+
+        // Unwrap: we passed a well known format, if it fails something has gone very wrong
+        let formatted = self.0.format(CIM_DATETIME_FORMAT).unwrap();
+
+        format!("{}{:+04}", formatted, self.0.offset().whole_minutes())
+    }
+}
+
 #[derive(Debug, Clone)]
 struct DateTimeVisitor;
 
@@ -82,43 +92,103 @@ impl<'de> de::Deserialize<'de> for WMIOffsetDateTime {
     }
 }
 
-const RFC3339_WITH_6_DIGITS: &[FormatItem<'_>] =format_description!(
-    "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6][offset_hour sign:mandatory]:[offset_minute]"
-);
-
 impl ser::Serialize for WMIOffsetDateTime {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: ser::Serializer,
     {
-        // Unwrap: we passed a well known format, if it fails something has gone very wrong
-        let formatted = self.0.format(RFC3339_WITH_6_DIGITS).unwrap();
+        serializer.serialize_str(&self.to_cim_datetime_string())
+    }
+}
+
+/// Serialize and deserialize a [`WMIOffsetDateTime`] as an RFC 3339 string, instead of a CIM_DATETIME string.
+///
+/// Use it with `#[serde(with = "wmi::datetime_time::rfc3339")]`, for example to keep the format used by older versions of this crate.
+pub mod rfc3339 {
+    use super::WMIOffsetDateTime;
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+    use time::{format_description::FormatItem, macros::format_description, OffsetDateTime};
+
+    const RFC3339_WITH_6_DIGITS: &[FormatItem<'static>] = format_description!(
+        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6][offset_hour sign:mandatory]:[offset_minute]"
+    );
+
+    pub fn serialize<S>(value: &WMIOffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let formatted = value
+            .0
+            .format(RFC3339_WITH_6_DIGITS)
+            .map_err(ser::Error::custom)?;
 
         serializer.serialize_str(&formatted)
     }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<WMIOffsetDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        OffsetDateTime::parse(&s, &time::format_description::well_known::Rfc3339)
+            .map(WMIOffsetDateTime)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Filter on a datetime property, which is matched as a quoted CIM_DATETIME string.
+impl From<WMIOffsetDateTime> for FilterValue {
+    fn from(value: WMIOffsetDateTime) -> Self {
+        FilterValue::String(value.to_cim_datetime_string())
+    }
+}
+
+impl From<time::OffsetDateTime> for FilterValue {
+    fn from(value: time::OffsetDateTime) -> Self {
+        WMIOffsetDateTime(value).into()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::WMIOffsetDateTime;
+    use serde::{Deserialize, Serialize};
     use serde_json;
+    use time::{format_description::FormatItem, macros::format_description};
+
+    const RFC3339_WITH_6_DIGITS: &[FormatItem<'_>] = format_description!(
+        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6][offset_hour sign:mandatory]:[offset_minute]"
+    );
 
     #[test]
     fn it_works_with_negative_offset() {
         let dt: WMIOffsetDateTime = "20190113200517.500000-180".parse().unwrap();
 
-        let formatted = dt.0.format(super::RFC3339_WITH_6_DIGITS).unwrap();
+        let formatted = dt.0.format(RFC3339_WITH_6_DIGITS).unwrap();
 
-        assert_eq!(formatted, "2019-01-13T20:05:17.000500-03:00");
+        assert_eq!(formatted, "2019-01-13T20:05:17.500000-03:00");
     }
 
     #[test]
     fn it_works_with_positive_offset() {
         let dt: WMIOffsetDateTime = "20190113200517.500000+060".parse().unwrap();
 
-        let formatted = dt.0.format(super::RFC3339_WITH_6_DIGITS).unwrap();
+        let formatted = dt.0.format(RFC3339_WITH_6_DIGITS).unwrap();
+
+        assert_eq!(formatted, "2019-01-13T20:05:17.500000+01:00");
+    }
+
+    #[test]
+    fn it_preserves_microseconds() {
+        let dt: WMIOffsetDateTime = "19980401135809.123456+000".parse().unwrap();
+
+        assert_eq!(dt.0.microsecond(), 123456);
+        assert_eq!(dt.0.nanosecond(), 123456000);
+
+        let dt: WMIOffsetDateTime = "19980401135809.000042+000".parse().unwrap();
 
-        assert_eq!(formatted, "2019-01-13T20:05:17.000500+01:00");
+        assert_eq!(dt.0.microsecond(), 42);
     }
 
     #[test]
@@ -136,10 +206,48 @@ mod tests {
     }
 
     #[test]
-    fn it_serializes_to_rfc() {
+    fn it_serializes_to_cim_datetime() {
         let dt: WMIOffsetDateTime = "20190113200517.500000+060".parse().unwrap();
 
         let v = serde_json::to_string(&dt).unwrap();
-        assert_eq!(v, "\"2019-01-13T20:05:17.000500+01:00\"");
+        assert_eq!(v, "\"20190113200517.500000+060\"");
+    }
+
+    #[test]
+    fn it_serializes_to_rfc3339_with_helper() {
+        #[derive(Serialize, Deserialize)]
+        struct Event {
+            #[serde(with = "crate::datetime_time::rfc3339")]
+            time: WMIOffsetDateTime,
+        }
+
+        let event = Event {
+            time: "20190113200517.500000+060".parse().unwrap(),
+        };
+
+        let v = serde_json::to_string(&event).unwrap();
+        assert_eq!(v, r#"{"time":"2019-01-13T20:05:17.500000+01:00"}"#);
+
+        let event_from_json: Event = serde_json::from_str(&v).unwrap();
+        assert_eq!(event_from_json.time, event.time);
+    }
+
+    #[test]
+    fn it_round_trips_cim_datetime() {
+        for s in [
+            "20190113200517.500000+060",
+            "20190113200517.000001-180",
+            "19980401135809.123456+000",
+            "20231231235959.999999+330",
+            "20000101000000.000000-720",
+        ] {
+            let dt: WMIOffsetDateTime = s.parse().unwrap();
+
+            assert_eq!(dt.to_cim_datetime_string(), s);
+
+            let v = serde_json::to_string(&dt).unwrap();
+            let dt_from_json: WMIOffsetDateTime = serde_json::from_str(&v).unwrap();
+            assert_eq!(dt_from_json, dt);
+        }
     }
 }
@@ -76,11 +76,14 @@ where
 
     match name {
         None =>  Err(de::Error::custom("Expected a named struct. \
-            Hint: You cannot use a HashMap<...> in this context because it requires the struct to have a name")),
+            Hint: You cannot use a HashMap<...> (or a struct with a #[serde(flatten)] field) in this context because it requires the struct to have a name")),
         Some(name) => {
             validate_identifier(name)?;
+            // Fields can select a property of an embedded object, like `TargetInstance.Name`.
             for field in fields.into_iter().flatten() {
-                validate_identifier(field)?;
+                for segment in field.split('.') {
+                    validate_identifier(segment)?;
+                }
             }
 
             Ok((name, fields.unwrap()))
@@ -98,7 +101,7 @@ where
 /// > All following characters must be in set S2 where S2 = S1 union {U+0030...U+0039} \[This is alphabetic, underscore, plus Arabic numerals 0 through 9.\]<br>
 ///
 /// [DMTF-DSP0004]:     https://www.dmtf.org/sites/default/files/standards/documents/DSP0004V2.3_final.pdf
-fn validate_identifier<E: de::Error>(s: &str) -> Result<&str, E> {
+pub(crate) fn validate_identifier<E: de::Error>(s: &str) -> Result<&str, E> {
     fn is_s1(ch: char) -> bool {
         match ch {
             '\u{005f}' => true,
@@ -187,6 +190,30 @@ mod tests {
 
         struct_name_and_fields::<EvilStructName>().unwrap_err();
         struct_name_and_fields::<EvilFieldName>().unwrap_err();
+
+        #[derive(Deserialize, Debug)]
+        struct EmptySegmentFieldName {
+            #[serde(rename = "TargetInstance..Name")]
+            #[allow(dead_code)]
+            field: String,
+        }
+
+        struct_name_and_fields::<EmptySegmentFieldName>().unwrap_err();
+    }
+
+    #[test]
+    fn it_allows_dotted_field_names() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct __InstanceCreationEvent {
+            #[serde(rename = "TargetInstance.Name")]
+            name: String,
+        }
+
+        let (name, fields) = struct_name_and_fields::<__InstanceCreationEvent>().unwrap();
+
+        assert_eq!(name, "__InstanceCreationEvent");
+        assert_eq!(fields, ["TargetInstance.Name"]);
     }
 
     #[test]
@@ -194,5 +221,16 @@ mod tests {
         let err = struct_name_and_fields::<HashMap<String, Variant>>().unwrap_err();
 
         assert!(format!("{:?}", err).contains("Expected a named struct"));
+
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Flattened {
+            #[serde(flatten)]
+            other: HashMap<String, Variant>,
+        }
+
+        let err = struct_name_and_fields::<Flattened>().unwrap_err();
+
+        assert!(format!("{:?}", err).contains("Expected a named struct"));
     }
 }
@@ -1,9 +1,16 @@
-use crate::{de::wbem_class_de::Deserializer, variant::Variant, WMIError};
+use crate::{
+    de::wbem_class_de::Deserializer, result_enumerator::IWbemClassWrapper, variant::Variant,
+    WMIError,
+};
 use serde::{
-    de::{self, IntoDeserializer},
+    de::{
+        self,
+        value::{MapAccessDeserializer, MapDeserializer},
+        IntoDeserializer,
+    },
     forward_to_deserialize_any, Deserialize,
 };
-use std::{fmt, vec::IntoIter};
+use std::{cell::RefCell, fmt, iter, vec::IntoIter};
 
 #[derive(Debug)]
 struct SeqAccess {
@@ -35,6 +42,8 @@ impl<'de> serde::Deserializer<'de> for Variant {
             Variant::Null => visitor.visit_none(),
             Variant::Empty => visitor.visit_unit(),
             Variant::String(s) => visitor.visit_string(s),
+            // Integers can be deserialized into any integer type which can hold the value,
+            // so a non-negative `I4` can be deserialized into a `u32` (or a `u8`) field, but not a negative one.
             Variant::I1(n) => visitor.visit_i8(n),
             Variant::I2(n) => visitor.visit_i16(n),
             Variant::I4(n) => visitor.visit_i32(n),
@@ -49,6 +58,11 @@ impl<'de> serde::Deserializer<'de> for Variant {
             Variant::Array(v) => visitor.visit_seq(SeqAccess {
                 data: v.into_iter(),
             }),
+            Variant::Object(o) => Deserializer::from_wbem_class_obj(o).deserialize_any(visitor),
+            // References are paths, and the placeholder has no value (like `deserialize_option`).
+            // Both are preserved when deserializing into a `Variant`, see `deserialize_newtype_struct`.
+            Variant::Unsupported(_) => visitor.visit_none(),
+            Variant::Reference(path) => visitor.visit_string(path),
             _ => Err(WMIError::InvalidDeserializationVariantError(format!(
                 "{:?}",
                 self
@@ -63,10 +77,48 @@ impl<'de> serde::Deserializer<'de> for Variant {
         match self {
             Variant::Null => visitor.visit_none(),
             Variant::Empty => visitor.visit_none(),
+            Variant::Unsupported(_) => visitor.visit_none(),
             some => visitor.visit_some(some),
         }
     }
 
+    // A reference is a path, so it can be deserialized into a `String` as well.
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Variant::Reference(path) => visitor.visit_string(path),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    // Used for the tag of internally tagged enums (`#[serde(tag = "DriveType")]`).
+    // Integers are visited as strings, so variants can be matched with `#[serde(rename = "3")]`.
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Variant::I1(n) => visitor.visit_string(n.to_string()),
+            Variant::I2(n) => visitor.visit_string(n.to_string()),
+            Variant::I4(n) => visitor.visit_string(n.to_string()),
+            Variant::I8(n) => visitor.visit_string(n.to_string()),
+            Variant::UI1(n) => visitor.visit_string(n.to_string()),
+            Variant::UI2(n) => visitor.visit_string(n.to_string()),
+            Variant::UI4(n) => visitor.visit_string(n.to_string()),
+            Variant::UI8(n) => visitor.visit_string(n.to_string()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     fn deserialize_struct<V>(
         self,
         name: &'static str,
@@ -84,6 +136,51 @@ impl<'de> serde::Deserializer<'de> for Variant {
         }
     }
 
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Variant::Object(o) => Deserializer::from_wbem_class_obj(o).deserialize_map(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            // `Variant`'s own `Deserialize` impl asks for this name, so the variants which `deserialize_any`
+            // maps to other types are passed as enum variants instead.
+            Variant::Reference(path) if name == VARIANT_NEWTYPE_NAME => {
+                visit_variant_enum("Reference", Variant::String(path), visitor)
+            }
+            Variant::Unsupported(variant_type) if name == VARIANT_NEWTYPE_NAME => {
+                visit_variant_enum("Unsupported", Variant::UI2(variant_type), visitor)
+            }
+            // An embedded object cannot be passed through a visitor, so it is handed over using `PENDING_OBJECT`.
+            Variant::Object(o) if name == VARIANT_NEWTYPE_NAME => {
+                PENDING_OBJECT.with(|pending| *pending.borrow_mut() = Some(o));
+
+                let res = visit_variant_enum("Object", Variant::Empty, visitor);
+
+                // Don't keep the object alive if the visitor did not take it.
+                PENDING_OBJECT.with(|pending| pending.borrow_mut().take());
+
+                res
+            }
+            Variant::Object(o) if name != VARIANT_NEWTYPE_NAME => {
+                Deserializer::from_wbem_class_obj(o).deserialize_newtype_struct(name, visitor)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     fn deserialize_enum<V>(
         self,
         name: &'static str,
@@ -105,9 +202,45 @@ impl<'de> serde::Deserializer<'de> for Variant {
     }
 
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf unit unit_struct newtype_struct seq tuple
-        tuple_struct map identifier ignored_any
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct ignored_any
+    }
+}
+
+/// The name `Variant` passes to `deserialize_newtype_struct`, to preserve `Variant::Reference`, `Variant::Unsupported` and `Variant::Object`.
+const VARIANT_NEWTYPE_NAME: &str = "$wmi::Variant";
+
+thread_local! {
+    /// The embedded object being deserialized into a `Variant::Object`, see `deserialize_newtype_struct`.
+    static PENDING_OBJECT: RefCell<Option<IWbemClassWrapper>> = const { RefCell::new(None) };
+}
+
+/// Visit a single-entry map as an enum variant (`variant` holding `value`).
+fn visit_variant_enum<'de, V>(
+    variant: &'static str,
+    value: Variant,
+    visitor: V,
+) -> Result<V::Value, WMIError>
+where
+    V: de::Visitor<'de>,
+{
+    let map = MapDeserializer::new(iter::once((variant, value)));
+
+    de::Deserializer::deserialize_enum(
+        MapAccessDeserializer::new(map),
+        VARIANT_NEWTYPE_NAME,
+        &[],
+        visitor,
+    )
+}
+
+// Allows using variants as values in serde's value deserializers (like `MapDeserializer`).
+impl<'de> IntoDeserializer<'de, WMIError> for Variant {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
     }
 }
 
@@ -212,6 +345,39 @@ impl<'de> Deserialize<'de> for Variant {
                 Ok(Variant::Null)
             }
 
+            // Deserializers which do not know the name passed to `deserialize_newtype_struct` call this with themselves.
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+
+            // Used for `Variant::Reference`, `Variant::Unsupported` and `Variant::Object`, see `deserialize_newtype_struct`.
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::EnumAccess<'de>,
+            {
+                let (variant, value): (String, _) = data.variant()?;
+
+                match variant.as_str() {
+                    "Reference" => Ok(Variant::Reference(value.newtype_variant()?)),
+                    "Unsupported" => Ok(Variant::Unsupported(value.newtype_variant()?)),
+                    "Object" => {
+                        value.unit_variant()?;
+
+                        PENDING_OBJECT
+                            .with(|pending| pending.borrow_mut().take())
+                            .map(Variant::Object)
+                            .ok_or_else(|| de::Error::custom("Expected an embedded object"))
+                    }
+                    other => Err(de::Error::unknown_variant(
+                        other,
+                        &["Reference", "Unsupported", "Object"],
+                    )),
+                }
+            }
+
             #[inline]
             fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
             where
@@ -226,15 +392,15 @@ impl<'de> Deserialize<'de> for Variant {
                 Ok(Variant::Array(vec))
             }
 
-            fn visit_map<V>(self, mut _visitor: V) -> Result<Self::Value, V::Error>
+            // Embedded objects are passed using `visit_enum`, and other maps cannot be converted into an object.
+            fn visit_map<V>(self, _visitor: V) -> Result<Self::Value, V::Error>
             where
                 V: de::MapAccess<'de>,
             {
-                // TODO: Add support for map type
-                unimplemented!()
+                Err(de::Error::invalid_type(de::Unexpected::Map, &self))
             }
         }
 
-        deserializer.deserialize_any(VariantVisitor)
+        deserializer.deserialize_newtype_struct(VARIANT_NEWTYPE_NAME, VariantVisitor)
     }
 }
@@ -1,4 +1,4 @@
-use crate::{result_enumerator::IWbemClassWrapper, WMIError, WMIResult};
+use crate::{result_enumerator::IWbemClassWrapper, Variant, WMIError, WMIResult};
 use serde::{
     de::{
         self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
@@ -8,13 +8,111 @@ use serde::{
 };
 use std::iter::Peekable;
 
+/// A deserializer for WMI objects.
+///
+/// Structs and maps are deserialized from the object's properties.
+/// Map entries are visited in the order the properties are listed by WMI,
+/// so an order-preserving map (like `indexmap::IndexMap`, using the `indexmap` feature) keeps that order.
+///
+/// Enums are dispatched on the object's class name (`__CLASS`):
+/// - Externally tagged enums (the serde default) use the class name as the variant name,
+///   and deserialize the object into the variant's newtype.
+/// - Internally tagged enums are supported using `#[serde(tag = "__CLASS")]`.
+/// - Internally tagged enums can also be dispatched on any other property, like `#[serde(tag = "DriveType")]`.
+///   Integer values are matched as strings (use `#[serde(rename = "3")]`), and `#[serde(other)]` can be used
+///   for a unit variant matching all other values. The tag property is consumed by serde,
+///   so it cannot also be a field of the variant's struct.
+/// - Adjacently tagged and untagged enums are not supported, since a WMI object does not have
+///   a separate content property.
+///
+/// String properties are decoded from UTF-16 into an owned `String` (a single allocation), which is moved into the field.
+/// Borrowed `&str` fields are not supported, since WMI stores strings as UTF-16 `BSTR`s,
+/// so there is no UTF-8 buffer to borrow from. `Cow<str>` fields are supported, and are always `Cow::Owned`.
+///
+/// Properties holding an embedded object (like the `TargetInstance` of an event) are deserialized
+/// into nested structs (or maps), and `#[serde(flatten)]` can be used to split an object's properties
+/// (including those of an embedded object) between several structs.
+/// Note that serde deserializes structs with a flattened field as maps, so:
+/// - They don't have a name, so they can't be used with [`WMIConnection::query`](crate::WMIConnection::query) and similar methods
+///   which build the query from the struct name. Use [`WMIConnection::raw_query`](crate::WMIConnection::raw_query) instead.
+/// - Like other maps, they only contain the object's non-system properties.
+///
+/// A struct field renamed to a dotted name (like `#[serde(rename = "TargetInstance.Name")]`)
+/// is read from a property of an embedded object.
+///
+/// By default, maps only contain the object's non-system properties.
+/// Use [`Deserializer::with_system_properties`] to also include system properties like `__PATH`.
+///
+/// By default, a property with an unsupported `VARIANT` type fails the deserialization of the entire object.
+/// Use [`Deserializer::lenient`] to read such properties as [`Variant::Unsupported`](crate::Variant::Unsupported) placeholders instead
+/// (which are deserialized as `None` into `Option` fields).
 pub struct Deserializer {
     pub wbem_class_obj: IWbemClassWrapper,
+    include_system_properties: bool,
+    lenient: bool,
 }
 
 impl Deserializer {
     pub fn from_wbem_class_obj(wbem_class_obj: IWbemClassWrapper) -> Self {
-        Deserializer { wbem_class_obj }
+        Deserializer {
+            wbem_class_obj,
+            include_system_properties: false,
+            lenient: false,
+        }
+    }
+
+    /// Include the object's system properties (such as `__PATH`, `__CLASS` and `__NAMESPACE`)
+    /// when deserializing it into a map.
+    pub fn with_system_properties(mut self) -> Self {
+        self.include_system_properties = true;
+        self
+    }
+
+    /// Read properties with an unsupported `VARIANT` type as [`Variant::Unsupported`](crate::Variant::Unsupported) placeholders,
+    /// instead of failing.
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Get a property, where a dotted name (like `TargetInstance.Name`) selects a property of an embedded object.
+    fn get_property(&self, name: &str) -> WMIResult<Variant> {
+        let get_property = |object: &IWbemClassWrapper, name: &str| {
+            if self.lenient {
+                object.get_property_lenient(name)
+            } else {
+                object.get_property(name)
+            }
+        };
+
+        let mut segments = name.split('.');
+        let mut value = get_property(&self.wbem_class_obj, segments.next().unwrap_or_default())?;
+
+        for segment in segments {
+            value = match value {
+                Variant::Object(object) => get_property(&object, segment)?,
+                // A missing embedded object is read as `None`.
+                Variant::Null => Variant::Null,
+                other => {
+                    return Err(WMIError::SerdeError(format!(
+                        "Cannot get property {} of {:?} (in {})",
+                        segment, other, name
+                    )))
+                }
+            };
+        }
+
+        Ok(value)
+    }
+
+    fn map_fields(&self) -> WMIResult<Vec<String>> {
+        let mut fields = self.wbem_class_obj.list_properties()?;
+
+        if self.include_system_properties {
+            fields.extend(self.wbem_class_obj.list_system_properties()?);
+        }
+
+        Ok(fields)
     }
 }
 
@@ -137,10 +235,7 @@ where
             .next()
             .ok_or_else(|| WMIError::SerdeError("Expected current field to not be None".into()))?;
 
-        let property_value = self
-            .de
-            .wbem_class_obj
-            .get_property(current_field.as_ref())?;
+        let property_value = self.de.get_property(current_field.as_ref())?;
 
         seed.deserialize(property_value)
     }
@@ -149,13 +244,16 @@ where
 impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer {
     type Error = WMIError;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    // Used by serde for internally tagged enums (`#[serde(tag = "__CLASS")]`), which buffer the object as a map.
+    // The `__CLASS` system property is included so it can be used as the tag.
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(WMIError::SerdeError(
-            "Only structs and maps can be deserialized from WMI objects".into(),
-        ))
+        let mut fields = self.wbem_class_obj.list_properties()?;
+        fields.push("__CLASS".to_owned());
+
+        visitor.visit_map(WMIMapAccess::new(fields.iter(), self))
     }
 
     // Support for deserializing `Wrapper(Win32_OperatingSystem)`.
@@ -174,7 +272,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer {
     where
         V: Visitor<'de>,
     {
-        let fields = self.wbem_class_obj.list_properties()?;
+        let fields = self.map_fields()?;
 
         visitor.visit_map(WMIMapAccess::new(fields.iter(), self))
     }
@@ -222,7 +320,14 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer {
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
         byte_buf option unit_struct seq tuple
-        tuple_struct ignored_any
+        tuple_struct
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
     }
 }
 
@@ -237,6 +342,7 @@ mod tests {
     use crate::variant::Variant;
     use crate::FilterValue;
     use serde::Deserialize;
+    use std::borrow::Cow;
     use std::collections::HashMap;
     use std::time::Duration;
 
@@ -318,6 +424,29 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "indexmap")]
+    fn it_desr_into_index_map_in_property_order() {
+        use indexmap::IndexMap;
+
+        let wmi_con = wmi_con();
+
+        let raw_os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        let properties = raw_os.list_properties().unwrap();
+
+        let first: IndexMap<String, Variant> = raw_os.clone().into_desr().unwrap();
+        let second: IndexMap<String, Variant> = raw_os.into_desr().unwrap();
+
+        let first_keys: Vec<_> = first.keys().cloned().collect();
+        let second_keys: Vec<_> = second.keys().cloned().collect();
+
+        assert_eq!(first_keys, properties);
+        assert_eq!(first_keys, second_keys);
+    }
+
     #[test]
     fn it_desr_into_map_with_selected_fields() {
         let wmi_con = wmi_con();
@@ -340,6 +469,244 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_desr_into_map_with_system_properties() {
+        let wmi_con = wmi_con();
+
+        let raw_os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        let w: HashMap<String, Variant> = raw_os.clone().into_desr().unwrap();
+
+        assert!(w.contains_key("Caption"));
+        assert_eq!(w.get("__PATH"), None);
+
+        let w: HashMap<String, Variant> = raw_os.into_desr_with_system_properties().unwrap();
+
+        assert!(w.contains_key("Caption"));
+        assert!(matches!(w.get("__PATH"), Some(Variant::String(_))));
+        assert_eq!(
+            w.get("__CLASS"),
+            Some(&Variant::String("Win32_OperatingSystem".to_string()))
+        );
+        assert!(matches!(w.get("__NAMESPACE"), Some(Variant::String(_))));
+    }
+
+    #[test]
+    fn it_desr_embedded_objects_into_maps_without_system_properties() {
+        let wmi_con = wmi_con();
+
+        let raw_os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        // Embedded objects are deserialized like top-level objects.
+        let w = HashMap::<String, Variant>::deserialize(Variant::Object(raw_os.clone())).unwrap();
+
+        assert!(w.contains_key("Caption"));
+        assert_eq!(w.get("__PATH"), None);
+
+        let mut deserializer = Deserializer::from_wbem_class_obj(raw_os)
+            .lenient()
+            .with_system_properties();
+        let w = HashMap::<String, Variant>::deserialize(&mut deserializer).unwrap();
+
+        assert!(w.contains_key("Caption"));
+        assert!(matches!(w.get("__PATH"), Some(Variant::String(_))));
+    }
+
+    #[test]
+    fn it_reads_properties_into_a_map() {
+        let wmi_con = wmi_con();
+
+        let raw_os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        let map = raw_os.to_map().unwrap();
+        let desr_map: HashMap<String, Variant> = raw_os.clone().into_desr().unwrap();
+
+        assert_eq!(map, desr_map);
+        assert_eq!(map.len(), raw_os.list_properties().unwrap().len());
+        assert_eq!(map.get("__PATH"), None);
+
+        let map_with_system = raw_os.to_map_with_system().unwrap();
+
+        assert_eq!(map_with_system.get("Caption"), map.get("Caption"));
+        assert!(matches!(
+            map_with_system.get("__PATH"),
+            Some(Variant::String(_))
+        ));
+        assert_eq!(
+            map_with_system.len(),
+            map.len() + raw_os.list_system_properties().unwrap().len()
+        );
+    }
+
+    #[test]
+    fn it_reads_property_count() {
+        let wmi_con = wmi_con();
+
+        let os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        let property_count = os.property_count().unwrap();
+
+        assert!(property_count >= 60);
+        assert_eq!(property_count as usize, os.list_properties().unwrap().len());
+    }
+
+    #[test]
+    fn it_reads_derivation() {
+        let wmi_con = wmi_con();
+
+        let process = wmi_con
+            .get_raw_by_path(format!(r#"Win32_Process.Handle="{}""#, process::id()))
+            .unwrap();
+
+        let derivation = process.derivation().unwrap();
+
+        assert_eq!(derivation[0], "CIM_Process");
+        assert!(derivation.contains(&"CIM_LogicalElement".to_string()));
+        assert_eq!(derivation.last().unwrap(), "CIM_ManagedSystemElement");
+        assert_eq!(derivation, process.system_properties().unwrap().derivation);
+
+        let top_level_class = wmi_con.get_raw_by_path("CIM_ManagedSystemElement").unwrap();
+
+        assert!(top_level_class.derivation().unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_clones_objects() {
+        let wmi_con = wmi_con();
+
+        let process = wmi_con
+            .get_raw_by_path(format!(r#"Win32_Process.Handle="{}""#, process::id()))
+            .unwrap();
+        let name = process.get_property("Name").unwrap();
+
+        let cloned = process.clone_object().unwrap();
+        cloned.put_property("Name", "cloned.exe").unwrap();
+
+        assert_eq!(
+            cloned.get_property("Name").unwrap(),
+            Variant::String("cloned.exe".to_string())
+        );
+        assert_eq!(process.get_property("Name").unwrap(), name);
+        assert_ne!(cloned, process);
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn it_converts_objects_to_json() {
+        let wmi_con = wmi_con();
+
+        let process = wmi_con
+            .get_raw_by_path(format!(r#"Win32_Process.Handle="{}""#, process::id()))
+            .unwrap()
+            .into_json()
+            .unwrap();
+
+        assert_eq!(process["ProcessId"], serde_json::Value::from(process::id()));
+        assert!(process["ProcessId"].is_u64());
+        assert!(process["Name"].is_string());
+        assert!(process.get("__CLASS").is_none());
+
+        let raw_os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+        let property_count = raw_os.property_count().unwrap() as usize;
+
+        let os = raw_os.into_json().unwrap();
+
+        assert_eq!(os.as_object().unwrap().len(), property_count);
+        assert!(os["MUILanguages"].is_array());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn it_converts_associations_to_json() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize)]
+        struct Win32_Group {
+            __Path: String,
+        }
+
+        let mut filters = HashMap::new();
+        filters.insert("SID".to_owned(), FilterValue::Str("S-1-5-32-544"));
+        let admin_group = wmi_con.filtered_query::<Win32_Group>(&filters).unwrap();
+
+        let association = wmi_con
+            .exec_query_native_wrapper(format!(
+                "REFERENCES OF {{{}}} WHERE ResultClass = Win32_GroupUser",
+                admin_group[0].__Path
+            ))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(
+            association.get_property("GroupComponent").unwrap(),
+            Variant::Reference(_)
+        ));
+
+        let association = association.into_json().unwrap();
+
+        assert!(association["GroupComponent"]
+            .as_str()
+            .unwrap()
+            .contains("Win32_Group"));
+        assert!(association["PartComponent"].is_string());
+    }
+
+    #[test]
+    fn it_reads_system_properties() {
+        let wmi_con = wmi_con();
+
+        let process = wmi_con
+            .exec_query_native_wrapper(format!(
+                "SELECT * FROM Win32_Process WHERE ProcessId = {}",
+                std::process::id()
+            ))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let system_properties = process.system_properties().unwrap();
+
+        assert_eq!(system_properties.genus, 2);
+        assert_eq!(system_properties.class, "Win32_Process");
+        assert_eq!(system_properties.superclass.as_deref(), Some("CIM_Process"));
+        assert_eq!(system_properties.dynasty, "CIM_ManagedSystemElement");
+        assert_eq!(
+            system_properties.relpath,
+            Some(format!(r#"Win32_Process.Handle="{}""#, std::process::id()))
+        );
+        assert_eq!(
+            system_properties.property_count as usize,
+            process.list_properties().unwrap().len()
+        );
+        assert_eq!(
+            system_properties.derivation,
+            [
+                "CIM_Process",
+                "CIM_LogicalElement",
+                "CIM_ManagedSystemElement"
+            ]
+        );
+        assert!(!system_properties.server.unwrap().is_empty());
+        assert_eq!(system_properties.namespace.as_deref(), Some("ROOT\\CIMV2"));
+        assert!(system_properties
+            .path
+            .unwrap()
+            .ends_with(&system_properties.relpath.unwrap()));
+    }
+
     #[test]
     fn it_desr_array() {
         let wmi_con = wmi_con();
@@ -358,6 +725,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_desr_cow_str() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_OperatingSystem {
+            Caption: Cow<'static, str>,
+            Name: Cow<'static, str>,
+        }
+
+        let os: Win32_OperatingSystem = wmi_con.get().unwrap();
+
+        assert!(os.Caption.contains("Microsoft"));
+        assert!(matches!(os.Caption, Cow::Owned(_)));
+        assert!(matches!(os.Name, Cow::Owned(_)));
+
+        #[derive(Deserialize, Debug)]
+        #[serde(rename = "Win32_OperatingSystem")]
+        struct BorrowedOperatingSystem<'a> {
+            Caption: &'a str,
+        }
+
+        let res = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .and_then(|os| {
+                let mut de = Deserializer::from_wbem_class_obj(os);
+                BorrowedOperatingSystem::deserialize(&mut de).map(|os| os.Caption.len())
+            });
+
+        assert!(res.is_err());
+    }
+
     #[test]
     fn it_desr_option_string() {
         let wmi_con = wmi_con();
@@ -411,6 +810,118 @@ mod tests {
         )
     }
 
+    #[test]
+    fn it_desr_option_bool() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        pub struct Win32_NetworkAdapterConfiguration {
+            pub DHCPEnabled: Option<bool>,
+            pub IPEnabled: Option<bool>,
+            pub DNSEnabledForWINSResolution: Option<bool>,
+        }
+
+        let inst = wmi_con
+            .get_raw_by_path("Win32_NetworkAdapterConfiguration")
+            .unwrap()
+            .spawn_instance()
+            .unwrap();
+
+        inst.put_property("DHCPEnabled", true).unwrap();
+        inst.put_property("IPEnabled", Variant::Null).unwrap();
+        inst.put_property("DNSEnabledForWINSResolution", false)
+            .unwrap();
+
+        let config: Win32_NetworkAdapterConfiguration = inst.into_desr().unwrap();
+
+        assert_eq!(config.DHCPEnabled, Some(true));
+        assert_eq!(config.IPEnabled, None);
+        assert_eq!(config.DNSEnabledForWINSResolution, Some(false));
+
+        // Unset values can also be returned as `Empty`.
+        assert_eq!(Option::<bool>::deserialize(Variant::Empty).unwrap(), None);
+        assert_eq!(Option::<bool>::deserialize(Variant::Null).unwrap(), None);
+        assert_eq!(
+            Option::<bool>::deserialize(Variant::Bool(true)).unwrap(),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn it_desr_references_and_placeholders() {
+        let reference = Variant::Reference(r#"Win32_Group.Domain="BUILTIN",Name="Users""#.into());
+
+        assert_eq!(
+            String::deserialize(reference.clone()).unwrap(),
+            r#"Win32_Group.Domain="BUILTIN",Name="Users""#
+        );
+        assert_eq!(
+            serde_json::Value::deserialize(reference.clone()).unwrap(),
+            serde_json::Value::String(r#"Win32_Group.Domain="BUILTIN",Name="Users""#.into())
+        );
+        assert_eq!(Variant::deserialize(reference.clone()).unwrap(), reference);
+
+        let placeholder = Variant::Unsupported(0x24);
+
+        assert_eq!(
+            serde_json::Value::deserialize(placeholder.clone()).unwrap(),
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            Option::<u16>::deserialize(placeholder.clone()).unwrap(),
+            None
+        );
+        assert_eq!(
+            Variant::deserialize(placeholder.clone()).unwrap(),
+            placeholder
+        );
+
+        // Both are preserved inside arrays as well.
+        let array = Variant::Array(vec![reference, placeholder]);
+        assert_eq!(Variant::deserialize(array.clone()).unwrap(), array);
+
+        // Values from other deserializers are not affected.
+        let value = serde_json::json!({ "Name": "a", "Values": [1, null] });
+        let map = HashMap::<String, Variant>::deserialize(value).unwrap();
+        assert_eq!(map["Name"], Variant::String("a".into()));
+        assert_eq!(
+            map["Values"],
+            Variant::Array(vec![Variant::UI8(1), Variant::Null])
+        );
+    }
+
+    #[test]
+    fn it_desr_signed_values_into_unsigned_fields() {
+        use serde::de::value::MapDeserializer;
+
+        assert_eq!(u32::deserialize(Variant::I4(256)).unwrap(), 256);
+        assert_eq!(u8::deserialize(Variant::I4(2)).unwrap(), 2);
+        assert_eq!(
+            u64::deserialize(Variant::I8(i64::MAX)).unwrap(),
+            i64::MAX as u64
+        );
+        assert_eq!(i32::deserialize(Variant::UI4(256)).unwrap(), 256);
+        assert_eq!(Option::<u16>::deserialize(Variant::I2(7)).unwrap(), Some(7));
+
+        // Values which do not fit in the field are still an error.
+        assert!(u32::deserialize(Variant::I4(-1)).is_err());
+        assert!(u8::deserialize(Variant::I4(256)).is_err());
+        assert!(i32::deserialize(Variant::UI4(u32::MAX)).is_err());
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_OperatingSystem {
+            EncryptionLevel: u32,
+        }
+
+        let properties = [("EncryptionLevel", Variant::I4(256))];
+        let os = Win32_OperatingSystem::deserialize(MapDeserializer::<_, WMIError>::new(
+            properties.into_iter(),
+        ))
+        .unwrap();
+
+        assert_eq!(os.EncryptionLevel, 256);
+    }
+
     #[test]
     fn it_desr_duration() {
         let wmi_con = wmi_con();
@@ -444,6 +955,139 @@ mod tests {
         assert_ne!(&wrapped_service.0.Name, "")
     }
 
+    /// Start `ping.exe`, and wait for the `__InstanceCreationEvent` of its process.
+    fn next_ping_creation_event<T: DeserializeOwned>() -> T {
+        let wmi_con = wmi_con();
+
+        let mut events = wmi_con
+            .raw_notification::<T>(
+                "SELECT * FROM __InstanceCreationEvent WITHIN 1 \
+                WHERE TargetInstance ISA 'Win32_Process' AND TargetInstance.Name = 'ping.exe'",
+            )
+            .unwrap();
+
+        std::process::Command::new("ping.exe")
+            .arg("127.0.0.1")
+            .status()
+            .unwrap();
+
+        events
+            .next_timeout(Duration::from_secs(10))
+            .expect("No process creation event within 10 seconds")
+            .unwrap()
+    }
+
+    #[test]
+    fn it_can_desr_embedded_object_with_flatten() {
+        #[derive(Deserialize, Debug)]
+        struct ProcessIds {
+            ProcessId: u32,
+            ParentProcessId: u32,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            Name: String,
+            #[serde(flatten)]
+            ids: ProcessIds,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct EventTime {
+            TIME_CREATED: u64,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct ProcessCreation {
+            TargetInstance: Win32_Process,
+            #[serde(flatten)]
+            time: EventTime,
+        }
+
+        let event: ProcessCreation = next_ping_creation_event();
+
+        assert_eq!(event.TargetInstance.Name, "ping.exe");
+        assert_ne!(event.TargetInstance.ids.ProcessId, 0);
+        assert_eq!(event.TargetInstance.ids.ParentProcessId, process::id());
+        assert!(event.time.TIME_CREATED > 0);
+    }
+
+    #[test]
+    fn it_can_desr_embedded_object_properties_by_dotted_name() {
+        #[derive(Deserialize, Debug)]
+        struct ProcessCreation {
+            #[serde(rename = "TargetInstance.Name")]
+            name: String,
+            #[serde(rename = "TargetInstance.ParentProcessId")]
+            parent_process_id: u32,
+        }
+
+        let event: ProcessCreation = next_ping_creation_event();
+
+        assert_eq!(event.name, "ping.exe");
+        assert_eq!(event.parent_process_id, process::id());
+    }
+
+    #[test]
+    fn it_can_desr_embedded_objects_into_structs_maps_and_newtypes() {
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            Name: String,
+            ParentProcessId: u32,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct ProcessWrapper(Win32_Process);
+
+        #[derive(Deserialize, Debug)]
+        struct __InstanceCreationEvent {
+            TargetInstance: Variant,
+        }
+
+        let event: __InstanceCreationEvent = next_ping_creation_event();
+
+        let object = match &event.TargetInstance {
+            Variant::Object(object) => object,
+            other => panic!("Unexpected variant {:?}", other),
+        };
+
+        let process = Win32_Process::deserialize(Variant::Object(object.clone())).unwrap();
+        assert_eq!(process.Name, "ping.exe");
+        assert_eq!(process.ParentProcessId, process::id());
+
+        let ProcessWrapper(process) =
+            ProcessWrapper::deserialize(Variant::Object(object.clone())).unwrap();
+        assert_eq!(process.Name, "ping.exe");
+
+        // Like top-level objects, embedded objects are deserialized into maps without their system properties.
+        let map = HashMap::<String, Variant>::deserialize(event.TargetInstance).unwrap();
+        assert_eq!(
+            map.get("Name"),
+            Some(&Variant::String("ping.exe".to_owned()))
+        );
+        assert!(!map.contains_key("__CLASS"));
+    }
+
+    #[test]
+    fn it_can_desr_embedded_objects_into_variants() {
+        let event: HashMap<String, Variant> = next_ping_creation_event();
+
+        let object = match event.get("TargetInstance") {
+            Some(Variant::Object(object)) => object,
+            other => panic!("Unexpected variant {:?}", other),
+        };
+
+        assert_eq!(object.class().unwrap(), "Win32_Process");
+
+        match Variant::deserialize(Variant::Object(object.clone())).unwrap() {
+            Variant::Object(object) => assert_eq!(
+                object.get_property("Name").unwrap(),
+                Variant::String("ping.exe".to_owned())
+            ),
+            other => panic!("Unexpected variant {:?}", other),
+        }
+    }
+
     #[test]
     fn it_can_desr_newtype_enum_field() {
         let wmi_con = wmi_con();
@@ -496,6 +1140,117 @@ mod tests {
         assert!(matches!(proc.TargetInstance, Instance::Process(..)))
     }
 
+    #[test]
+    fn it_can_desr_internally_tagged_enum() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_OperatingSystem {
+            Caption: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_ComputerSystem {
+            Caption: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        #[serde(tag = "__CLASS")]
+        enum Instance {
+            Win32_OperatingSystem(Win32_OperatingSystem),
+            Win32_ComputerSystem(Win32_ComputerSystem),
+        }
+
+        let instance: Instance = wmi_con
+            .get_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        match instance {
+            Instance::Win32_OperatingSystem(os) => assert!(os.Caption.contains("Microsoft")),
+            other => panic!("Unexpected variant {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_can_desr_enum_tagged_by_property_value() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct LogicalDisk {
+            DeviceID: String,
+            FreeSpace: Option<u64>,
+        }
+
+        #[derive(Deserialize, Debug)]
+        #[serde(tag = "DriveType")]
+        enum Drive {
+            #[serde(rename = "2")]
+            Removable(LogicalDisk),
+            #[serde(rename = "3")]
+            Local(LogicalDisk),
+            #[serde(rename = "4")]
+            Network(LogicalDisk),
+            #[serde(other)]
+            Other,
+        }
+
+        let drives: Vec<Drive> = wmi_con
+            .raw_query("SELECT DeviceID, DriveType, FreeSpace FROM Win32_LogicalDisk")
+            .unwrap();
+
+        let system_drive = drives
+            .iter()
+            .find_map(|drive| match drive {
+                Drive::Local(disk) if disk.DeviceID == "C:" => Some(disk),
+                _ => None,
+            })
+            .unwrap();
+
+        assert!(system_drive.FreeSpace.unwrap() > 0);
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_LogicalDisk {
+            DeviceID: String,
+            DriveType: u32,
+        }
+
+        let disks: Vec<Win32_LogicalDisk> = wmi_con.query().unwrap();
+        let drives: Vec<Drive> = wmi_con
+            .raw_query("SELECT DeviceID, DriveType, FreeSpace FROM Win32_LogicalDisk")
+            .unwrap();
+
+        for (disk, drive) in disks.iter().zip(drives.iter()) {
+            match (disk.DriveType, drive) {
+                (2, Drive::Removable(d)) | (3, Drive::Local(d)) | (4, Drive::Network(d)) => {
+                    assert_eq!(d.DeviceID, disk.DeviceID)
+                }
+                (0 | 1 | 5 | 6, Drive::Other) => {}
+                other => panic!("Unexpected drive {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn it_fails_to_desr_adjacently_tagged_enum() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_OperatingSystem {
+            Caption: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        #[serde(tag = "__CLASS", content = "Content")]
+        enum Instance {
+            Win32_OperatingSystem(Win32_OperatingSystem),
+        }
+
+        let res: Result<Instance, WMIError> =
+            wmi_con.get_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#);
+
+        assert!(res.is_err());
+    }
+
     #[test]
     fn it_can_desr_unit_enum_field_from_string() {
         let wmi_con = wmi_con();
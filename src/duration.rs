@@ -4,6 +4,9 @@ use std::{fmt, str::FromStr, time::Duration};
 
 /// A wrapper type around Duration, which supports parsing from WMI-format strings.
 ///
+/// The string must be a [CIM_DATETIME](https://learn.microsoft.com/en-us/windows/win32/wmisdk/cim-datetime) interval,
+/// of the form `ddddddddHHMMSS.mmmmmm:000`. Absolute datetimes (like `20190113200517.500000+060`) are rejected.
+///
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct WMIDuration(pub Duration);
 
@@ -11,16 +14,29 @@ impl FromStr for WMIDuration {
     type Err = WMIError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 25 {
+        // An interval has the form `ddddddddHHMMSS.mmmmmm:000`, where the `:000` suffix
+        // takes the place of the UTC offset of an absolute datetime (like `+060`).
+        if s.len() != 25 || !s.is_ascii() || &s[14..15] != "." || &s[21..] != ":000" {
+            return Err(WMIError::ConvertDurationError(s.into()));
+        }
+
+        // Every field must be all digits, since `parse` would also accept a sign (like `+0000005`).
+        let is_digits = |field: &str| field.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(&s[..14]) || !is_digits(&s[15..21]) {
             return Err(WMIError::ConvertDurationError(s.into()));
         }
 
-        let (seconds_part, reminder) = s.split_at(14);
-        let (micros_part, _) = reminder[1..].split_at(6);
+        let days: u64 = s[..8].parse()?;
+        let hours: u64 = s[8..10].parse()?;
+        let minutes: u64 = s[10..12].parse()?;
+        let seconds: u64 = s[12..14].parse()?;
+        let micros: u64 = s[15..21].parse()?;
 
-        let seconds: u64 = seconds_part.parse()?;
-        let micros: u64 = micros_part.parse()?;
+        if hours >= 24 || minutes >= 60 || seconds >= 60 {
+            return Err(WMIError::ConvertDurationError(s.into()));
+        }
 
+        let seconds = ((days * 24 + hours) * 60 + minutes) * 60 + seconds;
         let duration = Duration::from_secs(seconds) + Duration::from_micros(micros);
 
         Ok(Self(duration))
@@ -34,7 +50,7 @@ impl<'de> de::Visitor<'de> for DurationVisitor {
     type Value = WMIDuration;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        write!(formatter, "a timestamp in WMI format")
+        write!(formatter, "an interval in WMI format")
     }
 
     fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -66,15 +82,53 @@ impl ser::Serialize for WMIDuration {
 #[cfg(test)]
 mod tests {
     use super::WMIDuration;
+    use crate::WMIError;
     use serde_json;
+    use std::time::Duration;
 
     #[test]
     fn it_works() {
+        let duration: WMIDuration = "00000000141436.100001:000".parse().unwrap();
+
+        assert_eq!(duration.0.as_micros(), 51276100001);
+        assert_eq!(duration.0.as_millis(), 51276100);
+        assert_eq!(duration.0.as_secs(), 51276);
+    }
+
+    #[test]
+    fn it_works_with_multiple_days() {
         let duration: WMIDuration = "00000005141436.100001:000".parse().unwrap();
 
-        assert_eq!(duration.0.as_micros(), 5141436100001);
-        assert_eq!(duration.0.as_millis(), 5141436100);
-        assert_eq!(duration.0.as_secs(), 5141436);
+        let expected = Duration::from_secs(5 * 24 * 60 * 60 + 14 * 60 * 60 + 14 * 60 + 36)
+            + Duration::from_micros(100001);
+        assert_eq!(duration.0, expected);
+    }
+
+    #[test]
+    fn it_fails_with_absolute_datetime() {
+        let res: Result<WMIDuration, _> = "20190113200517.500000+060".parse();
+
+        assert!(matches!(res, Err(WMIError::ConvertDurationError(_))));
+    }
+
+    #[test]
+    fn it_fails_with_out_of_range_components() {
+        let res: Result<WMIDuration, _> = "00000005241436.100001:000".parse();
+
+        assert!(matches!(res, Err(WMIError::ConvertDurationError(_))));
+    }
+
+    #[test]
+    fn it_fails_with_signed_components() {
+        for s in [
+            "+0000005141436.100001:000",
+            "00000005+41436.100001:000",
+            "00000005141436.+00001:000",
+        ] {
+            let res: Result<WMIDuration, _> = s.parse();
+
+            assert!(matches!(res, Err(WMIError::ConvertDurationError(_))));
+        }
     }
 
     #[test]
@@ -82,6 +136,6 @@ mod tests {
         let duration: WMIDuration = "00000005141436.100001:000".parse().unwrap();
 
         let v = serde_json::to_string(&duration).unwrap();
-        assert_eq!(v, "5141436100001");
+        assert_eq!(v, "483276100001");
     }
 }
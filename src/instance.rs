@@ -0,0 +1,289 @@
+use crate::{
+    ser::variant_ser::{VariantSerializer, VariantSerializerError},
+    Variant, WMIConnection, WMIError, WMIResult,
+};
+use serde::Serialize;
+use windows::core::BSTR;
+use windows::Win32::System::Wmi::{
+    WBEM_FLAG_CREATE_ONLY, WBEM_FLAG_CREATE_OR_UPDATE, WBEM_FLAG_UPDATE_ONLY,
+    WBEM_GENERIC_FLAG_TYPE, WBEM_INFINITE,
+};
+
+/// Controls whether [`WMIConnection::put_instance`] creates a new instance, updates an existing one, or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PutInstanceMode {
+    /// Create the instance if it does not exist, or update it otherwise (`WBEM_FLAG_CREATE_OR_UPDATE`).
+    #[default]
+    CreateOrUpdate,
+    /// Fail with `WBEM_E_ALREADY_EXISTS` if the instance already exists (`WBEM_FLAG_CREATE_ONLY`).
+    CreateOnly,
+    /// Fail with `WBEM_E_NOT_FOUND` if the instance does not exist (`WBEM_FLAG_UPDATE_ONLY`).
+    UpdateOnly,
+}
+
+impl From<PutInstanceMode> for WBEM_GENERIC_FLAG_TYPE {
+    fn from(mode: PutInstanceMode) -> Self {
+        let flag = match mode {
+            PutInstanceMode::CreateOrUpdate => WBEM_FLAG_CREATE_OR_UPDATE,
+            PutInstanceMode::CreateOnly => WBEM_FLAG_CREATE_ONLY,
+            PutInstanceMode::UpdateOnly => WBEM_FLAG_UPDATE_ONLY,
+        };
+
+        WBEM_GENERIC_FLAG_TYPE(flag.0)
+    }
+}
+
+///
+/// ### Instance modification methods
+///
+impl WMIConnection {
+    /// Create or update a WMI instance using WMI's [PutInstance](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-putinstance) function.
+    ///
+    /// A new instance of the class named after `T` is created, and each field of `instance` is used to set the matching property.
+    /// Returns the `__Path` of the created or updated instance.
+    ///
+    /// ```edition2021
+    /// # use serde::Serialize;
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # use wmi::instance::PutInstanceMode;
+    /// #[derive(Serialize)]
+    /// # #[allow(non_camel_case_types, non_snake_case)]
+    /// struct __EventFilter {
+    ///     Name: String,
+    ///     Query: String,
+    ///     QueryLanguage: String,
+    /// }
+    ///
+    /// # #[cfg(not(feature = "test"))]
+    /// # fn main() {}
+    /// # #[cfg(feature = "test")]
+    /// # fn main() -> WMIResult<()> {
+    /// #   wmi::tests::ignore_access_denied(run())
+    /// # }
+    /// # fn run() -> WMIResult<()> {
+    /// let wmi_con = WMIConnection::with_namespace_path("ROOT\\subscription", COMLibrary::new()?)?;
+    ///
+    /// let filter = __EventFilter {
+    ///     Name: "MyFilter".to_string(),
+    ///     Query: "SELECT * FROM __InstanceCreationEvent WITHIN 5 WHERE TargetInstance ISA 'Win32_Process'".to_string(),
+    ///     QueryLanguage: "WQL".to_string(),
+    /// };
+    ///
+    /// let path = wmi_con.put_instance(&filter, PutInstanceMode::CreateOrUpdate)?;
+    /// # wmi_con.delete_instance(&path)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn put_instance<T>(&self, instance: &T, mode: PutInstanceMode) -> WMIResult<String>
+    where
+        T: Serialize,
+    {
+        let instance = match instance.serialize(VariantSerializer::with_connection(self)) {
+            Ok(Variant::Object(instance)) => instance,
+            Ok(other) => {
+                return Err(WMIError::ConvertVariantError(format!(
+                    "Expected a struct, but {:?} was serialized",
+                    other
+                )))
+            }
+            Err(VariantSerializerError::WMIError(e)) => return Err(e),
+            Err(e) => return Err(WMIError::ConvertVariantError(e.to_string())),
+        };
+
+        let mut call_result = None;
+
+        unsafe {
+            self.svc.PutInstance(
+                &instance.inner,
+                mode.into(),
+                &self.ctx.0,
+                Some(&mut call_result),
+            )?;
+        }
+
+        let call_result = call_result.ok_or(WMIError::NullPointerResult)?;
+        let path = unsafe { call_result.GetResultString(WBEM_INFINITE)? };
+
+        Ok(path.to_string())
+    }
+
+    /// Wrapper for WMI's [DeleteInstance](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-deleteinstance) function.
+    ///
+    /// `object_path` is the `__Path` of the instance to delete, which can be obtained from a WMI query.
+    ///
+    /// Common errors are returned as [`WMIError::HResultError`](crate::WMIError::HResultError) with:
+    /// - `WBEM_E_NOT_FOUND` if the instance does not exist.
+    /// - `WBEM_E_ACCESS_DENIED` if the current user is not allowed to delete the instance.
+    ///
+    /// ```edition2021
+    /// # use serde::Deserialize;
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// #[derive(Deserialize)]
+    /// # #[allow(non_camel_case_types, non_snake_case)]
+    /// struct __EventFilter {
+    ///     __Path: String,
+    ///     Name: String,
+    /// }
+    ///
+    /// # #[cfg(not(feature = "test"))]
+    /// # fn main() {}
+    /// # #[cfg(feature = "test")]
+    /// # fn main() -> WMIResult<()> {
+    /// #   wmi::tests::ignore_access_denied(run())
+    /// # }
+    /// # fn run() -> WMIResult<()> {
+    /// let wmi_con = WMIConnection::with_namespace_path("ROOT\\subscription", COMLibrary::new()?)?;
+    ///
+    /// for filter in wmi_con.query::<__EventFilter>()? {
+    ///     if filter.Name == "MyFilter" {
+    ///         wmi_con.delete_instance(&filter.__Path)?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn delete_instance(&self, object_path: impl AsRef<str>) -> WMIResult<()> {
+        let object_path = BSTR::from(object_path.as_ref());
+
+        unsafe {
+            self.svc
+                .DeleteInstance(&object_path, Default::default(), &self.ctx.0, None)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[allow(non_snake_case)]
+#[allow(non_camel_case_types)]
+#[cfg(test)]
+mod tests {
+    use super::PutInstanceMode;
+    use crate::tests::{fixtures::wmi_con, ignore_access_denied};
+    use crate::{COMLibrary, FilterValue, WMIConnection, WMIError, WMIResult};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use windows::core::{BSTR, HSTRING, VARIANT};
+    use windows::Win32::System::Wmi::{
+        WBEM_E_ALREADY_EXISTS, WBEM_E_NOT_FOUND, WBEM_FLAG_CREATE_ONLY, WBEM_GENERIC_FLAG_TYPE,
+    };
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_Environment {
+        __Path: String,
+    }
+
+    const TEST_VARIABLE: &str = "WMI_RS_TEST_INSTANCE";
+
+    fn user_name() -> String {
+        format!(
+            "{}\\{}",
+            std::env::var("USERDOMAIN").unwrap(),
+            std::env::var("USERNAME").unwrap()
+        )
+    }
+
+    fn create_test_variable(wmi_con: &WMIConnection) {
+        let class = wmi_con.get_raw_by_path("Win32_Environment").unwrap();
+
+        unsafe {
+            let inst = class.inner.SpawnInstance(0).unwrap();
+
+            for (name, value) in [
+                ("Name", TEST_VARIABLE.to_string()),
+                ("UserName", user_name()),
+                ("VariableValue", "1".to_string()),
+            ] {
+                let value = VARIANT::from(BSTR::from(value));
+                inst.Put(&HSTRING::from(name), 0, &value, 0).unwrap();
+            }
+
+            wmi_con
+                .svc
+                .PutInstance(
+                    &inst,
+                    WBEM_GENERIC_FLAG_TYPE(WBEM_FLAG_CREATE_ONLY.0),
+                    &wmi_con.ctx.0,
+                    None,
+                )
+                .unwrap();
+        }
+    }
+
+    fn find_test_variable(wmi_con: &WMIConnection) -> Vec<Win32_Environment> {
+        let mut filters = HashMap::new();
+        filters.insert("Name".to_owned(), FilterValue::Str(TEST_VARIABLE));
+
+        wmi_con.filtered_query(&filters).unwrap()
+    }
+
+    #[test]
+    fn it_can_delete_an_instance() {
+        let wmi_con = wmi_con();
+
+        create_test_variable(&wmi_con);
+
+        let variables = find_test_variable(&wmi_con);
+        assert_eq!(variables.len(), 1);
+
+        wmi_con.delete_instance(&variables[0].__Path).unwrap();
+
+        assert_eq!(find_test_variable(&wmi_con).len(), 0);
+
+        match wmi_con.delete_instance(&variables[0].__Path) {
+            Err(WMIError::HResultError { hres, .. }) => assert_eq!(hres, WBEM_E_NOT_FOUND.0),
+            res => panic!("Unexpected result {:?}", res),
+        }
+    }
+
+    #[derive(Serialize)]
+    struct __EventFilter {
+        Name: String,
+        Query: String,
+        QueryLanguage: String,
+        EventNamespace: String,
+    }
+
+    fn put_event_filter() -> WMIResult<()> {
+        let wmi_con = WMIConnection::with_namespace_path(
+            "ROOT\\subscription",
+            COMLibrary::without_security()?,
+        )?;
+
+        let mut filter = __EventFilter {
+            Name: "WMI_RS_TEST_FILTER".to_string(),
+            Query: "SELECT * FROM __InstanceCreationEvent WITHIN 5 WHERE TargetInstance ISA 'Win32_Process'".to_string(),
+            QueryLanguage: "WQL".to_string(),
+            EventNamespace: "ROOT\\CIMV2".to_string(),
+        };
+
+        let path = wmi_con.put_instance(&filter, PutInstanceMode::CreateOnly)?;
+        assert_eq!(path, r#"__EventFilter.Name="WMI_RS_TEST_FILTER""#);
+
+        let res = wmi_con.put_instance(&filter, PutInstanceMode::CreateOnly);
+        assert!(matches!(
+            res,
+            Err(WMIError::HResultError { hres, .. }) if hres == WBEM_E_ALREADY_EXISTS.0
+        ));
+
+        filter.Query = filter.Query.replace("WITHIN 5", "WITHIN 10");
+        let updated_path = wmi_con.put_instance(&filter, PutInstanceMode::UpdateOnly)?;
+        assert_eq!(updated_path, path);
+
+        wmi_con.delete_instance(&path)?;
+
+        let res = wmi_con.put_instance(&filter, PutInstanceMode::UpdateOnly);
+        assert!(matches!(
+            res,
+            Err(WMIError::HResultError { hres, .. }) if hres == WBEM_E_NOT_FOUND.0
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_can_put_an_instance() {
+        // Creating an event filter requires running as admin.
+        ignore_access_denied(put_event_filter()).unwrap();
+    }
+}
@@ -273,22 +273,35 @@
 #![allow(clippy::needless_lifetimes)]
 #![cfg(windows)]
 
+pub mod class;
 pub mod connection;
 
 #[cfg(feature = "chrono")]
 pub mod datetime;
 
 #[cfg(feature = "time")]
-mod datetime_time;
+pub mod datetime_time;
 
 pub mod context;
 pub mod de;
 pub mod duration;
+pub mod instance;
+pub mod live_object;
 pub mod method;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod perf;
+pub mod provider;
 pub mod query;
+pub mod queryable;
+pub mod registry;
 pub mod result_enumerator;
 pub mod safearray;
 pub mod ser;
+pub mod services;
+pub mod sid;
+pub mod subscription;
+pub mod table;
 pub mod utils;
 pub mod variant;
 
@@ -301,7 +314,8 @@ pub mod notification;
 #[cfg(any(test, feature = "test"))]
 pub mod tests;
 
-pub use connection::{COMLibrary, WMIConnection};
+pub use async_query::join_all_limited;
+pub use connection::{ApartmentModel, COMLibrary, WMIConnection, WMIThreadContext};
 
 #[cfg(feature = "chrono")]
 pub use datetime::WMIDateTime;
@@ -310,7 +324,15 @@ pub use datetime::WMIDateTime;
 pub use datetime_time::WMIOffsetDateTime;
 
 pub use duration::WMIDuration;
-pub use query::{build_notification_query, build_query, FilterValue};
+pub use method::HasPath;
+pub use query::{
+    build_associators_query, build_notification_query, build_query, build_query_ordered,
+    explain_notification_query, explain_query, AssociatorsOptions, FilterValue, SortOrder,
+};
+pub use query_sink::AsyncQueryResultStream;
+pub use queryable::WMIQueryable;
+pub use sid::Sid;
+pub use table::Table;
 pub use utils::{WMIError, WMIResult};
 pub use variant::Variant;
 
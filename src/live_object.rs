@@ -0,0 +1,122 @@
+use crate::{result_enumerator::IWbemClassWrapper, Variant, WMIConnection, WMIResult};
+use serde::de;
+use std::cell::RefCell;
+
+/// A WMI object which can be re-fetched using its path, to observe a single instance over time.
+///
+/// Properties are read from the cached copy of the object, which is only updated when calling [`LiveObject::refresh`].
+///
+/// ```edition2021
+/// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+/// # use wmi::live_object::LiveObject;
+/// # fn main() -> WMIResult<()> {
+/// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+/// let os = LiveObject::new(&wmi_con, r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)?;
+///
+/// for _ in 0..3 {
+///     let free_memory: u64 = os.get("FreePhysicalMemory")?;
+///     println!("Free memory: {} KB", free_memory);
+///
+///     os.refresh(&wmi_con)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LiveObject {
+    path: String,
+    object: RefCell<IWbemClassWrapper>,
+}
+
+impl LiveObject {
+    /// Fetch the object at `object_path` using [`WMIConnection::get_raw_by_path`].
+    pub fn new(wmi_con: &WMIConnection, object_path: impl Into<String>) -> WMIResult<Self> {
+        let path = object_path.into();
+        let object = wmi_con.get_raw_by_path(&path)?;
+
+        Ok(Self {
+            path,
+            object: RefCell::new(object),
+        })
+    }
+
+    /// The path used to fetch the object.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Re-fetch the object, updating the cached properties.
+    ///
+    /// If fetching the object fails (for example, because the instance was deleted),
+    /// the previously cached properties are kept.
+    pub fn refresh(&self, wmi_con: &WMIConnection) -> WMIResult<()> {
+        let object = wmi_con.get_raw_by_path(&self.path)?;
+        self.object.replace(object);
+
+        Ok(())
+    }
+
+    /// Get the cached value of a property as a [`Variant`].
+    pub fn get_property(&self, property_name: &str) -> WMIResult<Variant> {
+        self.object.borrow().get_property(property_name)
+    }
+
+    /// Get the cached value of a property, deserialized into `T`.
+    pub fn get<T>(&self, property_name: &str) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        T::deserialize(self.get_property(property_name)?)
+    }
+
+    /// Deserialize the cached object into `T`, like [`IWbemClassWrapper::into_desr`].
+    pub fn desr<T>(&self) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        self.object().into_desr()
+    }
+
+    /// A copy of the cached object, which is not updated by later calls to [`LiveObject::refresh`].
+    pub fn object(&self) -> IWbemClassWrapper {
+        self.object.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::wmi_con;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_OperatingSystem {
+        Caption: String,
+        FreePhysicalMemory: u64,
+    }
+
+    #[test]
+    fn it_can_refresh_a_live_object() {
+        let wmi_con = wmi_con();
+
+        let os = LiveObject::new(&wmi_con, r#"\\.\root\cimv2:Win32_OperatingSystem=@"#).unwrap();
+
+        assert_eq!(os.path(), r#"\\.\root\cimv2:Win32_OperatingSystem=@"#);
+
+        let before: Win32_OperatingSystem = os.desr().unwrap();
+        assert!(before.Caption.contains("Microsoft "));
+
+        os.refresh(&wmi_con).unwrap();
+
+        let after: Win32_OperatingSystem = os.desr().unwrap();
+        assert_eq!(after.Caption, before.Caption);
+        assert!(after.FreePhysicalMemory > 0);
+
+        let caption: String = os.get("Caption").unwrap();
+        assert_eq!(caption, before.Caption);
+        assert_eq!(
+            os.get_property("Caption").unwrap(),
+            Variant::String(before.Caption)
+        );
+    }
+}
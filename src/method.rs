@@ -1,13 +1,126 @@
 use std::collections::HashMap;
 
+use futures::StreamExt;
 use serde::{de, Serialize};
-use windows_core::{BSTR, HSTRING, VARIANT};
+use windows::Win32::System::Wmi::{IWbemClassObject, IWbemObjectSink, CIMTYPE_ENUMERATION};
+use windows_core::{BSTR, HSTRING, PCWSTR};
 
 use crate::{
-    de::meta::struct_name_and_fields, result_enumerator::IWbemClassWrapper,
-    ser::variant_ser::VariantStructSerializer, Variant, WMIConnection, WMIError, WMIResult,
+    de::meta::struct_name_and_fields,
+    query_sink::{AsyncQueryResultStream, AsyncQueryResultStreamInner, QuerySink},
+    result_enumerator::IWbemClassWrapper,
+    ser::variant_ser::VariantStructSerializer,
+    Variant, WMIConnection, WMIError, WMIResult,
 };
 
+/// The input and output parameters of a WMI method, as returned by [`IWbemClassWrapper::get_method_signature`].
+///
+/// Each parameter is a pair of the parameter's name and its CIM type.
+/// Methods with a return type other than `void` have an additional `ReturnValue` output parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MethodSignature {
+    pub in_params: Vec<(String, CIMTYPE_ENUMERATION)>,
+    pub out_params: Vec<(String, CIMTYPE_ENUMERATION)>,
+}
+
+/// An object which knows its WMI object path, like a struct with a `__Path` field.
+///
+/// Used by [`WMIConnection::exec_method_on`] to execute methods on objects returned by a query.
+///
+/// ```edition2021
+/// # use serde::Deserialize;
+/// # use wmi::HasPath;
+/// #[derive(Deserialize)]
+/// # #[allow(non_camel_case_types, non_snake_case)]
+/// struct Win32_Process {
+///     __Path: String,
+///     Name: String,
+/// }
+///
+/// impl HasPath for Win32_Process {
+///     fn path(&self) -> &str {
+///         &self.__Path
+///     }
+/// }
+/// ```
+pub trait HasPath {
+    /// The object's path (the value of its `__Path` property).
+    fn path(&self) -> &str;
+}
+
+impl IWbemClassWrapper {
+    /// Get the names and types of the input and output parameters of a method,
+    /// where `self` is a class definition (which can be obtained using [`WMIConnection::get_raw_by_path`] with the class name).
+    ///
+    /// See [IWbemClassObject::GetMethod](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemclassobject-getmethod).
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let class = wmi_con.get_raw_by_path("Win32_Process")?;
+    ///
+    /// for method in class.list_methods()? {
+    ///     let signature = class.get_method_signature(&method)?;
+    ///     println!("{method}: {:?} -> {:?}", signature.in_params, signature.out_params);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_method_signature(&self, method: &str) -> WMIResult<MethodSignature> {
+        let method = HSTRING::from(method);
+
+        let mut input_signature = None;
+        let mut output_signature = None;
+
+        unsafe {
+            self.inner.GetMethod(
+                &method,
+                Default::default(),
+                &mut input_signature,
+                &mut output_signature,
+            )?;
+        }
+
+        Ok(MethodSignature {
+            in_params: method_params(input_signature)?,
+            out_params: method_params(output_signature)?,
+        })
+    }
+}
+
+fn method_params(
+    signature: Option<IWbemClassObject>,
+) -> WMIResult<Vec<(String, CIMTYPE_ENUMERATION)>> {
+    // Methods without input or output parameters have no signature object.
+    let Some(signature) = signature else {
+        return Ok(vec![]);
+    };
+
+    let signature = IWbemClassWrapper::new(signature);
+
+    signature
+        .list_properties()?
+        .into_iter()
+        .map(|name| {
+            let name_prop = HSTRING::from(&name);
+            let mut cim_type = 0;
+
+            unsafe {
+                signature.inner.Get(
+                    PCWSTR::from_raw(name_prop.as_ptr()),
+                    0,
+                    std::ptr::null_mut(),
+                    Some(&mut cim_type),
+                    None,
+                )?;
+            }
+
+            Ok((name, CIMTYPE_ENUMERATION(cim_type)))
+        })
+        .collect()
+}
+
 impl WMIConnection {
     /// Wrapper for WMI's [ExecMethod](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-execmethod) function.
     ///
@@ -41,10 +154,80 @@ impl WMIConnection {
         method: impl AsRef<str>,
         in_params: HashMap<String, Variant>,
     ) -> WMIResult<Option<IWbemClassWrapper>> {
-        let method_class = BSTR::from(method_class.as_ref());
         let object_path = BSTR::from(object_path.as_ref());
         let method = BSTR::from(method.as_ref());
 
+        let in_params = self.method_in_params(method_class.as_ref(), &method, in_params)?;
+
+        // In the case of a method with no out parameters and a VOID return type, there will be no out-parameters object
+        let mut output = None;
+        unsafe {
+            self.svc.ExecMethod(
+                &object_path,
+                &method,
+                Default::default(),
+                &self.ctx.0,
+                in_params.as_ref(),
+                Some(&mut output),
+                None,
+            )?;
+        }
+
+        Ok(output.map(IWbemClassWrapper::new))
+    }
+
+    /// Like [`WMIConnection::exec_method_native_wrapper`], but returns the out parameters (including the `ReturnValue`) as a map,
+    /// which is useful for exploring the outputs of a method without defining a struct for them.
+    ///
+    /// Returns an empty map if the method has no out parameters and a `void` return type.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, Variant, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let in_params = [
+    ///     ("CommandLine".to_string(), Variant::from("explorer.exe".to_string()))
+    /// ].into_iter().collect();
+    ///
+    /// let out = wmi_con.exec_method_to_map("Win32_Process", "Win32_Process", "Create", in_params)?;
+    /// println!("{:?}", out);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn exec_method_to_map(
+        &self,
+        method_class: impl AsRef<str>,
+        object_path: impl AsRef<str>,
+        method: impl AsRef<str>,
+        in_params: HashMap<String, Variant>,
+    ) -> WMIResult<HashMap<String, Variant>> {
+        let output =
+            self.exec_method_native_wrapper(method_class, object_path, method, in_params)?;
+
+        match output {
+            Some(output) => output.into_desr(),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    /// Create the input parameters object of a method, with every field set to the corresponding input parameter.
+    ///
+    /// Returns `None` if the method has no input parameters, or if no input parameters were passed,
+    /// in which case no instance is spawned.
+    fn method_in_params(
+        &self,
+        method_class: &str,
+        method: &BSTR,
+        in_params: HashMap<String, Variant>,
+    ) -> WMIResult<Option<IWbemClassObject>> {
+        // When the input (e.g. `()`) has no fields, there is nothing to set on the input parameters object,
+        // so the method's class definition is not fetched and `ExecMethod` is called without one.
+        if in_params.is_empty() {
+            return Ok(None);
+        }
+
+        let method_class = BSTR::from(method_class);
+
         // See https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemclassobject-getmethod
         // GetMethod can only be called on a class definition, so we retrieve that before retrieving a specific object
         let mut class_definition = None;
@@ -63,7 +246,7 @@ impl WMIConnection {
         let mut input_signature = None;
         unsafe {
             class_definition.GetMethod(
-                &method,
+                method,
                 Default::default(),
                 &mut input_signature,
                 std::ptr::null_mut(),
@@ -73,42 +256,20 @@ impl WMIConnection {
         // The method may have no input parameters, such as in this case: https://learn.microsoft.com/en-us/windows/win32/cimwin32prov/reboot-method-in-class-win32-operatingsystem
         let in_params = match input_signature {
             Some(input) => {
-                let inst;
-                unsafe {
-                    inst = input.SpawnInstance(Default::default())?;
-                };
+                let inst =
+                    IWbemClassWrapper::new(unsafe { input.SpawnInstance(Default::default())? });
+
                 // Set every field of the input object to the corresponding input parameter passed to this function
-                for (wszname, value) in in_params {
-                    let wszname = HSTRING::from(wszname);
-                    let value = TryInto::<VARIANT>::try_into(value)?;
-
-                    // See https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemclassobject-put
-                    // Note that the example shows the variant is expected to be cleared (dropped) after the call to Put,
-                    // so passing &value is acceptable here
-                    unsafe {
-                        inst.Put(&wszname, Default::default(), &value, 0)?;
-                    }
+                for (name, value) in in_params {
+                    inst.put_property(&name, value)?;
                 }
-                Some(inst)
+
+                Some(inst.inner)
             }
             None => None,
         };
 
-        // In the case of a method with no out parameters and a VOID return type, there will be no out-parameters object
-        let mut output = None;
-        unsafe {
-            self.svc.ExecMethod(
-                &object_path,
-                &method,
-                Default::default(),
-                &self.ctx.0,
-                in_params.as_ref(),
-                Some(&mut output),
-                None,
-            )?;
-        }
-
-        Ok(output.map(IWbemClassWrapper::new))
+        Ok(in_params)
     }
 
     /// Executes a method of a WMI class not tied to any specific instance. Examples include
@@ -121,7 +282,10 @@ impl WMIConnection {
     /// A method with a return type other than `void` will always try to populate a generic property named `ReturnValue` in the output object with the return value of the WMI method call.
     /// If the method call has a `void` return type and no out parameters, the only acceptable type for `Out` is `()`.
     ///
-    /// Arrays, Options, unknowns, and nested objects cannot be passed as input parameters due to limitations in how variants are constructed by `windows-rs`.
+    /// Input parameters can be basic types (numbers, strings, `bool`s, unit enum variants), sequences of these types (like `Vec<String>`),
+    /// and nested structs, which are created as new instances of the WMI class with the same name as the struct.
+    /// Optional parameters can be `Option`s, where `None` sets the parameter to `NULL`.
+    /// Unknowns cannot be passed as input parameters.
     ///
     /// This function uses [`WMIConnection::exec_instance_method`] internally, with the name of the method class being the instance path, as is expected by WMI.
     ///
@@ -183,7 +347,10 @@ impl WMIConnection {
     /// A method with a return type other than `void` will always try to populate a generic property named `ReturnValue` in the output object with the return value of the WMI method call.
     /// If the method call has a `void` return type and no out parameters, the only acceptable type for `Out` is `()`.
     ///
-    /// Arrays, Options, unknowns, and nested objects cannot be passed as input parameters due to limitations in how variants are constructed by `windows-rs`.
+    /// Input parameters can be basic types (numbers, strings, `bool`s, unit enum variants), sequences of these types (like `Vec<String>`),
+    /// and nested structs, which are created as new instances of the WMI class with the same name as the struct.
+    /// Optional parameters can be `Option`s, where `None` sets the parameter to `NULL`.
+    /// Unknowns cannot be passed as input parameters.
     ///
     /// ```edition2021
     /// # use serde::{Deserialize, Serialize};
@@ -226,28 +393,241 @@ impl WMIConnection {
         Out: de::DeserializeOwned,
     {
         let (method_class, _) = struct_name_and_fields::<MethodClass>()?;
-        let serializer = VariantStructSerializer::new();
-        match in_params.serialize(serializer) {
-            Ok(field_map) => {
-                let output =
-                    self.exec_method_native_wrapper(method_class, object_path, method, field_map)?;
-
-                match output {
-                    Some(class_wrapper) => Ok(class_wrapper.into_desr()?),
-                    None => Out::deserialize(Variant::Empty),
-                }
+        let field_map = self.serialize_in_params(in_params)?;
+
+        let output =
+            self.exec_method_native_wrapper(method_class, object_path, method, field_map)?;
+
+        desr_out_params(output)
+    }
+
+    /// Executes a WMI method on an object returned by a query, using its path (see [`HasPath`]).
+    ///
+    /// The struct of the object should have the name of its class (like in [`WMIConnection::query`]),
+    /// which is used as the `MethodClass` of [`WMIConnection::exec_instance_method`].
+    ///
+    /// ```edition2021
+    /// # use serde::Deserialize;
+    /// # use wmi::{COMLibrary, HasPath, WMIConnection, WMIResult};
+    /// #[derive(Deserialize)]
+    /// # #[allow(non_camel_case_types, non_snake_case)]
+    /// struct Win32_Process {
+    ///     __Path: String,
+    ///     Name: String,
+    /// }
+    ///
+    /// impl HasPath for Win32_Process {
+    ///     fn path(&self) -> &str {
+    ///         &self.__Path
+    ///     }
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// # #[allow(non_snake_case)]
+    /// struct GetOwnerOutput {
+    ///     ReturnValue: u32,
+    ///     User: Option<String>,
+    /// }
+    ///
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// for process in wmi_con.query::<Win32_Process>()? {
+    ///     let owner: GetOwnerOutput = wmi_con.exec_method_on(&process, "GetOwner", ())?;
+    ///     println!("{} is owned by {:?}", process.Name, owner.User);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn exec_method_on<T, In, Out>(
+        &self,
+        object: &T,
+        method: impl AsRef<str>,
+        in_params: In,
+    ) -> WMIResult<Out>
+    where
+        T: HasPath + de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned,
+    {
+        self.exec_instance_method::<T, In, Out>(method, object.path(), in_params)
+    }
+
+    /// Async version of [`WMIConnection::exec_method_native_wrapper`], using WMI's
+    /// [ExecMethodAsync](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-execmethodasync) function.
+    ///
+    /// The call is cancelled if the returned future is dropped before it completes.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, Variant, WMIConnection, WMIResult};
+    /// # use futures::executor::block_on;
+    /// # fn main() -> WMIResult<()> {
+    /// #   block_on(exec_async_method())
+    /// # }
+    /// # async fn exec_async_method() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let in_params = [
+    ///     ("CommandLine".to_string(), Variant::from("explorer.exe".to_string()))
+    /// ].into_iter().collect();
+    ///
+    /// let out = wmi_con.async_exec_method_native_wrapper("Win32_Process", "Win32_Process", "Create", in_params).await?.unwrap();
+    /// println!("The return code of the Create call is {:?}", out.get_property("ReturnValue")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn async_exec_method_native_wrapper(
+        &self,
+        method_class: impl AsRef<str>,
+        object_path: impl AsRef<str>,
+        method: impl AsRef<str>,
+        in_params: HashMap<String, Variant>,
+    ) -> WMIResult<Option<IWbemClassWrapper>> {
+        let object_path = BSTR::from(object_path.as_ref());
+        let method = BSTR::from(method.as_ref());
+
+        let in_params = self.method_in_params(method_class.as_ref(), &method, in_params)?;
+
+        let stream = AsyncQueryResultStreamInner::new();
+        // The internal RefCount has initial value = 1.
+        let p_sink = QuerySink {
+            stream: stream.clone(),
+        };
+        let p_sink_handle: IWbemObjectSink = p_sink.into();
+
+        unsafe {
+            self.svc.ExecMethodAsync(
+                &object_path,
+                &method,
+                Default::default(),
+                &self.ctx.0,
+                in_params.as_ref(),
+                &p_sink_handle,
+            )?;
+        }
+
+        // The stream cancels the call when dropped.
+        let mut results = AsyncQueryResultStream::new(stream.clone(), self.clone(), p_sink_handle);
+
+        // In the case of a method with no out parameters and a VOID return type, there will be no out-parameters object
+        let output = results.next().await.transpose()?;
+
+        // The status of the call is only available once the stream is done.
+        if output.is_some() {
+            while let Some(item) = results.next().await {
+                item?;
             }
-            Err(e) => Err(WMIError::ConvertVariantError(e.to_string())),
         }
+
+        stream.status().ok()?;
+
+        Ok(output)
+    }
+
+    /// Async version of [`WMIConnection::exec_class_method`].
+    ///
+    /// ```edition2021
+    /// # use serde::{Deserialize, Serialize};
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # use futures::executor::block_on;
+    /// #[derive(Serialize)]
+    /// # #[allow(non_snake_case)]
+    /// struct CreateInput {
+    ///     CommandLine: String
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// # #[allow(non_snake_case)]
+    /// struct CreateOutput {
+    ///     ReturnValue: u32,
+    ///     ProcessId: u32
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// # #[allow(non_camel_case_types)]
+    /// struct Win32_Process;
+    ///
+    /// # fn main() -> WMIResult<()> {
+    /// #   block_on(exec_async_method())
+    /// # }
+    /// # async fn exec_async_method() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let input = CreateInput {
+    ///     CommandLine: "explorer.exe".to_string()
+    /// };
+    /// let output: CreateOutput = wmi_con.async_exec_class_method::<Win32_Process, _, _>("Create", input).await?;
+    ///
+    /// println!("The ID of the created process is: {}", output.ProcessId);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn async_exec_class_method<MethodClass, In, Out>(
+        &self,
+        method: impl AsRef<str>,
+        in_params: In,
+    ) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned,
+    {
+        let (method_class, _) = struct_name_and_fields::<MethodClass>()?;
+        self.async_exec_instance_method::<MethodClass, In, Out>(method, method_class, in_params)
+            .await
+    }
+
+    /// Async version of [`WMIConnection::exec_instance_method`].
+    pub async fn async_exec_instance_method<MethodClass, In, Out>(
+        &self,
+        method: impl AsRef<str>,
+        object_path: impl AsRef<str>,
+        in_params: In,
+    ) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned,
+    {
+        let (method_class, _) = struct_name_and_fields::<MethodClass>()?;
+        let field_map = self.serialize_in_params(in_params)?;
+
+        let output = self
+            .async_exec_method_native_wrapper(method_class, object_path, method, field_map)
+            .await?;
+
+        desr_out_params(output)
+    }
+
+    fn serialize_in_params<In>(&self, in_params: In) -> WMIResult<HashMap<String, Variant>>
+    where
+        In: Serialize,
+    {
+        let serializer = VariantStructSerializer::with_connection(self);
+
+        in_params
+            .serialize(serializer)
+            .map_err(|e| WMIError::ConvertVariantError(e.to_string()))
+    }
+}
+
+fn desr_out_params<Out>(output: Option<IWbemClassWrapper>) -> WMIResult<Out>
+where
+    Out: de::DeserializeOwned,
+{
+    match output {
+        Some(class_wrapper) => Ok(class_wrapper.into_desr()?),
+        None => Out::deserialize(Variant::Empty),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::HasPath;
     use crate::tests::fixtures::wmi_con;
+    use crate::{FilterValue, Variant};
     use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
     use std::thread::sleep;
     use std::time::Duration;
+    use windows::Win32::System::Wmi::{CIM_STRING, CIM_UINT32};
 
     #[derive(Deserialize)]
     struct Win32_Process {
@@ -266,6 +646,34 @@ mod tests {
         ProcessId: u32,
     }
 
+    #[async_std::test]
+    async fn async_it_exec_methods() {
+        let wmi_con = wmi_con();
+        let in_params = CreateParams {
+            CommandLine: "explorer.exe".to_string(),
+        };
+        let out = wmi_con
+            .async_exec_class_method::<Win32_Process, CreateParams, CreateOutput>(
+                "Create", in_params,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(out.ReturnValue, 0);
+
+        let query = format!(
+            "SELECT * FROM Win32_Process WHERE ProcessId = {}",
+            out.ProcessId
+        );
+
+        let process = &wmi_con.raw_query::<Win32_Process>(&query).unwrap()[0];
+
+        wmi_con
+            .async_exec_instance_method::<Win32_Process, (), ()>("Terminate", &process.__Path, ())
+            .await
+            .unwrap();
+    }
+
     #[test]
     fn it_exec_methods() {
         let wmi_con = wmi_con();
@@ -299,4 +707,257 @@ mod tests {
 
         assert!(wmi_con.raw_query::<Win32_Process>(&query).unwrap().len() == 0);
     }
+
+    #[test]
+    fn it_exec_methods_on_objects_with_path() {
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            __Path: String,
+            ProcessId: u32,
+        }
+
+        impl HasPath for Win32_Process {
+            fn path(&self) -> &str {
+                &self.__Path
+            }
+        }
+
+        let wmi_con = wmi_con();
+
+        let filters = HashMap::from([(
+            "ProcessId".to_string(),
+            FilterValue::Number(std::process::id() as i64),
+        )]);
+        let process: Win32_Process = wmi_con
+            .filtered_query(&filters)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+
+        let owner: GetOwnerOutput = wmi_con.exec_method_on(&process, "GetOwner", ()).unwrap();
+
+        assert_eq!(owner.ReturnValue, 0);
+        assert!(!owner.User.unwrap().is_empty());
+        assert!(!owner.Domain.unwrap().is_empty());
+    }
+
+    #[test]
+    fn it_exec_methods_to_map() {
+        let wmi_con = wmi_con();
+
+        let in_params = HashMap::from([(
+            "CommandLine".to_string(),
+            Variant::from("explorer.exe".to_string()),
+        )]);
+
+        let out = wmi_con
+            .exec_method_to_map("Win32_Process", "Win32_Process", "Create", in_params)
+            .unwrap();
+
+        assert_eq!(out.get("ReturnValue"), Some(&Variant::UI4(0)));
+
+        let process_id = match out.get("ProcessId") {
+            Some(Variant::UI4(process_id)) => *process_id,
+            other => panic!("Unexpected ProcessId {:?}", other),
+        };
+
+        let query = format!(
+            "SELECT * FROM Win32_Process WHERE ProcessId = {}",
+            process_id
+        );
+        let process = &wmi_con.raw_query::<Win32_Process>(&query).unwrap()[0];
+
+        let out = wmi_con
+            .exec_method_to_map(
+                "Win32_Process",
+                &process.__Path,
+                "Terminate",
+                HashMap::new(),
+            )
+            .unwrap();
+
+        assert_eq!(out.get("ReturnValue"), Some(&Variant::UI4(0)));
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(non_snake_case)]
+    struct GetOwnerOutput {
+        ReturnValue: u32,
+        User: Option<String>,
+        Domain: Option<String>,
+    }
+
+    #[test]
+    fn it_exec_methods_without_in_params() {
+        let wmi_con = wmi_con();
+
+        let query = format!(
+            "SELECT * FROM Win32_Process WHERE ProcessId = {}",
+            std::process::id()
+        );
+        let process = &wmi_con.raw_query::<Win32_Process>(&query).unwrap()[0];
+
+        let out: GetOwnerOutput = wmi_con
+            .exec_instance_method::<Win32_Process, _, _>("GetOwner", &process.__Path, ())
+            .unwrap();
+
+        assert_eq!(out.ReturnValue, 0);
+        assert!(!out.User.unwrap().is_empty());
+
+        let out = wmi_con
+            .exec_method_native_wrapper(
+                "Win32_Process",
+                &process.__Path,
+                "GetOwner",
+                HashMap::new(),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(out.get_property("ReturnValue").unwrap(), Variant::UI4(0));
+    }
+
+    #[derive(Deserialize)]
+    struct StdRegProv;
+
+    const HKEY_CURRENT_USER: u32 = 0x80000001;
+    const TEST_SUB_KEY: &str = r"Software\wmi-rs-test";
+
+    #[derive(Serialize)]
+    #[allow(non_snake_case)]
+    struct KeyParams {
+        hDefKey: u32,
+        sSubKeyName: String,
+    }
+
+    #[derive(Serialize)]
+    #[allow(non_snake_case)]
+    struct SetMultiStringValueParams {
+        hDefKey: u32,
+        sSubKeyName: String,
+        sValueName: String,
+        sValue: Vec<String>,
+    }
+
+    #[derive(Serialize)]
+    #[allow(non_snake_case)]
+    struct GetValueParams {
+        hDefKey: u32,
+        sSubKeyName: String,
+        sValueName: String,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(non_snake_case)]
+    struct ReturnValueOutput {
+        ReturnValue: u32,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(non_snake_case)]
+    struct GetMultiStringValueOutput {
+        ReturnValue: u32,
+        sValue: Vec<String>,
+    }
+
+    #[test]
+    fn it_exec_methods_with_array_params() {
+        let wmi_con = wmi_con();
+
+        let key_params = || KeyParams {
+            hDefKey: HKEY_CURRENT_USER,
+            sSubKeyName: TEST_SUB_KEY.to_string(),
+        };
+
+        let out: ReturnValueOutput = wmi_con
+            .exec_class_method::<StdRegProv, _, _>("CreateKey", key_params())
+            .unwrap();
+        assert_eq!(out.ReturnValue, 0);
+
+        let value = vec!["first".to_string(), "second".to_string()];
+
+        let out: ReturnValueOutput = wmi_con
+            .exec_class_method::<StdRegProv, _, _>(
+                "SetMultiStringValue",
+                SetMultiStringValueParams {
+                    hDefKey: HKEY_CURRENT_USER,
+                    sSubKeyName: TEST_SUB_KEY.to_string(),
+                    sValueName: "Value".to_string(),
+                    sValue: value.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(out.ReturnValue, 0);
+
+        let out: GetMultiStringValueOutput = wmi_con
+            .exec_class_method::<StdRegProv, _, _>(
+                "GetMultiStringValue",
+                GetValueParams {
+                    hDefKey: HKEY_CURRENT_USER,
+                    sSubKeyName: TEST_SUB_KEY.to_string(),
+                    sValueName: "Value".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(out.ReturnValue, 0);
+        assert_eq!(out.sValue, value);
+
+        let out: ReturnValueOutput = wmi_con
+            .exec_class_method::<StdRegProv, _, _>("DeleteKey", key_params())
+            .unwrap();
+        assert_eq!(out.ReturnValue, 0);
+    }
+
+    #[test]
+    fn it_lists_class_methods() {
+        let wmi_con = wmi_con();
+
+        let methods = wmi_con
+            .get_raw_by_path("Win32_Process")
+            .unwrap()
+            .list_methods()
+            .unwrap();
+
+        assert!(methods.contains(&"Create".to_string()));
+        assert!(methods.contains(&"Terminate".to_string()));
+
+        let methods = wmi_con
+            .get_raw_by_path("Win32_OperatingSystem")
+            .unwrap()
+            .list_methods()
+            .unwrap();
+        assert!(methods.contains(&"Reboot".to_string()));
+
+        let methods = wmi_con
+            .get_raw_by_path("__Namespace")
+            .unwrap()
+            .list_methods()
+            .unwrap();
+        assert!(methods.is_empty());
+    }
+
+    #[test]
+    fn it_gets_method_signature() {
+        let wmi_con = wmi_con();
+
+        let class = wmi_con.get_raw_by_path("Win32_Process").unwrap();
+
+        let signature = class.get_method_signature("Create").unwrap();
+
+        assert!(signature
+            .in_params
+            .contains(&("CommandLine".to_string(), CIM_STRING)));
+        assert!(signature
+            .out_params
+            .contains(&("ProcessId".to_string(), CIM_UINT32)));
+        assert!(signature
+            .out_params
+            .contains(&("ReturnValue".to_string(), CIM_UINT32)));
+
+        let signature = class.get_method_signature("GetOwnerSid").unwrap();
+        assert!(signature.in_params.is_empty());
+
+        assert!(class.get_method_signature("NoSuchMethod").is_err());
+    }
 }
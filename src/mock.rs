@@ -0,0 +1,521 @@
+//! An offline connection, for unit testing code which queries WMI (requires the `mock` feature).
+//!
+//! Code which is generic over [`WMIQueryable`] can be called with a [`WMIConnection`](crate::WMIConnection)
+//! in production, and with a [`MockWMIConnection`] returning canned data in tests.
+//!
+//! Like the rest of the crate, the mock is only available on Windows, so tests using it must run on Windows as well.
+//!
+//! ```edition2021
+//! # use wmi::mock::MockWMIConnection;
+//! # use wmi::{Variant, WMIResult, WMIQueryable};
+//! # use serde::Deserialize;
+//! # use std::collections::HashMap;
+//! #[derive(Deserialize, Debug)]
+//! struct Win32_Process {
+//!     Name: String,
+//! }
+//!
+//! fn process_names(con: &impl WMIQueryable) -> WMIResult<Vec<String>> {
+//!     let processes: Vec<Win32_Process> = con.query()?;
+//!
+//!     Ok(processes.into_iter().map(|process| process.Name).collect())
+//! }
+//!
+//! let mut con = MockWMIConnection::new();
+//! con.add_object(
+//!     "Win32_Process",
+//!     HashMap::from([("Name".to_owned(), Variant::String("explorer.exe".to_owned()))]),
+//! );
+//!
+//! assert_eq!(process_names(&con).unwrap(), vec!["explorer.exe"]);
+//! ```
+
+use crate::{
+    de::meta::struct_name_and_fields, FilterValue, Variant, WMIError, WMIQueryable, WMIResult,
+};
+use serde::{
+    de::{self, value::MapDeserializer},
+    Serialize,
+};
+use std::collections::HashMap;
+use windows::{core::HRESULT, Win32::System::Wmi::WBEM_E_NOT_FOUND};
+
+/// The properties of a mocked WMI object, by name.
+pub type MockObject = HashMap<String, Variant>;
+
+/// A [`WMIQueryable`] which returns canned objects instead of querying WMI.
+///
+/// - Objects are added per class using [`MockWMIConnection::add_object`],
+///   and are returned by [`WMIQueryable::query`] and [`WMIQueryable::filtered_query`] for structs with that name.
+/// - Filters are evaluated against the objects' properties (`ISA` filters are not supported).
+///   Like in WQL, string comparisons are case-insensitive, and `LIKE` patterns support `%` and `_`.
+/// - [`WMIQueryable::raw_query`] returns results registered using [`MockWMIConnection::add_query_result`],
+///   or all the objects of the class for `SELECT ... FROM <class>` queries without a `WHERE` clause.
+/// - [`WMIQueryable::get_by_path`] returns the object whose `__PATH` or `__RELPATH` property matches the path,
+///   or an `WBEM_E_NOT_FOUND` error like a real connection.
+/// - [`WMIQueryable::exec_instance_method`] and [`WMIQueryable::exec_class_method`] return the output parameters
+///   registered using [`MockWMIConnection::add_method_result`]. The input parameters are ignored.
+#[derive(Debug, Default, Clone)]
+pub struct MockWMIConnection {
+    // Keyed by the lowercase class name.
+    classes: HashMap<String, Vec<MockObject>>,
+    queries: HashMap<String, Vec<MockObject>>,
+    // Keyed by the lowercase object path (or class name) and method name.
+    method_results: HashMap<(String, String), MockObject>,
+}
+
+impl MockWMIConnection {
+    /// Create a connection without any objects.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an object of the given class. The `__CLASS` property is set to the class name if it is missing.
+    pub fn add_object(&mut self, class_name: &str, mut object: MockObject) -> &mut Self {
+        object
+            .entry("__CLASS".to_owned())
+            .or_insert_with(|| Variant::String(class_name.to_owned()));
+
+        self.classes
+            .entry(class_name.to_ascii_lowercase())
+            .or_default()
+            .push(object);
+
+        self
+    }
+
+    /// Register the results of a specific query, which will be returned by [`WMIQueryable::raw_query`].
+    ///
+    /// The query text must match exactly (ignoring leading and trailing whitespace).
+    pub fn add_query_result(&mut self, query: &str, objects: Vec<MockObject>) -> &mut Self {
+        self.queries.insert(query.trim().to_owned(), objects);
+
+        self
+    }
+
+    /// Register the output parameters of a method, which will be returned when executing it on the object at `object_path`.
+    ///
+    /// For static methods (executed using [`WMIQueryable::exec_class_method`]), use the class name as the path.
+    pub fn add_method_result(
+        &mut self,
+        object_path: &str,
+        method: &str,
+        out_params: MockObject,
+    ) -> &mut Self {
+        self.method_results.insert(
+            (object_path.to_lowercase(), method.to_lowercase()),
+            out_params,
+        );
+
+        self
+    }
+
+    fn objects_of_class(&self, class_name: &str) -> &[MockObject] {
+        self.classes
+            .get(&class_name.to_ascii_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl WMIQueryable for MockWMIConnection {
+    fn raw_query<T>(&self, query: &str) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        if let Some(objects) = self.queries.get(query.trim()) {
+            return objects.iter().map(from_mock_object).collect();
+        }
+
+        let mut tokens = query
+            .split_whitespace()
+            .skip_while(|token| !token.eq_ignore_ascii_case("FROM"))
+            .skip(1);
+
+        let class_name = match (tokens.next(), tokens.next()) {
+            (Some(class_name), None) => class_name,
+            _ => {
+                return Err(WMIError::MockUnsupportedError(format!(
+                    "query {:?} was not registered and is not a simple SELECT query",
+                    query
+                )))
+            }
+        };
+
+        self.objects_of_class(class_name)
+            .iter()
+            .map(from_mock_object)
+            .collect()
+    }
+
+    fn filtered_query<T>(&self, filters: &HashMap<String, FilterValue>) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let (class_name, _fields) = struct_name_and_fields::<T>()?;
+
+        let mut results = vec![];
+
+        for object in self.objects_of_class(class_name) {
+            if matches_filters(object, filters)? {
+                results.push(from_mock_object(object)?);
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn get_by_path<T>(&self, object_path: &str) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        let object = self
+            .classes
+            .values()
+            .flatten()
+            .find(|object| {
+                ["__PATH", "__RELPATH"].iter().any(|path_property| {
+                    match object.get(*path_property) {
+                        Some(Variant::String(path)) => path.eq_ignore_ascii_case(object_path),
+                        _ => false,
+                    }
+                })
+            })
+            .ok_or_else(|| windows::core::Error::from(HRESULT(WBEM_E_NOT_FOUND.0)))?;
+
+        from_mock_object(object)
+    }
+
+    fn exec_class_method<MethodClass, In, Out>(&self, method: &str, in_params: In) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned,
+    {
+        let (method_class, _) = struct_name_and_fields::<MethodClass>()?;
+
+        self.exec_instance_method::<MethodClass, In, Out>(method, method_class, in_params)
+    }
+
+    fn exec_instance_method<MethodClass, In, Out>(
+        &self,
+        method: &str,
+        object_path: &str,
+        _in_params: In,
+    ) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned,
+    {
+        let out_params = self
+            .method_results
+            .get(&(object_path.to_lowercase(), method.to_lowercase()))
+            .ok_or_else(|| {
+                WMIError::MockUnsupportedError(format!(
+                    "no result was registered for method {} of {}",
+                    method, object_path
+                ))
+            })?;
+
+        from_mock_object(out_params)
+    }
+}
+
+fn from_mock_object<T>(object: &MockObject) -> WMIResult<T>
+where
+    T: de::DeserializeOwned,
+{
+    let deserializer: MapDeserializer<_, WMIError> =
+        MapDeserializer::new(object.clone().into_iter());
+
+    T::deserialize(deserializer)
+}
+
+fn matches_filters(object: &MockObject, filters: &HashMap<String, FilterValue>) -> WMIResult<bool> {
+    for (field, filter) in filters {
+        let value = object
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(field))
+            .map(|(_, value)| value);
+
+        let Some(value) = value else {
+            return Ok(false);
+        };
+
+        let is_match = match filter {
+            FilterValue::Bool(b) => value == &Variant::Bool(*b),
+            FilterValue::Number(n) => variant_as_i128(value) == Some(*n as i128),
+            FilterValue::Str(s) => variant_as_lowercase_str(value) == Some(s.to_lowercase()),
+            FilterValue::String(s) => variant_as_lowercase_str(value) == Some(s.to_lowercase()),
+            FilterValue::StrLike(pattern) => is_like(value, pattern),
+            FilterValue::StringLike(pattern) => is_like(value, pattern),
+            FilterValue::IsA(_) => {
+                return Err(WMIError::MockUnsupportedError(format!(
+                    "ISA filter on {}",
+                    field
+                )))
+            }
+        };
+
+        if !is_match {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+fn variant_as_i128(value: &Variant) -> Option<i128> {
+    match *value {
+        Variant::I1(n) => Some(n as i128),
+        Variant::I2(n) => Some(n as i128),
+        Variant::I4(n) => Some(n as i128),
+        Variant::I8(n) => Some(n as i128),
+        Variant::UI1(n) => Some(n as i128),
+        Variant::UI2(n) => Some(n as i128),
+        Variant::UI4(n) => Some(n as i128),
+        Variant::UI8(n) => Some(n as i128),
+        _ => None,
+    }
+}
+
+fn variant_as_lowercase_str(value: &Variant) -> Option<String> {
+    match value {
+        Variant::String(s) | Variant::Reference(s) => Some(s.to_lowercase()),
+        _ => None,
+    }
+}
+
+fn is_like(value: &Variant, pattern: &str) -> bool {
+    fn like(value: &[char], pattern: &[char]) -> bool {
+        match pattern.split_first() {
+            None => value.is_empty(),
+            Some(('%', rest)) => (0..=value.len()).any(|skip| like(&value[skip..], rest)),
+            Some(('_', rest)) => !value.is_empty() && like(&value[1..], rest),
+            Some((c, rest)) => value.first() == Some(c) && like(&value[1..], rest),
+        }
+    }
+
+    match variant_as_lowercase_str(value) {
+        Some(value) => {
+            let value: Vec<char> = value.chars().collect();
+            let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+
+            like(&value, &pattern)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Win32_Service {
+        Name: String,
+        Started: bool,
+        ProcessId: u32,
+        Description: Option<String>,
+    }
+
+    fn service(name: &str, started: bool, process_id: u32) -> MockObject {
+        HashMap::from([
+            ("Name".to_owned(), Variant::String(name.to_owned())),
+            ("Started".to_owned(), Variant::Bool(started)),
+            ("ProcessId".to_owned(), Variant::UI4(process_id)),
+            (
+                "__RELPATH".to_owned(),
+                Variant::String(format!(r#"Win32_Service.Name="{}""#, name)),
+            ),
+        ])
+    }
+
+    fn mock_con() -> MockWMIConnection {
+        let mut con = MockWMIConnection::new();
+
+        con.add_object("Win32_Service", service("Winmgmt", true, 1234))
+            .add_object("Win32_Service", service("WinRM", false, 0))
+            .add_object("Win32_Service", service("Spooler", true, 5678));
+
+        con
+    }
+
+    // Code under test only depends on the trait.
+    fn started_services(con: &impl WMIQueryable) -> WMIResult<Vec<String>> {
+        let services: Vec<Win32_Service> = con.query()?;
+
+        Ok(services
+            .into_iter()
+            .filter(|service| service.Started)
+            .map(|service| service.Name)
+            .collect())
+    }
+
+    #[test]
+    fn it_can_query_mocked_objects() {
+        let con = mock_con();
+
+        let services: Vec<Win32_Service> = con.query().unwrap();
+
+        assert_eq!(services.len(), 3);
+        assert_eq!(
+            services[0],
+            Win32_Service {
+                Name: "Winmgmt".to_owned(),
+                Started: true,
+                ProcessId: 1234,
+                Description: None,
+            }
+        );
+
+        assert_eq!(started_services(&con).unwrap(), vec!["Winmgmt", "Spooler"]);
+
+        let service: Win32_Service = con.get().unwrap();
+        assert_eq!(service.Name, "Winmgmt");
+
+        let empty_con = MockWMIConnection::new();
+        assert!(started_services(&empty_con).unwrap().is_empty());
+        assert!(matches!(
+            empty_con.get::<Win32_Service>(),
+            Err(WMIError::ResultEmpty)
+        ));
+    }
+
+    #[test]
+    fn it_can_filter_mocked_objects() {
+        let con = mock_con();
+
+        let filters = HashMap::from([
+            ("Name".to_owned(), FilterValue::StrLike("win%")),
+            ("Started".to_owned(), FilterValue::Bool(true)),
+        ]);
+        let services: Vec<Win32_Service> = con.filtered_query(&filters).unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].Name, "Winmgmt");
+
+        let filters = HashMap::from([("processid".to_owned(), FilterValue::Number(5678))]);
+        let services: Vec<Win32_Service> = con.filtered_query(&filters).unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].Name, "Spooler");
+
+        let filters = HashMap::from([("Name".to_owned(), FilterValue::Str("winrm"))]);
+        let services: Vec<Win32_Service> = con.filtered_query(&filters).unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].Name, "WinRM");
+
+        let filters = HashMap::from([("Name".to_owned(), FilterValue::Str("NoSuchService"))]);
+        let services: Vec<Win32_Service> = con.filtered_query(&filters).unwrap();
+        assert!(services.is_empty());
+
+        let filters = HashMap::from([("Name".to_owned(), FilterValue::IsA("Win32_Service"))]);
+        assert!(matches!(
+            con.filtered_query::<Win32_Service>(&filters),
+            Err(WMIError::MockUnsupportedError(_))
+        ));
+    }
+
+    #[test]
+    fn it_can_raw_query_mocked_objects() {
+        let mut con = mock_con();
+
+        let services: Vec<HashMap<String, Variant>> =
+            con.raw_query("SELECT * FROM win32_service").unwrap();
+        assert_eq!(services.len(), 3);
+        assert_eq!(
+            services[1].get("__CLASS"),
+            Some(&Variant::String("Win32_Service".to_owned()))
+        );
+
+        let query = "SELECT * FROM Win32_Service WHERE State = 'Running'";
+        assert!(matches!(
+            con.raw_query::<Win32_Service>(query),
+            Err(WMIError::MockUnsupportedError(_))
+        ));
+
+        con.add_query_result(query, vec![service("Winmgmt", true, 1234)]);
+        let services: Vec<Win32_Service> = con.raw_query(query).unwrap();
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].Name, "Winmgmt");
+    }
+
+    #[derive(Deserialize)]
+    struct StopServiceOutput {
+        ReturnValue: u32,
+    }
+
+    #[derive(Serialize)]
+    struct CreateParams {
+        CommandLine: String,
+    }
+
+    #[derive(Deserialize)]
+    struct CreateOutput {
+        ReturnValue: u32,
+        ProcessId: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct Win32_Process;
+
+    #[test]
+    fn it_can_exec_mocked_methods() {
+        let mut con = mock_con();
+
+        let path = r#"Win32_Service.Name="WinRM""#;
+        assert!(matches!(
+            con.exec_instance_method::<Win32_Service, _, StopServiceOutput>(
+                "StopService",
+                path,
+                ()
+            ),
+            Err(WMIError::MockUnsupportedError(_))
+        ));
+
+        con.add_method_result(
+            path,
+            "StopService",
+            HashMap::from([("ReturnValue".to_owned(), Variant::UI4(5))]),
+        );
+        let out: StopServiceOutput = con
+            .exec_instance_method::<Win32_Service, _, _>("StopService", path, ())
+            .unwrap();
+        assert_eq!(out.ReturnValue, 5);
+
+        con.add_method_result(
+            "Win32_Process",
+            "Create",
+            HashMap::from([
+                ("ReturnValue".to_owned(), Variant::UI4(0)),
+                ("ProcessId".to_owned(), Variant::UI4(4321)),
+            ]),
+        );
+        let out: CreateOutput = con
+            .exec_class_method::<Win32_Process, _, _>(
+                "Create",
+                CreateParams {
+                    CommandLine: "notepad.exe".to_owned(),
+                },
+            )
+            .unwrap();
+        assert_eq!(out.ReturnValue, 0);
+        assert_eq!(out.ProcessId, 4321);
+    }
+
+    #[test]
+    fn it_can_get_mocked_objects_by_path() {
+        let con = mock_con();
+
+        let service: Win32_Service = con.get_by_path(r#"Win32_Service.Name="WinRM""#).unwrap();
+        assert_eq!(service.Name, "WinRM");
+        assert!(!service.Started);
+
+        let res = con.get_by_path::<Win32_Service>(r#"Win32_Service.Name="NoSuchService""#);
+        assert!(matches!(
+            res,
+            Err(WMIError::HResultError { hres, .. }) if hres == WBEM_E_NOT_FOUND.0
+        ));
+    }
+}
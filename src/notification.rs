@@ -4,8 +4,18 @@ use crate::{
     result_enumerator::{IWbemClassWrapper, QueryResultEnumerator},
     FilterValue, WMIConnection, WMIResult,
 };
-use futures::{Stream, StreamExt};
-use std::{collections::HashMap, time::Duration};
+use futures::{channel::oneshot, stream, Future, Stream, StreamExt};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 use windows::core::BSTR;
 use windows::Win32::System::Wmi::{
     IWbemObjectSink, WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_RETURN_IMMEDIATELY,
@@ -43,6 +53,8 @@ impl WMIConnection {
     /// Can be used either with a struct (like `query` and `filtered_query`),
     /// but also with a generic map.
     ///
+    /// The iterator can be stopped from another thread using its [`NotificationHandle`].
+    ///
     /// ```edition2018
     /// # use wmi::*;
     /// # #[cfg(not(feature = "test"))]
@@ -61,16 +73,13 @@ impl WMIConnection {
     pub fn raw_notification<'a, T>(
         &'a self,
         query: impl AsRef<str>,
-    ) -> WMIResult<impl Iterator<Item = WMIResult<T>> + 'a>
+    ) -> WMIResult<NotificationIterator<'a, T>>
     where
         T: serde::de::DeserializeOwned + 'a,
     {
         let enumerator = self.notification_native_wrapper(query)?;
-        let iter = enumerator.map(|item| match item {
-            Ok(wbem_class_obj) => wbem_class_obj.into_desr(),
-            Err(e) => Err(e),
-        });
-        Ok(iter)
+
+        Ok(NotificationIterator::new(enumerator))
     }
 
     /// Subscribe to the T event and return an iterator of WMIResult\<T\>.
@@ -98,7 +107,7 @@ impl WMIConnection {
     /// #   Ok(()) // This query will fail when not run as admin
     /// # }
     /// ```
-    pub fn notification<'a, T>(&'a self) -> WMIResult<impl Iterator<Item = WMIResult<T>> + 'a>
+    pub fn notification<'a, T>(&'a self) -> WMIResult<NotificationIterator<'a, T>>
     where
         T: serde::de::DeserializeOwned + 'a,
     {
@@ -137,7 +146,7 @@ impl WMIConnection {
         &'a self,
         filters: &HashMap<String, FilterValue>,
         within: Option<Duration>,
-    ) -> WMIResult<impl Iterator<Item = WMIResult<T>> + 'a>
+    ) -> WMIResult<NotificationIterator<'a, T>>
     where
         T: serde::de::DeserializeOwned + 'a,
     {
@@ -152,7 +161,7 @@ impl WMIConnection {
     pub fn async_notification_native_wrapper(
         &self,
         query: impl AsRef<str>,
-    ) -> WMIResult<impl Stream<Item = WMIResult<IWbemClassWrapper>>> {
+    ) -> WMIResult<AsyncQueryResultStream> {
         let query_language = BSTR::from("WQL");
         let query = BSTR::from(query.as_ref());
 
@@ -222,6 +231,74 @@ impl WMIConnection {
         Ok(stream)
     }
 
+    /// Subscribe to multiple free-text notification queries, and return a single stream
+    /// of the incoming events from all of them, deserialized into WMIResult\<T\>.
+    ///
+    /// To subscribe to different event types, `T` can be an enum with a newtype variant per event class,
+    /// since enums are deserialized according to the object's class name.
+    ///
+    /// All the subscriptions are cancelled when the stream is dropped.
+    ///
+    /// ```edition2018
+    /// # use wmi::*;
+    /// # use futures::executor::block_on;
+    /// # #[cfg(not(feature = "test"))]
+    /// # fn main() {}
+    /// # #[cfg(feature = "test")]
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// #   tests::ignore_access_denied(block_on(exec_async_query()))
+    /// # }
+    /// #
+    /// # async fn exec_async_query() -> WMIResult<()> {
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// use futures::StreamExt;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct ProcessStart {
+    ///     ProcessID: u32,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct ThreadStart {
+    ///     ProcessID: u32,
+    ///     ThreadID: u32,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// enum Event {
+    ///     Win32_ProcessStartTrace(ProcessStart),
+    ///     Win32_ThreadStartTrace(ThreadStart),
+    /// }
+    ///
+    /// let mut stream = con.async_raw_notification_multi::<Event>([
+    ///     "SELECT * FROM Win32_ProcessStartTrace",
+    ///     "SELECT * FROM Win32_ThreadStartTrace",
+    /// ])?;
+    ///
+    /// let event = stream.next().await.unwrap()?;
+    /// #   Ok(()) // This query will fail when not run as admin
+    /// # }
+    /// ```
+    pub fn async_raw_notification_multi<T>(
+        &self,
+        queries: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> WMIResult<impl Stream<Item = WMIResult<T>>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let streams = queries
+            .into_iter()
+            .map(|query| self.async_notification_native_wrapper(query).map(Box::pin))
+            .collect::<WMIResult<Vec<_>>>()?;
+
+        let stream = stream::select_all(streams).map(|item| match item {
+            Ok(wbem_class_obj) => wbem_class_obj.into_desr(),
+            Err(e) => Err(e),
+        });
+        Ok(stream)
+    }
+
     /// Subscribe to the T event and return a stream of WMIResult\<T\>.
     ///
     /// ```edition2018
@@ -311,14 +388,440 @@ impl WMIConnection {
         let query_text = build_notification_query::<T>(Some(filters), within)?;
         self.async_raw_notification(query_text)
     }
+
+    /// Subscribe to the T event for the lifetime of the returned stream, while filtering according to `filters`.
+    /// Returns a stream of WMIResult\<T\>.
+    ///
+    /// This is a temporary (in-process) subscription, like `async_filtered_notification`,
+    /// which is automatically re-created if WMI ends it (for example, when the event provider is unloaded).
+    ///
+    /// Re-subscribing is delayed, starting at one second and doubling after each attempt without a new event.
+    /// After 5 such attempts the stream ends, returning the last error if re-subscribing failed.
+    ///
+    /// Note that this is not a *permanent* subscription: permanent subscriptions are registered
+    /// in the `ROOT\subscription` namespace (using `__EventFilter`, an event consumer such as
+    /// `ActiveScriptEventConsumer` or `CommandLineEventConsumer` and a `__FilterToConsumerBinding`),
+    /// and deliver events to an external script or executable even when the subscribing process is not running.
+    ///
+    /// ```edition2018
+    /// # use wmi::*;
+    /// # use futures::{future::FutureExt, select};
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// #   async_std::task::block_on(async {
+    /// #       select! { // End in 3 seconds or on event.
+    /// #           () = async_std::task::sleep(std::time::Duration::from_secs(3)).fuse() => Ok(()),
+    /// #           r = exec_async_query().fuse() => r
+    /// #       }
+    /// #   })
+    /// # }
+    /// #
+    /// # async fn exec_async_query() -> WMIResult<()> {
+    /// # use std::{collections::HashMap, time::Duration};
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// use futures::StreamExt;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize, Debug)]
+    /// struct __InstanceCreationEvent {
+    ///     TargetInstance: Win32_Process,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_Process {
+    ///     ProcessID: u32,
+    /// }
+    ///
+    /// let mut filters = HashMap::new();
+    ///
+    /// filters.insert("TargetInstance".to_owned(), FilterValue::is_a::<Win32_Process>()?);
+    ///
+    /// let mut stream = con.long_lived_subscription::<__InstanceCreationEvent>(&filters, Some(Duration::from_secs(1)))?;
+    ///
+    /// while let Some(event) = stream.next().await {
+    ///     println!("{:?}", event?);
+    /// #   break;
+    /// }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn long_lived_subscription<T>(
+        &self,
+        filters: &HashMap<String, FilterValue>,
+        within: Option<Duration>,
+    ) -> WMIResult<impl Stream<Item = WMIResult<T>>>
+    where
+        T: serde::de::DeserializeOwned + 'static,
+    {
+        type EventStream<T> = Pin<Box<dyn Stream<Item = WMIResult<T>>>>;
+
+        let query_text = build_notification_query::<T>(Some(filters), within)?;
+        let events: EventStream<T> = Box::pin(self.async_raw_notification(query_text.clone())?);
+
+        let state = Some((self.clone(), query_text, events));
+
+        let stream = stream::unfold(state, |state| async move {
+            let (con, query_text, mut events) = state?;
+
+            // The number of re-subscriptions since the last event.
+            let mut attempts = 0;
+
+            loop {
+                if let Some(event) = events.next().await {
+                    return Some((event, Some((con, query_text, events))));
+                }
+
+                loop {
+                    if attempts == RESUBSCRIBE_MAX_ATTEMPTS {
+                        log::warn!(
+                            "Subscription for {:?} ended {} times without new events, giving up",
+                            query_text,
+                            attempts
+                        );
+                        return None;
+                    }
+
+                    delay(RESUBSCRIBE_INITIAL_DELAY * 2u32.pow(attempts)).await;
+                    attempts += 1;
+
+                    log::debug!("Subscription for {:?} ended, re-subscribing", query_text);
+
+                    match con.async_raw_notification(query_text.clone()) {
+                        Ok(new_events) => {
+                            events = Box::pin(new_events);
+                            break;
+                        }
+                        Err(e) if attempts == RESUBSCRIBE_MAX_ATTEMPTS => {
+                            return Some((Err(e), None))
+                        }
+                        Err(e) => {
+                            log::debug!("Re-subscribing for {:?} failed: {}", query_text, e)
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
+
+/// The delay before the first re-subscription of [`WMIConnection::long_lived_subscription`], which doubles after each attempt.
+const RESUBSCRIBE_INITIAL_DELAY: Duration = Duration::from_secs(1);
+/// The number of re-subscriptions without a new event, after which [`WMIConnection::long_lived_subscription`] gives up.
+const RESUBSCRIBE_MAX_ATTEMPTS: u32 = 5;
+
+/// A request to wake up a [`delay`] at the given deadline.
+type TimerRequest = (Instant, oneshot::Sender<()>);
+
+/// Wait for `duration` without blocking the executor, regardless of the async runtime being used.
+///
+/// All waits are handled by a single timer thread, which is started on first use.
+fn delay(duration: Duration) -> impl Future<Output = ()> {
+    static TIMER: OnceLock<Mutex<mpsc::Sender<TimerRequest>>> = OnceLock::new();
+
+    let timer = TIMER.get_or_init(|| {
+        let (requests_tx, requests_rx) = mpsc::channel();
+
+        let _r = thread::Builder::new()
+            .name("wmi-timer".to_owned())
+            .spawn(move || run_timer(requests_rx));
+
+        Mutex::new(requests_tx)
+    });
+
+    let (tx, rx) = oneshot::channel();
+
+    // If the timer thread is not running, `tx` is dropped and the wait ends immediately.
+    if let Ok(timer) = timer.lock() {
+        let _r = timer.send((Instant::now() + duration, tx));
+    }
+
+    async move {
+        let _r = rx.await;
+    }
+}
+
+/// Wake up every pending [`delay`] when its deadline is reached, sleeping until the earliest deadline or a new request.
+fn run_timer(requests: mpsc::Receiver<TimerRequest>) {
+    let mut pending: Vec<TimerRequest> = vec![];
+
+    loop {
+        let now = Instant::now();
+
+        // Waits which were dropped before their deadline don't need to be woken up.
+        let (due, not_due): (Vec<_>, Vec<_>) = pending
+            .into_iter()
+            .filter(|(_, tx)| !tx.is_canceled())
+            .partition(|(deadline, _)| *deadline <= now);
+
+        for (_, tx) in due {
+            let _r = tx.send(());
+        }
+
+        pending = not_due;
+
+        let request = match pending.iter().map(|(deadline, _)| *deadline).min() {
+            Some(deadline) => match requests.recv_timeout(deadline - now) {
+                Ok(request) => Some(request),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            },
+            None => match requests.recv() {
+                Ok(request) => Some(request),
+                Err(mpsc::RecvError) => return,
+            },
+        };
+
+        pending.extend(request);
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl WMIConnection {
+    /// Subscribe to the given notification query, and forward the incoming events (deserialized into `WMIResult<T>`)
+    /// to a [`tokio::sync::mpsc`] channel with the given capacity.
+    ///
+    /// Since a `WMIConnection` cannot be sent to another thread, the events are forwarded by a task spawned using
+    /// [`tokio::task::spawn_local`], so this must be called from within a [`tokio::task::LocalSet`].
+    ///
+    /// The subscription is cancelled and the task ends when the receiver is dropped (or when the subscription ends).
+    ///
+    /// Requires the `tokio` feature.
+    ///
+    /// ```edition2021
+    /// # use wmi::*;
+    /// # #[cfg(not(feature = "test"))]
+    /// # fn main() {}
+    /// # #[cfg(feature = "test")]
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// # let local = tokio::task::LocalSet::new();
+    /// # local.block_on(&rt, async {
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_LocalTime {
+    ///     Second: u32,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct __InstanceModificationEvent {
+    ///     TargetInstance: Win32_LocalTime,
+    /// }
+    ///
+    /// let (handle, mut events) = con.spawn_notification_to_channel::<__InstanceModificationEvent>(
+    ///     "SELECT * FROM __InstanceModificationEvent WHERE TargetInstance ISA 'Win32_LocalTime'",
+    ///     16,
+    /// )?;
+    ///
+    /// while let Some(event) = events.recv().await {
+    ///     println!("{:?}", event?);
+    /// #   break;
+    /// }
+    ///
+    /// // Dropping the receiver cancels the subscription.
+    /// drop(events);
+    /// handle.await.unwrap();
+    /// #   Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub fn spawn_notification_to_channel<T>(
+        &self,
+        query: impl AsRef<str>,
+        buffer: usize,
+    ) -> WMIResult<(
+        tokio::task::JoinHandle<()>,
+        tokio::sync::mpsc::Receiver<WMIResult<T>>,
+    )>
+    where
+        T: serde::de::DeserializeOwned + 'static,
+    {
+        let mut events = Box::pin(self.async_raw_notification::<T>(query)?);
+        let (tx, rx) = tokio::sync::mpsc::channel(buffer);
+
+        let handle = tokio::task::spawn_local(async move {
+            loop {
+                let event = tokio::select! {
+                    _ = tx.closed() => break,
+                    event = events.next() => event,
+                };
+
+                let Some(event) = event else {
+                    break;
+                };
+
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok((handle, rx))
+    }
+}
+
+/// A handle which can be used to cancel a [`NotificationIterator`], possibly from another thread.
+///
+/// Once cancelled, the iterator returns `None`.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl NotificationHandle {
+    /// Cancel the notification iterator.
+    /// The iterator stops within [`NOTIFICATION_POLL_INTERVAL`], even if it is currently waiting for an event.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if the notification iterator was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// The interval at which a [`NotificationIterator`] waiting for an event checks if it was cancelled.
+pub const NOTIFICATION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An iterator of the events of a notification query, deserialized into WMIResult\<T\>.
+///
+/// Returned by [`WMIConnection::raw_notification`], [`WMIConnection::notification`] and [`WMIConnection::filtered_notification`].
+///
+/// ```edition2018
+/// # use wmi::*;
+/// # fn main() -> wmi::WMIResult<()> {
+/// # use std::{collections::HashMap, thread, time::Duration};
+/// # let con = WMIConnection::new(COMLibrary::new()?)?;
+/// let mut iterator = con.raw_notification::<HashMap<String, Variant>>(
+///     "SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process'",
+/// )?;
+///
+/// let handle = iterator.handle();
+///
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_secs(1));
+///     handle.cancel();
+/// });
+///
+/// for event in iterator {
+///     println!("{:?}", event?);
+/// }
+/// #   Ok(())
+/// # }
+/// ```
+pub struct NotificationIterator<'a, T> {
+    enumerator: QueryResultEnumerator<'a>,
+    handle: NotificationHandle,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T> NotificationIterator<'a, T> {
+    fn new(enumerator: QueryResultEnumerator<'a>) -> Self {
+        Self {
+            enumerator,
+            handle: NotificationHandle::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get a handle which can be used to cancel this iterator.
+    pub fn handle(&self) -> NotificationHandle {
+        self.handle.clone()
+    }
+
+    /// Returns `true` if the iterator was cancelled or the subscription has ended,
+    /// so no more events will be returned.
+    pub fn is_done(&self) -> bool {
+        self.handle.is_cancelled() || self.enumerator.is_done()
+    }
+}
+
+impl<'a, T> NotificationIterator<'a, T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    /// Wait at most `timeout` for the next event.
+    ///
+    /// Returns `None` if no event arrived within the timeout.
+    /// Unlike [`Iterator::next`], `None` does not mean that the iterator is exhausted,
+    /// which can be checked using [`NotificationIterator::is_done`].
+    ///
+    /// ```edition2018
+    /// # use wmi::*;
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use std::{collections::HashMap, time::Duration};
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let mut iterator = con.raw_notification::<HashMap<String, Variant>>(
+    ///     "SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process'",
+    /// )?;
+    ///
+    /// # let should_stop = true;
+    /// while !iterator.is_done() {
+    ///     if let Some(event) = iterator.next_timeout(Duration::from_secs(1)) {
+    ///         println!("{:?}", event?);
+    ///     }
+    ///
+    ///     if should_stop {
+    ///         break;
+    ///     }
+    /// }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn next_timeout(&mut self, timeout: Duration) -> Option<WMIResult<T>> {
+        let deadline = Instant::now() + timeout;
+
+        // Wait for the next event in short intervals, so cancellation is noticed even if no events arrive.
+        loop {
+            if self.is_done() {
+                return None;
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if let Some(item) = self
+                .enumerator
+                .next_timeout(remaining.min(NOTIFICATION_POLL_INTERVAL))
+            {
+                return Some(item.and_then(IWbemClassWrapper::into_desr));
+            }
+
+            if remaining.is_zero() {
+                return None;
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for NotificationIterator<'a, T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Item = WMIResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.is_done() {
+            if let Some(item) = self.next_timeout(NOTIFICATION_POLL_INTERVAL) {
+                return Some(item);
+            }
+        }
+
+        None
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{tests::fixtures::*, FilterValue, WMIError};
+    use crate::{tests::fixtures::*, FilterValue, Variant, WMIError};
     use futures::StreamExt;
     use serde::Deserialize;
-    use std::{collections::HashMap, time::Duration};
+    use std::{
+        collections::HashMap,
+        thread,
+        time::{Duration, Instant},
+    };
 
     #[cfg(feature = "chrono")]
     use chrono::Datelike;
@@ -350,6 +853,28 @@ mod tests {
         year: u32,
     }
 
+    #[test]
+    fn it_explains_notification_query() {
+        let query = crate::explain_notification_query::<InstanceModification>(
+            Some(&notification_filters()),
+            Some(Duration::from_secs(1)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            query,
+            crate::build_notification_query::<InstanceModification>(
+                Some(&notification_filters()),
+                Some(Duration::from_secs(1))
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            query,
+            r#"SELECT * FROM __InstanceModificationEvent WITHIN 1 WHERE TargetInstance ISA "Win32_LocalTime""#
+        );
+    }
+
     #[test]
     fn it_works() {
         let wmi_con = wmi_con();
@@ -367,6 +892,72 @@ mod tests {
         assert_eq!(props[props.len() - 2..], ["TIME_CREATED", "TargetInstance"]);
     }
 
+    #[test]
+    fn it_can_be_cancelled_from_another_thread() {
+        let wmi_con = wmi_con();
+
+        // An event which is not expected to happen during the test.
+        let mut iterator = wmi_con
+            .raw_notification::<HashMap<String, Variant>>(
+                "SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process' AND TargetInstance.Name = 'wmi-rs-no-such-process.exe'",
+            )
+            .unwrap();
+
+        let handle = iterator.handle();
+
+        let start = Instant::now();
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            handle.cancel();
+        });
+
+        assert!(iterator.next().is_none());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(iterator.handle().is_cancelled());
+
+        canceller.join().unwrap();
+    }
+
+    #[test]
+    fn it_returns_on_timeout_without_an_event() {
+        let wmi_con = wmi_con();
+
+        let mut iterator = wmi_con
+            .raw_notification::<HashMap<String, Variant>>(
+                "SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process' AND TargetInstance.Name = 'wmi-rs-no-such-process.exe'",
+            )
+            .unwrap();
+
+        let start = Instant::now();
+
+        assert!(iterator.next_timeout(Duration::from_secs(1)).is_none());
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(900));
+        assert!(elapsed < Duration::from_secs(5));
+
+        assert!(!iterator.is_done());
+    }
+
+    #[test]
+    fn it_stops_when_the_subscription_ends() {
+        let wmi_con = wmi_con();
+
+        // A regular query ends like a subscription, so use it to drive the iterator.
+        let enumerator = wmi_con
+            .exec_query_native_wrapper("SELECT * FROM Win32_OperatingSystem")
+            .unwrap();
+        let mut iterator = super::NotificationIterator::<HashMap<String, Variant>>::new(enumerator);
+
+        let start = Instant::now();
+
+        assert!(iterator.next().unwrap().is_ok());
+        assert!(iterator.next().is_none());
+        assert!(iterator.is_done());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     fn it_fails_gracefully() {
         let wmi_con = wmi_con();
@@ -389,7 +980,7 @@ mod tests {
         match result {
             Ok(_) => assert!(false),
             Err(wmi_err) => match wmi_err {
-                WMIError::HResultError { hres } => assert_eq!(hres, WBEM_E_UNPARSABLE_QUERY.0),
+                WMIError::HResultError { hres, .. } => assert_eq!(hres, WBEM_E_UNPARSABLE_QUERY.0),
                 _ => assert!(false),
             },
         }
@@ -499,6 +1090,55 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[derive(Deserialize, Debug)]
+    #[serde(rename = "__InstanceModificationEvent")]
+    #[serde(rename_all = "PascalCase")]
+    pub struct TimeModification {
+        target_instance: Time,
+    }
+
+    #[derive(Deserialize, Debug)]
+    pub enum Time {
+        #[serde(rename = "Win32_LocalTime")]
+        Local(LocalTime),
+        #[serde(rename = "Win32_UTCTime")]
+        Utc(LocalTime),
+    }
+
+    #[async_std::test]
+    async fn async_it_merges_multiple_notifications() {
+        let wmi_con = wmi_con();
+
+        let mut stream = wmi_con
+            .async_raw_notification_multi::<TimeModification>([
+                TEST_QUERY,
+                "SELECT * FROM __InstanceModificationEvent WHERE TargetInstance ISA 'Win32_UTCTime'",
+            ])
+            .unwrap();
+
+        let (mut got_local, mut got_utc) = (false, false);
+
+        for _ in 0..10 {
+            match stream.next().await.unwrap().unwrap().target_instance {
+                Time::Local(time) => {
+                    assert!(time.year >= 2000);
+                    got_local = true;
+                }
+                Time::Utc(time) => {
+                    assert!(time.year >= 2000);
+                    got_utc = true;
+                }
+            }
+
+            if got_local && got_utc {
+                break;
+            }
+        }
+
+        assert!(got_local);
+        assert!(got_utc);
+    }
+
     #[async_std::test]
     async fn async_it_handles_invalid_query() {
         let wmi_con = wmi_con();
@@ -506,7 +1146,7 @@ mod tests {
         let result = wmi_con.async_notification_native_wrapper("Invalid Query");
 
         assert!(result.is_err());
-        if let WMIError::HResultError { hres } = result.err().unwrap() {
+        if let WMIError::HResultError { hres, .. } = result.err().unwrap() {
             assert_eq!(hres, WBEM_E_UNPARSABLE_QUERY.0);
         } else {
             assert!(false, "Invalid WMIError type");
@@ -551,6 +1191,49 @@ mod tests {
         )
     }
 
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_it_forwards_notifications_to_a_channel() {
+        let local = tokio::task::LocalSet::new();
+
+        local
+            .run_until(async {
+                let wmi_con = wmi_con();
+
+                let (handle, mut events) = wmi_con
+                    .spawn_notification_to_channel::<InstanceModification>(TEST_QUERY, 1)
+                    .unwrap();
+
+                for _ in 0..2 {
+                    let event = events.recv().await.unwrap().unwrap();
+                    assert!(event.target_instance.year > 1970);
+                }
+
+                // Dropping the receiver should cancel the subscription and end the task.
+                drop(events);
+                handle.await.unwrap();
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn async_it_provides_long_lived_subscription_result() {
+        let wmi_con = wmi_con();
+
+        let mut stream = wmi_con
+            .long_lived_subscription::<InstanceModification>(
+                &notification_filters(),
+                Some(Duration::from_secs_f32(0.1)),
+            )
+            .unwrap();
+
+        let result = stream.next().await.unwrap();
+        assert!(result.is_ok());
+
+        let result = stream.next().await.unwrap();
+        assert!(result.unwrap().target_instance.year > 0);
+    }
+
     #[async_std::test]
     #[cfg(feature = "chrono")]
     async fn async_it_provides_filtered_notification_result() {
@@ -594,4 +1277,19 @@ mod tests {
             time::OffsetDateTime::now_utc().year()
         )
     }
+
+    #[async_std::test]
+    async fn async_it_wakes_up_concurrent_delays() {
+        let start = Instant::now();
+
+        // The waits are registered out of order, and share the same timer thread.
+        futures::future::join_all(
+            [300, 100, 200].map(|millis| super::delay(Duration::from_millis(millis))),
+        )
+        .await;
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(300));
+        assert!(elapsed < Duration::from_secs(2));
+    }
 }
@@ -0,0 +1,200 @@
+use crate::{result_enumerator::IWbemClassWrapper, WMIConnection, WMIError, WMIResult};
+use log::debug;
+use std::collections::HashMap;
+use windows::core::{Interface, HSTRING};
+use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+use windows::Win32::System::Wmi::{
+    IWbemClassObject, IWbemConfigureRefresher, IWbemHiPerfEnum, IWbemObjectAccess, IWbemRefresher,
+    WbemRefresher, WBEM_E_BUFFER_TOO_SMALL, WBEM_FLAG_REFRESH_AUTO_RECONNECT,
+};
+
+/// A cache of performance counter classes (like `Win32_PerfFormattedData_PerfOS_Processor`),
+/// which can be refreshed together, created using [`WMIConnection::perf_refresher`].
+///
+/// This wraps [`IWbemRefresher`](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nn-wbemcli-iwbemrefresher),
+/// which keeps the enumerators of the added classes, so refreshing is much cheaper than querying the classes again.
+///
+/// Formatted ("cooked") counters are calculated from two samples,
+/// so values like `PercentProcessorTime` are only meaningful after the second refresh.
+///
+/// ```edition2021
+/// # fn main() -> wmi::WMIResult<()> {
+/// # use wmi::*;
+/// # use serde::Deserialize;
+/// # use std::{thread::sleep, time::Duration};
+/// # let con = WMIConnection::new(COMLibrary::new()?)?;
+/// #[derive(Deserialize, Debug)]
+/// struct Win32_PerfFormattedData_PerfOS_Processor {
+///     Name: String,
+///     PercentProcessorTime: u64,
+/// }
+///
+/// let mut refresher = con.perf_refresher()?;
+/// refresher.add_class("Win32_PerfFormattedData_PerfOS_Processor")?;
+///
+/// for _ in 0..3 {
+///     let mut results = refresher.refresh_all()?;
+///
+///     for processor in results.remove("Win32_PerfFormattedData_PerfOS_Processor").unwrap() {
+///         let processor: Win32_PerfFormattedData_PerfOS_Processor = processor.into_desr()?;
+///         println!("{}: {}%", processor.Name, processor.PercentProcessorTime);
+///     }
+///
+///     sleep(Duration::from_millis(100));
+/// }
+/// #   Ok(())
+/// # }
+/// ```
+pub struct PerfRefresher<'a> {
+    wmi_con: &'a WMIConnection,
+    refresher: IWbemRefresher,
+    config: IWbemConfigureRefresher,
+    enums: Vec<(String, IWbemHiPerfEnum)>,
+}
+
+impl<'a> PerfRefresher<'a> {
+    /// Add all the instances of a performance counter class to the refresher.
+    ///
+    /// Instances which are added or removed (like processes) are tracked by the refresher.
+    pub fn add_class(&mut self, class_name: &str) -> WMIResult<()> {
+        let mut hi_perf_enum = None;
+        let mut id = 0;
+
+        unsafe {
+            self.config.AddEnum(
+                &self.wmi_con.svc,
+                &HSTRING::from(class_name),
+                WBEM_FLAG_REFRESH_AUTO_RECONNECT.0,
+                &self.wmi_con.ctx.0,
+                &mut hi_perf_enum,
+                &mut id,
+            )?;
+        }
+
+        let hi_perf_enum = hi_perf_enum.ok_or(WMIError::NullPointerResult)?;
+
+        debug!("Added {} to the refresher with id {}", class_name, id);
+
+        self.enums.push((class_name.to_owned(), hi_perf_enum));
+
+        Ok(())
+    }
+
+    /// Refresh all the added classes, and return the latest instances of each class (by the name used in [`PerfRefresher::add_class`]).
+    pub fn refresh_all(&mut self) -> WMIResult<HashMap<String, Vec<IWbemClassWrapper>>> {
+        unsafe { self.refresher.Refresh(WBEM_FLAG_REFRESH_AUTO_RECONNECT.0)? };
+
+        let mut results = HashMap::new();
+
+        for (class_name, hi_perf_enum) in &self.enums {
+            let objects = get_objects(hi_perf_enum)?;
+
+            results.insert(class_name.clone(), objects);
+        }
+
+        Ok(results)
+    }
+}
+
+fn get_objects(hi_perf_enum: &IWbemHiPerfEnum) -> WMIResult<Vec<IWbemClassWrapper>> {
+    let mut objects: Vec<Option<IWbemObjectAccess>> = vec![];
+
+    // Query the number of objects first, and retry if it grew between the calls.
+    loop {
+        let mut returned = 0;
+
+        match unsafe { hi_perf_enum.GetObjects(0, &mut objects, &mut returned) } {
+            Ok(()) => {
+                objects.truncate(returned as usize);
+                break;
+            }
+            Err(e) if e.code().0 == WBEM_E_BUFFER_TOO_SMALL.0 => {
+                objects.resize(returned as usize, None);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    objects
+        .into_iter()
+        .flatten()
+        .map(|object| Ok(IWbemClassWrapper::new(object.cast::<IWbemClassObject>()?)))
+        .collect()
+}
+
+impl WMIConnection {
+    /// Create a [`PerfRefresher`] for efficiently polling performance counter classes in the connection's namespace.
+    pub fn perf_refresher(&self) -> WMIResult<PerfRefresher<'_>> {
+        let refresher: IWbemRefresher =
+            unsafe { CoCreateInstance(&WbemRefresher, None, CLSCTX_INPROC_SERVER)? };
+        let config = refresher.cast::<IWbemConfigureRefresher>()?;
+
+        Ok(PerfRefresher {
+            wmi_con: self,
+            refresher,
+            config,
+            enums: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::fixtures::wmi_con;
+    use serde::Deserialize;
+    use std::{thread::sleep, time::Duration};
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_PerfFormattedData_PerfOS_Processor {
+        Name: String,
+        PercentProcessorTime: u64,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_PerfFormattedData_PerfOS_Memory {
+        AvailableBytes: u64,
+    }
+
+    #[test]
+    fn it_can_refresh_perf_classes() {
+        let wmi_con = wmi_con();
+
+        let mut refresher = wmi_con.perf_refresher().unwrap();
+        refresher
+            .add_class("Win32_PerfFormattedData_PerfOS_Processor")
+            .unwrap();
+        refresher
+            .add_class("Win32_PerfFormattedData_PerfOS_Memory")
+            .unwrap();
+
+        for _ in 0..2 {
+            let mut results = refresher.refresh_all().unwrap();
+            assert_eq!(results.len(), 2);
+
+            let processors = results
+                .remove("Win32_PerfFormattedData_PerfOS_Processor")
+                .unwrap();
+            let processors: Vec<Win32_PerfFormattedData_PerfOS_Processor> = processors
+                .into_iter()
+                .map(|processor| processor.into_desr().unwrap())
+                .collect();
+
+            let total = processors
+                .iter()
+                .find(|processor| processor.Name == "_Total")
+                .unwrap();
+            assert!(total.PercentProcessorTime <= 100);
+
+            let memory = results
+                .remove("Win32_PerfFormattedData_PerfOS_Memory")
+                .unwrap();
+            assert_eq!(memory.len(), 1);
+
+            let memory: Win32_PerfFormattedData_PerfOS_Memory =
+                memory.into_iter().next().unwrap().into_desr().unwrap();
+            assert!(memory.AvailableBytes > 0);
+
+            sleep(Duration::from_millis(100));
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{query::quote_and_escape_wql_str, WMIConnection, WMIResult};
+use serde::Deserialize;
+
+///
+/// ### Provider information methods
+///
+impl WMIConnection {
+    /// Get the name of the provider backing the given (dynamic) class, using the class's `provider` qualifier.
+    ///
+    /// Returns a `WBEM_E_NOT_FOUND` error if the class is not provided by a provider (for example, static classes).
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let provider = wmi_con.class_provider("Win32_Process")?;
+    /// assert_eq!(provider, "CIMWin32");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn class_provider(&self, class_name: &str) -> WMIResult<String> {
+        let class = self.get_raw_by_path(class_name)?;
+
+        class.get_qualifier("provider")?.try_into()
+    }
+
+    /// Get the [hosting model](https://learn.microsoft.com/en-us/windows/win32/wmisdk/provider-hosting-and-security)
+    /// of the provider backing the given class, as specified by the `HostingModel` property of its `__Win32Provider` registration.
+    ///
+    /// This is useful when diagnosing provider failures, such as `WBEM_E_PROVIDER_LOAD_FAILURE`.
+    ///
+    /// Returns `None` if the provider's registration does not specify a hosting model.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let hosting_model = wmi_con.provider_hosting_model("Win32_Process")?;
+    /// println!("{:?}", hosting_model); // Some("NetworkServiceHost")
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn provider_hosting_model(&self, class_name: &str) -> WMIResult<Option<String>> {
+        #[derive(Deserialize)]
+        struct __Win32Provider {
+            HostingModel: Option<String>,
+        }
+
+        let provider = self.class_provider(class_name)?;
+        let provider_path = format!(
+            "__Win32Provider.Name={}",
+            quote_and_escape_wql_str(provider)
+        );
+
+        let provider: __Win32Provider = self.get_by_path(&provider_path)?;
+
+        Ok(provider.HostingModel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::fixtures::wmi_con;
+    use crate::{Variant, WMIError};
+    use windows::Win32::System::Wmi::WBEM_E_NOT_FOUND;
+
+    #[test]
+    fn it_can_get_class_provider() {
+        let wmi_con = wmi_con();
+
+        assert_eq!(wmi_con.class_provider("Win32_Process").unwrap(), "CIMWin32");
+
+        match wmi_con.class_provider("__Win32Provider") {
+            Err(WMIError::HResultError { hres, .. }) => assert_eq!(hres, WBEM_E_NOT_FOUND.0),
+            res => panic!("Unexpected result {:?}", res),
+        }
+    }
+
+    #[test]
+    fn it_can_get_provider_hosting_model() {
+        let wmi_con = wmi_con();
+
+        let hosting_model = wmi_con
+            .provider_hosting_model("Win32_Process")
+            .unwrap()
+            .unwrap();
+
+        assert!(hosting_model.ends_with("Host"));
+    }
+
+    #[test]
+    fn it_can_list_class_qualifiers() {
+        let wmi_con = wmi_con();
+
+        let class = wmi_con.get_raw_by_path("Win32_Process").unwrap();
+        let qualifiers = class.get_qualifiers().unwrap();
+
+        assert_eq!(qualifiers["dynamic"], Variant::Bool(true));
+        assert_eq!(
+            qualifiers["provider"],
+            Variant::String("CIMWin32".to_string())
+        );
+        assert!(matches!(qualifiers["UUID"], Variant::String(_)));
+    }
+}
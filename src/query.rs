@@ -1,16 +1,19 @@
 use crate::{
     connection::WMIConnection,
-    de::meta::struct_name_and_fields,
+    de::meta::{struct_name_and_fields, validate_identifier},
     de::wbem_class_de::from_wbem_class_obj,
     result_enumerator::{IWbemClassWrapper, QueryResultEnumerator},
-    WMIError, WMIResult,
+    Variant, WMIError, WMIResult,
 };
-use log::trace;
+use log::{trace, warn};
 use serde::de;
 use std::{collections::HashMap, time::Duration};
 use windows::core::BSTR;
+use windows::Win32::Foundation::E_ACCESSDENIED;
 use windows::Win32::System::Wmi::{
+    WBEM_E_ACCESS_DENIED, WBEM_E_INVALID_CLASS, WBEM_E_PRIVILEGE_NOT_HELD, WBEM_FLAG_DEEP,
     WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_RETURN_IMMEDIATELY, WBEM_FLAG_RETURN_WBEM_COMPLETE,
+    WBEM_FLAG_SHALLOW, WBEM_GENERIC_FLAG_TYPE,
 };
 
 #[non_exhaustive]
@@ -72,6 +75,38 @@ impl FilterValue {
         let (name, _) = struct_name_and_fields::<T>()?;
         Ok(Self::IsA(name))
     }
+
+    /// Create a filter on the `__Class` system property, which matches any object whose class is `T` or derives from it.
+    ///
+    /// This is useful for extrinsic events, where a query over a base class (like `__ExtrinsicEvent`)
+    /// can be limited to a part of the event hierarchy.
+    ///
+    /// ```edition2018
+    /// # use std::collections::HashMap;
+    /// # use std::time::Duration;
+    /// # use wmi::{build_notification_query, FilterValue};
+    /// # use serde::Deserialize;
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// #[derive(Deserialize)]
+    /// struct __ExtrinsicEvent {}
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Win32_VolumeChangeEvent {}
+    ///
+    /// let mut filters = HashMap::<String, FilterValue>::new();
+    /// filters.extend([FilterValue::class_is_a::<Win32_VolumeChangeEvent>()?]);
+    ///
+    /// let query = build_notification_query::<__ExtrinsicEvent>(Some(&filters), None)?;
+    /// assert_eq!(query, r#"SELECT * FROM __ExtrinsicEvent WHERE __Class ISA "Win32_VolumeChangeEvent""#);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn class_is_a<'de, T>() -> WMIResult<(String, Self)>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        Ok(("__Class".to_owned(), Self::is_a::<T>()?))
+    }
 }
 
 /// Build an SQL query for the given filters, over the given type (using its name and fields).
@@ -99,6 +134,17 @@ impl FilterValue {
 /// ```
 ///
 pub fn build_query<'de, T>(filters: Option<&HashMap<String, FilterValue>>) -> WMIResult<String>
+where
+    T: de::Deserialize<'de>,
+{
+    build_query_from::<T>(None, filters)
+}
+
+/// Build a query for the fields of `T`, from the given class (or from the class named after `T`).
+fn build_query_from<'de, T>(
+    class_name: Option<&str>,
+    filters: Option<&HashMap<String, FilterValue>>,
+) -> WMIResult<String>
 where
     T: de::Deserialize<'de>,
 {
@@ -107,13 +153,183 @@ where
     let query_text = format!(
         "SELECT {} FROM {} {}",
         fields.join(","),
-        name,
+        class_name.unwrap_or(name),
         optional_where_clause
     );
 
     Ok(query_text)
 }
 
+/// Return the WQL query that [`WMIConnection::query`] (when `filters` is `None`)
+/// or [`WMIConnection::filtered_query`] would execute for T, without executing it.
+///
+/// ```edition2018
+/// # fn main() -> wmi::WMIResult<()> {
+/// use wmi::*;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// struct Win32_Process {
+///     Name: String,
+/// }
+///
+/// assert_eq!(explain_query::<Win32_Process>(None)?, "SELECT Name FROM Win32_Process ");
+/// #   Ok(())
+/// # }
+/// ```
+pub fn explain_query<T>(filters: Option<&HashMap<String, FilterValue>>) -> WMIResult<String>
+where
+    T: de::DeserializeOwned,
+{
+    build_query::<T>(filters)
+}
+
+/// The direction of an `ORDER BY` clause, used by [`build_query_ordered`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn as_wql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Additional qualifiers for an `ASSOCIATORS OF` query, used by [`WMIConnection::associators_with_options`].
+///
+/// See <https://learn.microsoft.com/en-us/windows/win32/wmisdk/associators-of-statement> for more info.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssociatorsOptions {
+    /// Only return objects associated with the source object where the source object plays this role
+    /// (the name of the association's property which refers to the source object).
+    pub role: Option<String>,
+    /// Only return objects which play this role in the association
+    /// (the name of the association's property which refers to the returned objects).
+    pub result_role: Option<String>,
+    /// Only return objects whose class has this qualifier.
+    pub required_qualifier: Option<String>,
+}
+
+/// Build an `ASSOCIATORS OF` query for the objects of type `ResultClass` which are associated with the object at `object_path`
+/// using the association class `AssocClass`, with the given additional qualifiers.
+///
+/// ```edition2018
+/// # use wmi::{build_associators_query, AssociatorsOptions};
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Win32_Account {}
+///
+/// #[derive(Deserialize)]
+/// struct Win32_GroupUser {}
+///
+/// let options = AssociatorsOptions {
+///     result_role: Some("PartComponent".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let query = build_associators_query::<Win32_Account, Win32_GroupUser>(r#"Win32_Group.Domain="BUILTIN",Name="Users""#, &options).unwrap();
+///
+/// assert_eq!(query, r#"ASSOCIATORS OF {Win32_Group.Domain="BUILTIN",Name="Users"} WHERE AssocClass = Win32_GroupUser ResultClass = Win32_Account ResultRole = PartComponent"#);
+/// ```
+pub fn build_associators_query<'de, ResultClass, AssocClass>(
+    object_path: &str,
+    options: &AssociatorsOptions,
+) -> WMIResult<String>
+where
+    ResultClass: de::Deserialize<'de>,
+    AssocClass: de::Deserialize<'de>,
+{
+    let (class_name, _fields) = struct_name_and_fields::<ResultClass>()?;
+    let (association_class, _) = struct_name_and_fields::<AssocClass>()?;
+
+    // See more at:
+    // https://docs.microsoft.com/en-us/windows/desktop/wmisdk/associators-of-statement
+    let mut query = format!(
+        "ASSOCIATORS OF {{{object_path}}} WHERE AssocClass = {association_class} ResultClass = {class_name}",
+        object_path = object_path,
+        association_class = association_class,
+        class_name = class_name
+    );
+
+    for (keyword, value) in [
+        ("Role", &options.role),
+        ("ResultRole", &options.result_role),
+        ("RequiredQualifier", &options.required_qualifier),
+    ] {
+        if let Some(value) = value {
+            query.push_str(&format!(" {} = {}", keyword, value));
+        }
+    }
+
+    Ok(query)
+}
+
+/// Build an SQL query for the given filters and ordering, over the given type (using its name and fields).
+///
+/// Every field in `order_by` must be one of the fields of the struct.
+///
+/// Note that `ORDER BY` is not supported by every WMI provider,
+/// in which case the query will fail when executed.
+///
+/// # Examples
+///
+/// ```edition2018
+/// # use wmi::*;
+/// # use serde::Deserialize;
+/// #[derive(Deserialize, Debug)]
+/// struct Win32_Process {
+///     Name: String,
+///     ProcessId: u32,
+/// }
+///
+/// let query = build_query_ordered::<Win32_Process>(None, &[("ProcessId", SortOrder::Desc)])?;
+///
+/// assert_eq!(query, "SELECT Name,ProcessId FROM Win32_Process ORDER BY ProcessId DESC");
+/// # Ok::<(), WMIError>(())
+/// ```
+pub fn build_query_ordered<'de, T>(
+    filters: Option<&HashMap<String, FilterValue>>,
+    order_by: &[(&str, SortOrder)],
+) -> WMIResult<String>
+where
+    T: de::Deserialize<'de>,
+{
+    let query_text = build_query::<T>(filters)?;
+
+    if order_by.is_empty() {
+        return Ok(query_text);
+    }
+
+    let (name, fields) = struct_name_and_fields::<T>()?;
+
+    let mut order_by_items = vec![];
+
+    for (field, order) in order_by {
+        if !fields.iter().any(|f| f.eq_ignore_ascii_case(field)) {
+            return Err(de::Error::custom(format!(
+                "Cannot order by {:?}, which is not a field of {}",
+                field, name
+            )));
+        }
+
+        order_by_items.push(format!("{} {}", field, order.as_wql()));
+    }
+
+    Ok(format!(
+        "{} ORDER BY {}",
+        query_text.trim_end(),
+        order_by_items.join(", ")
+    ))
+}
+
+/// Shorter `WITHIN` polling intervals are logged as a warning.
+const MIN_RECOMMENDED_WITHIN: Duration = Duration::from_secs(1);
+
 /// Build an SQL query for an event notification subscription with the given filters and within polling time, over the given type (using its fields).
 /// For example, for:
 ///
@@ -143,6 +359,10 @@ where
 /// "SELECT * FROM Win32_ProcessStartTrace WITHIN 10 WHERE ProcessName = 'explorer.exe'";
 /// ```
 ///
+/// The within polling time must be greater than zero, or a [`WMIError::InvalidWithinIntervalError`] is returned.
+/// Intervals shorter than a second are allowed, but a warning is logged: when polling, WMI enumerates all the
+/// instances of the class on every interval, so very short intervals are expensive.
+///
 pub fn build_notification_query<'de, T>(
     filters: Option<&HashMap<String, FilterValue>>,
     within: Option<Duration>,
@@ -153,7 +373,19 @@ where
     let (name, _, optional_where_clause) = get_query_segments::<T>(filters)?;
 
     let optional_within_caluse = match within {
-        Some(within) => format!("WITHIN {} ", within.as_secs_f64()),
+        Some(within) if within.is_zero() => {
+            return Err(WMIError::InvalidWithinIntervalError(within));
+        }
+        Some(within) => {
+            if within < MIN_RECOMMENDED_WITHIN {
+                warn!(
+                    "Polling interval of {:?} for {} is very short, consider using at least {:?}",
+                    within, name, MIN_RECOMMENDED_WITHIN
+                );
+            }
+
+            format!("WITHIN {} ", within.as_secs_f64())
+        }
         None => String::new(),
     };
 
@@ -165,6 +397,18 @@ where
     Ok(query_text)
 }
 
+/// Return the WQL query that [`WMIConnection::notification`] (when `filters` and `within` are `None`)
+/// or [`WMIConnection::filtered_notification`] would execute for T, without executing it.
+pub fn explain_notification_query<T>(
+    filters: Option<&HashMap<String, FilterValue>>,
+    within: Option<Duration>,
+) -> WMIResult<String>
+where
+    T: de::DeserializeOwned,
+{
+    build_notification_query::<T>(filters, within)
+}
+
 fn get_query_segments<'de, T>(
     filters: Option<&HashMap<String, FilterValue>>,
 ) -> WMIResult<(&'static str, &'static [&'static str], String)>
@@ -263,6 +507,9 @@ pub fn quote_and_escape_wql_str(s: impl AsRef<str>) -> String {
     o
 }
 
+/// The number of objects fetched from WMI at once by [`WMIConnection::exec_query_native_wrapper`].
+const QUERY_BATCH_SIZE: usize = 64;
+
 impl WMIConnection {
     /// Execute the given query and return an iterator of WMI pointers.
     /// It's better to use the other query methods, since this is relatively low level.
@@ -270,22 +517,64 @@ impl WMIConnection {
     pub fn exec_query_native_wrapper(
         &self,
         query: impl AsRef<str>,
+    ) -> WMIResult<QueryResultEnumerator> {
+        self.exec_query_native_wrapper_with_flags(
+            query,
+            WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY,
+        )
+    }
+
+    /// Like [`WMIConnection::exec_query_native_wrapper`], but with the given flags
+    /// instead of the default `WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY`.
+    ///
+    /// See [ExecQuery](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-execquery) for the supported flags.
+    /// For example:
+    /// - `WBEM_FLAG_USE_AMENDED_QUALIFIERS` to include localized qualifiers (like `Description`) in the results.
+    /// - `WBEM_FLAG_ENSURE_LOCATABLE` to make sure the results have the system properties needed to locate them (like `__PATH`).
+    /// - `WBEM_FLAG_BIDIRECTIONAL` (the absence of `WBEM_FLAG_FORWARD_ONLY`) to allow the enumerator to be reset and re-enumerated.
+    ///
+    /// Note that without `WBEM_FLAG_FORWARD_ONLY`, WMI keeps a copy of every result until the enumerator is released,
+    /// which is slower and uses more memory. Without `WBEM_FLAG_RETURN_IMMEDIATELY`, the call blocks until all the results are ready.
+    ///
+    /// The flags are passed to `ExecQuery` as-is, so this can also be used as an escape hatch for flags which aren't
+    /// covered by the other query functions (a raw bitmask can be passed as `WBEM_GENERIC_FLAG_TYPE(bits)`).
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// use windows::Win32::System::Wmi::{WBEM_FLAG_FORWARD_ONLY, WBEM_FLAG_USE_AMENDED_QUALIFIERS};
+    ///
+    /// let enumerator = con.exec_query_native_wrapper_with_flags(
+    ///     "SELECT * FROM meta_class WHERE __CLASS = 'Win32_OperatingSystem'",
+    ///     WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_USE_AMENDED_QUALIFIERS,
+    /// )?;
+    ///
+    /// for class in enumerator {
+    ///     println!("{:?}", class?.get_qualifier("Description")?);
+    /// }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn exec_query_native_wrapper_with_flags(
+        &self,
+        query: impl AsRef<str>,
+        flags: WBEM_GENERIC_FLAG_TYPE,
     ) -> WMIResult<QueryResultEnumerator> {
         let query_language = BSTR::from("WQL");
-        let query = BSTR::from(query.as_ref());
+        let query = match &self.query_hook {
+            Some(hook) => BSTR::from(hook.call(query.as_ref())),
+            None => BSTR::from(query.as_ref()),
+        };
 
         let enumerator = unsafe {
-            self.svc.ExecQuery(
-                &query_language,
-                &query,
-                WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY,
-                &self.ctx.0,
-            )?
+            self.svc
+                .ExecQuery(&query_language, &query, flags, &self.ctx.0)?
         };
 
         trace!("Got enumerator {:?}", enumerator);
 
-        Ok(QueryResultEnumerator::new(self, enumerator))
+        Ok(QueryResultEnumerator::new(self, enumerator).with_batch_size(QUERY_BATCH_SIZE))
     }
 
     /// Execute a free-text query and deserialize the results.
@@ -305,7 +594,21 @@ impl WMIConnection {
     where
         T: de::DeserializeOwned,
     {
-        let enumerator = self.exec_query_native_wrapper(query)?;
+        self.raw_query_with_flags(query, WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY)
+    }
+
+    /// Like [`WMIConnection::raw_query`], but with the given flags.
+    ///
+    /// See [`WMIConnection::exec_query_native_wrapper_with_flags`] for the supported flags and their trade-offs.
+    pub fn raw_query_with_flags<T>(
+        &self,
+        query: impl AsRef<str>,
+        flags: WBEM_GENERIC_FLAG_TYPE,
+    ) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let enumerator = self.exec_query_native_wrapper_with_flags(query, flags)?;
 
         enumerator
             .map(|item| match item {
@@ -315,6 +618,126 @@ impl WMIConnection {
             .collect()
     }
 
+    /// Like [`WMIConnection::raw_query`], but properties with an unsupported `VARIANT` type do not fail the query.
+    ///
+    /// Instead, they are read as [`Variant::Unsupported`] placeholders (holding the `VARTYPE`), which are
+    /// deserialized as `None` into `Option` fields, so the rest of the object can still be used.
+    /// See [`IWbemClassWrapper::into_desr_lenient`].
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use std::collections::HashMap;
+    /// # use wmi::*;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let results: Vec<HashMap<String, Variant>> = con.raw_query_lenient("SELECT * FROM Win32_OperatingSystem")?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn raw_query_lenient<T>(&self, query: impl AsRef<str>) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        self.exec_query_native_wrapper(query)?
+            .map(|item| match item {
+                Ok(wbem_class_obj) => wbem_class_obj.into_desr_lenient(),
+                Err(e) => Err(e),
+            })
+            .collect()
+    }
+
+    /// Execute a free-text query and deserialize only the first result.
+    /// If there are no results, a [`WMIError::ResultEmpty`] error is returned.
+    ///
+    /// Like [`WMIConnection::get`], but for any query (and any `T`, such as a `HashMap`).
+    /// The rest of the results are not read.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use std::collections::HashMap;
+    /// # use wmi::*;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let os: HashMap<String, Variant> = con.raw_query_one("SELECT Caption FROM Win32_OperatingSystem")?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn raw_query_one<T>(&self, query: impl AsRef<str>) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        self.exec_query_native_wrapper(query)?
+            .next()
+            .ok_or(WMIError::ResultEmpty)??
+            .into_desr()
+    }
+
+    /// Wrapper for WMI's [CreateInstanceEnum](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemservices-createinstanceenum)
+    /// function, which enumerates the instances of a class.
+    ///
+    /// If `shallow` is `true`, only instances of `class_name` itself are returned (`WBEM_FLAG_SHALLOW`).
+    /// Otherwise, instances of every class deriving from it are returned as well (`WBEM_FLAG_DEEP`),
+    /// which is also the behavior of a `SELECT * FROM` query.
+    ///
+    /// Each provider enumerates the instances of its own classes, so a shallow enumeration of a base class
+    /// (like `CIM_LogicalDisk`) is usually empty, since all the instances belong to derived classes.
+    pub fn enumerate_instances_native_wrapper(
+        &self,
+        class_name: impl AsRef<str>,
+        shallow: bool,
+    ) -> WMIResult<QueryResultEnumerator> {
+        let class_name = BSTR::from(class_name.as_ref());
+
+        let depth = if shallow {
+            WBEM_FLAG_SHALLOW
+        } else {
+            WBEM_FLAG_DEEP
+        };
+        let flags =
+            WBEM_GENERIC_FLAG_TYPE(depth.0) | WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_RETURN_IMMEDIATELY;
+
+        let enumerator = unsafe {
+            self.svc
+                .CreateInstanceEnum(&class_name, flags, &self.ctx.0)?
+        };
+
+        trace!("Got enumerator {:?}", enumerator);
+
+        Ok(QueryResultEnumerator::new(self, enumerator).with_batch_size(QUERY_BATCH_SIZE))
+    }
+
+    /// Enumerate all the instances of the class `T` (using the name of the struct), and deserialize them.
+    ///
+    /// See [`WMIConnection::enumerate_instances_native_wrapper`] for the meaning of `shallow`.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use serde::Deserialize;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// #[derive(Deserialize, Debug)]
+    /// struct CIM_LogicalDisk {
+    ///     __CLASS: String,
+    ///     DeviceID: String,
+    /// }
+    ///
+    /// // Includes instances of `Win32_LogicalDisk`, `Win32_MappedLogicalDisk`, etc.
+    /// let disks: Vec<CIM_LogicalDisk> = con.enumerate_instances(false)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn enumerate_instances<T>(&self, shallow: bool) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let (class_name, _) = struct_name_and_fields::<T>()?;
+
+        self.enumerate_instances_native_wrapper(class_name, shallow)?
+            .map(|item| match item {
+                Ok(wbem_class_obj) => wbem_class_obj.into_desr(),
+                Err(e) => Err(e),
+            })
+            .collect()
+    }
+
     /// Query all the objects of type T.
     ///
     /// ```edition2018
@@ -342,6 +765,41 @@ impl WMIConnection {
         self.raw_query(query_text)
     }
 
+    /// Query all the objects of type T, and return an iterator of WMIResult\<T\>.
+    ///
+    /// Unlike `query`, results are fetched and deserialized lazily, which is useful
+    /// when only some of the results are needed or when the result set is large.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// use wmi::*;
+    /// use serde::Deserialize;
+    ///
+    /// let con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_Process {
+    ///     Name: String,
+    /// }
+    ///
+    /// let first_proc = con.query_iter::<Win32_Process>()?.next();
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn query_iter<'a, T>(&'a self) -> WMIResult<impl Iterator<Item = WMIResult<T>> + 'a>
+    where
+        T: de::DeserializeOwned + 'a,
+    {
+        let query_text = build_query::<T>(None)?;
+
+        let enumerator = self.exec_query_native_wrapper(query_text)?;
+        let iter = enumerator.map(|item| match item {
+            Ok(wbem_class_obj) => wbem_class_obj.into_desr(),
+            Err(e) => Err(e),
+        });
+        Ok(iter)
+    }
+
     /// Query all the objects of type T, while filtering according to `filters`.
     ///
     /// ```edition2018
@@ -374,35 +832,380 @@ impl WMIConnection {
         self.raw_query(query_text)
     }
 
-    /// Get a single object of type T.
-    /// If none are found, an error is returned.
-    /// If more than one object is found, all but the first are ignored.
+    /// Like [`WMIConnection::filtered_query`] (or [`WMIConnection::query`] when `filters` is `None`),
+    /// but selects `T`'s fields from `class_name` instead of from the class named after `T`.
+    ///
+    /// This allows using the same struct for different classes which share the selected fields.
     ///
     /// ```edition2018
     /// # fn main() -> wmi::WMIResult<()> {
-    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// # use std::collections::HashMap;
     /// # use wmi::*;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
     /// use serde::Deserialize;
-    /// #[derive(Deserialize)]
-    /// struct Win32_OperatingSystem {
+    /// #[derive(Deserialize, Debug)]
+    /// struct Element {
     ///     Name: String,
     /// }
     ///
-    /// let os = con.get::<Win32_OperatingSystem>()?;
+    /// let mut filters = HashMap::new();
+    /// filters.insert("Name".to_owned(), FilterValue::Str(r#"C:\Windows"#));
+    ///
+    /// let results = con.query_class::<Element>("Win32_Directory", Some(&filters))?;
+    ///
+    /// assert_eq!(results.len(), 1);
     /// #   Ok(())
     /// # }
     /// ```
-    pub fn get<T>(&self) -> WMIResult<T>
+    pub fn query_class<T>(
+        &self,
+        class_name: &str,
+        filters: Option<&HashMap<String, FilterValue>>,
+    ) -> WMIResult<Vec<T>>
     where
         T: de::DeserializeOwned,
     {
-        let results = self.query()?;
+        let query_text = build_query_from::<T>(Some(class_name), filters)?;
 
-        results.into_iter().next().ok_or(WMIError::ResultEmpty)
+        self.raw_query(query_text)
     }
 
-    /// Get a WMI object by path, and return a wrapper around a WMI pointer.
-    /// It's better to use the `get_by_path` method, since this function is more low level.
+    /// Query at most `n` objects of type T.
+    /// Stops enumerating the results once `n` objects were returned, so only they are deserialized.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// use wmi::*;
+    /// use serde::Deserialize;
+    ///
+    /// let con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_Process {
+    ///     Name: String,
+    /// }
+    ///
+    /// let procs: Vec<Win32_Process> = con.query_top(3)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn query_top<T>(&self, n: usize) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let query_text = build_query::<T>(None)?;
+
+        self.raw_query_top(query_text, n)
+    }
+
+    /// Query at most `n` objects of type T, while filtering according to `filters`.
+    /// Stops enumerating the results once `n` objects were returned, so only they are deserialized.
+    pub fn filtered_query_top<T>(
+        &self,
+        filters: &HashMap<String, FilterValue>,
+        n: usize,
+    ) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let query_text = build_query::<T>(Some(filters))?;
+
+        self.raw_query_top(query_text, n)
+    }
+
+    fn raw_query_top<T>(&self, query: impl AsRef<str>, n: usize) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let enumerator = self
+            .exec_query_native_wrapper(query)?
+            .with_batch_size(n.min(QUERY_BATCH_SIZE));
+
+        enumerator
+            .take(n)
+            .map(|item| match item {
+                Ok(wbem_class_obj) => wbem_class_obj.into_desr(),
+                Err(e) => Err(e),
+            })
+            .collect()
+    }
+
+    /// Check whether querying the given class requires elevation (running as an administrator),
+    /// by querying a single instance of the class and checking for `WBEM_E_ACCESS_DENIED`,
+    /// `E_ACCESSDENIED` or `WBEM_E_PRIVILEGE_NOT_HELD` errors.
+    ///
+    /// This is a best-effort check, useful for prompting for elevation before reading a class:
+    /// some providers only check for access when specific properties or instances are read.
+    /// Other errors (like `WBEM_E_INVALID_CLASS`) are returned as is,
+    /// and a `class_name` which is not a valid class name is rejected without querying.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// if con.requires_elevation("Win32_OperatingSystem")? {
+    ///     println!("Please run as an administrator");
+    /// }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn requires_elevation(&self, class_name: &str) -> WMIResult<bool> {
+        // The class name is used as is in the query, so it must not contain anything else.
+        let query = format!(
+            "SELECT * FROM {}",
+            validate_identifier::<WMIError>(class_name)?
+        );
+
+        let first = self
+            .exec_query_native_wrapper(query)
+            .and_then(|enumerator| enumerator.with_batch_size(1).next().transpose());
+
+        match first {
+            Ok(_) => Ok(false),
+            Err(WMIError::HResultError { hres, .. })
+                if [
+                    WBEM_E_ACCESS_DENIED.0,
+                    E_ACCESSDENIED.0,
+                    WBEM_E_PRIVILEGE_NOT_HELD.0,
+                ]
+                .contains(&hres) =>
+            {
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Query all the objects of type T, while filtering according to `filters` and ordering according to `order_by`.
+    ///
+    /// See [`build_query_ordered`] for the generated query.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_Process {
+    ///     Name: String,
+    ///     ProcessId: u32,
+    /// }
+    ///
+    /// let results = con.ordered_query::<Win32_Process>(None, &[("ProcessId", SortOrder::Asc)]);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn ordered_query<T>(
+        &self,
+        filters: Option<&HashMap<String, FilterValue>>,
+        order_by: &[(&str, SortOrder)],
+    ) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let query_text = build_query_ordered::<T>(filters, order_by)?;
+
+        self.raw_query(query_text)
+    }
+
+    /// Query all the objects of type T in the namespace of this connection and in all of its child namespaces, recursively.
+    ///
+    /// Each result is returned with the path of the namespace it was found in,
+    /// relative to the namespace of this connection (the connection's own namespace is `""`).
+    ///
+    /// Namespaces where the class does not exist, or which cannot be accessed, are skipped.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// use wmi::*;
+    /// use serde::Deserialize;
+    ///
+    /// let con = WMIConnection::with_namespace_path("ROOT\\Microsoft\\Windows", COMLibrary::new()?)?;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct __Win32Provider {
+    ///     Name: String,
+    /// }
+    ///
+    /// for (namespace, provider) in con.query_recursive::<__Win32Provider>()? {
+    ///     println!("{}: {}", namespace, provider.Name);
+    /// }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn query_recursive<T>(&self) -> WMIResult<Vec<(String, T)>>
+    where
+        T: de::DeserializeOwned,
+    {
+        let query_text = build_query::<T>(None)?;
+
+        let mut results = vec![];
+        self.query_recursive_inner(&query_text, "", &mut results)?;
+
+        Ok(results)
+    }
+
+    fn query_recursive_inner<T>(
+        &self,
+        query_text: &str,
+        namespace_path: &str,
+        results: &mut Vec<(String, T)>,
+    ) -> WMIResult<()>
+    where
+        T: de::DeserializeOwned,
+    {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "__NAMESPACE")]
+        struct Namespace {
+            #[serde(rename = "Name")]
+            name: String,
+        }
+
+        fn is_skippable(err: &WMIError) -> bool {
+            match err {
+                WMIError::HResultError { hres, .. } => [
+                    WBEM_E_INVALID_CLASS.0,
+                    WBEM_E_ACCESS_DENIED.0,
+                    E_ACCESSDENIED.0,
+                ]
+                .contains(hres),
+                _ => false,
+            }
+        }
+
+        match self.raw_query::<T>(query_text) {
+            Ok(items) => results.extend(
+                items
+                    .into_iter()
+                    .map(|item| (namespace_path.to_string(), item)),
+            ),
+            Err(err) if is_skippable(&err) => {}
+            Err(err) => return Err(err),
+        }
+
+        let children = match self.query::<Namespace>() {
+            Ok(children) => children,
+            Err(err) if is_skippable(&err) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        for child in children {
+            let child_con = match self.open_namespace(&child.name) {
+                Ok(child_con) => child_con,
+                Err(err) if is_skippable(&err) => continue,
+                Err(err) => return Err(err),
+            };
+
+            let child_path = if namespace_path.is_empty() {
+                child.name
+            } else {
+                format!("{}\\{}", namespace_path, child.name)
+            };
+
+            child_con.query_recursive_inner(query_text, &child_path, results)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get a single object of type T.
+    /// If none are found, an error is returned.
+    /// If more than one object is found, all but the first are ignored.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// # use wmi::*;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Win32_OperatingSystem {
+    ///     Name: String,
+    /// }
+    ///
+    /// let os = con.get::<Win32_OperatingSystem>()?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn get<T>(&self) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        let results = self.query()?;
+
+        results.into_iter().next().ok_or(WMIError::ResultEmpty)
+    }
+
+    /// Count the objects of type T (optionally matching the given filters), without deserializing them.
+    ///
+    /// WQL doesn't support `COUNT(*)`, so the objects are still returned by WMI,
+    /// but only the properties used by T are selected, and the results are only counted.
+    ///
+    /// ```edition2021
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Win32_Process {
+    ///     Name: String,
+    /// }
+    ///
+    /// let process_count = con.count::<Win32_Process>(None)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn count<T>(&self, filters: Option<&HashMap<String, FilterValue>>) -> WMIResult<usize>
+    where
+        T: de::DeserializeOwned,
+    {
+        let query_text = build_query::<T>(filters)?;
+
+        let mut count = 0;
+
+        for item in self.exec_query_native_wrapper(query_text)? {
+            item?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Check if any object of type T matches the given filters.
+    ///
+    /// Unlike `filtered_query(...).is_empty()`, this stops after the first result, without deserializing it.
+    ///
+    /// ```edition2021
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use std::collections::HashMap;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Win32_Process {
+    ///     Name: String,
+    /// }
+    ///
+    /// let mut filters = HashMap::new();
+    /// filters.insert("Name".to_owned(), FilterValue::Str("explorer.exe"));
+    ///
+    /// let is_running = con.exists::<Win32_Process>(&filters)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn exists<T>(&self, filters: &HashMap<String, FilterValue>) -> WMIResult<bool>
+    where
+        T: de::DeserializeOwned,
+    {
+        let query_text = build_query::<T>(Some(filters))?;
+
+        match self.exec_query_native_wrapper(query_text)?.next() {
+            Some(item) => item.map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Get a WMI object by path, and return a wrapper around a WMI pointer.
+    /// It's better to use the `get_by_path` method, since this function is more low level.
     ///
     /// ```edition2018
     /// # fn main() -> wmi::WMIResult<()> {
@@ -534,147 +1337,820 @@ impl WMIConnection {
         from_wbem_class_obj(wbem_class_obj)
     }
 
-    /// Query all the associators of type T of the given object.
-    /// The `object_path` argument can be provided by querying an object wih it's `__Path` property.
-    /// `AssocClass` must be have the name as the connecting association class between the original object and the results.
-    /// See <https://docs.microsoft.com/en-us/windows/desktop/cimwin32prov/win32-diskdrivetodiskpartition> for example.
+    /// Get the WMI object referenced by a [`Variant::Reference`] (like the `Antecedent` of an association class),
+    /// and return a deserialized object.
     ///
     /// ```edition2018
     /// # fn main() -> wmi::WMIResult<()> {
+    /// # use std::collections::HashMap;
     /// # use wmi::*;
     /// # use serde::Deserialize;
     /// # let con = WMIConnection::new(COMLibrary::new()?)?;
-    ///
     /// #[derive(Deserialize, Debug)]
     /// struct Win32_DiskDrive {
-    ///     // `__Path` is a WMI-internal property used to uniquely identify objects.
-    ///     __Path: String,
     ///     Caption: String,
     /// }
     ///
+    /// let results: Vec<HashMap<String, Variant>> = con.raw_query("SELECT * FROM Win32_DiskDriveToDiskPartition")?;
+    ///
+    /// for association in results {
+    ///     let disk: Win32_DiskDrive = con.get_by_reference(&association["Antecedent"])?;
+    ///     println!("{:?}", disk);
+    /// }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn get_by_reference<T>(&self, reference: &Variant) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        let object_path = reference.as_reference().ok_or_else(|| {
+            WMIError::ConvertVariantError(format!("Variant {:?} is not a reference", reference))
+        })?;
+
+        self.get_by_path(object_path)
+    }
+
+    /// Get the association instance at `assoc_path` (like a `Win32_GroupUser`),
+    /// and resolve both of its endpoints to deserialized objects.
+    ///
+    /// The struct `A` must have exactly two fields, which are the names of the reference properties (the roles) of the association.
+    /// The object referenced by the first field is deserialized into `L`, and the second into `R`.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use serde::Deserialize;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_GroupUser {
+    ///     GroupComponent: String,
+    ///     PartComponent: String,
+    /// }
+    ///
     /// #[derive(Deserialize, Debug)]
-    /// struct Win32_DiskPartition {
+    /// struct Win32_Group {
+    ///     Name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_Account {
     ///     Caption: String,
     /// }
     ///
-    /// // There's no need to specify any fields here, since only the name of the struct is used by `associators`.
     /// #[derive(Deserialize, Debug)]
-    /// struct Win32_DiskDriveToDiskPartition {}
+    /// struct Association {
+    ///     __Path: String,
+    /// }
     ///
-    /// let disk = con.get::<Win32_DiskDrive>()?;
-    /// let results = con.associators::<Win32_DiskPartition, Win32_DiskDriveToDiskPartition>(&disk.__Path)?;
+    /// let associations: Vec<Association> = con.raw_query("SELECT * FROM Win32_GroupUser")?;
+    ///
+    /// for association in associations {
+    ///     let (group, account) = con
+    ///         .resolve_association_endpoints::<Win32_GroupUser, Win32_Group, Win32_Account>(&association.__Path)?;
+    ///     println!("{} is a member of {}", account.Caption, group.Name);
+    /// }
     /// #   Ok(())
     /// # }
     /// ```
-    pub fn associators<ResultClass, AssocClass>(
-        &self,
-        object_path: &str,
-    ) -> WMIResult<Vec<ResultClass>>
+    pub fn resolve_association_endpoints<A, L, R>(&self, assoc_path: &str) -> WMIResult<(L, R)>
+    where
+        A: de::DeserializeOwned,
+        L: de::DeserializeOwned,
+        R: de::DeserializeOwned,
+    {
+        let (name, fields) = struct_name_and_fields::<A>()?;
+
+        let [left_role, right_role] = fields else {
+            return Err(WMIError::SerdeError(format!(
+                "Association {} must have exactly two fields (the roles of its endpoints), found {:?}",
+                name, fields
+            )));
+        };
+
+        let association = self.get_raw_by_path(assoc_path)?;
+
+        let left = self.get_by_reference(&association.get_property(left_role)?)?;
+        let right = self.get_by_reference(&association.get_property(right_role)?)?;
+
+        Ok((left, right))
+    }
+
+    /// Query all the associators of type T of the given object.
+    /// The `object_path` argument can be provided by querying an object wih it's `__Path` property.
+    /// `AssocClass` must be have the name as the connecting association class between the original object and the results.
+    /// See <https://docs.microsoft.com/en-us/windows/desktop/cimwin32prov/win32-diskdrivetodiskpartition> for example.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use serde::Deserialize;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_DiskDrive {
+    ///     // `__Path` is a WMI-internal property used to uniquely identify objects.
+    ///     __Path: String,
+    ///     Caption: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_DiskPartition {
+    ///     Caption: String,
+    /// }
+    ///
+    /// // There's no need to specify any fields here, since only the name of the struct is used by `associators`.
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_DiskDriveToDiskPartition {}
+    ///
+    /// let disk = con.get::<Win32_DiskDrive>()?;
+    /// let results = con.associators::<Win32_DiskPartition, Win32_DiskDriveToDiskPartition>(&disk.__Path)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn associators<ResultClass, AssocClass>(
+        &self,
+        object_path: &str,
+    ) -> WMIResult<Vec<ResultClass>>
+    where
+        ResultClass: de::DeserializeOwned,
+        AssocClass: de::DeserializeOwned,
+    {
+        self.associators_with_options::<ResultClass, AssocClass>(
+            object_path,
+            &AssociatorsOptions::default(),
+        )
+    }
+
+    /// Like [`WMIConnection::associators`], but with additional qualifiers (like `Role` and `ResultRole`)
+    /// which can be used to disambiguate associations where both ends are of the same class.
+    ///
+    /// See [`build_associators_query`] for the generated query.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use serde::Deserialize;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_Account {
+    ///     Caption: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_GroupUser {}
+    ///
+    /// // Only get the members of the group, and not the groups it is a member of.
+    /// let options = AssociatorsOptions {
+    ///     role: Some("GroupComponent".to_string()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let members = con.associators_with_options::<Win32_Account, Win32_GroupUser>(
+    ///     r#"Win32_Group.Domain="BUILTIN",Name="Administrators""#,
+    ///     &options,
+    /// )?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn associators_with_options<ResultClass, AssocClass>(
+        &self,
+        object_path: &str,
+        options: &AssociatorsOptions,
+    ) -> WMIResult<Vec<ResultClass>>
+    where
+        ResultClass: de::DeserializeOwned,
+        AssocClass: de::DeserializeOwned,
+    {
+        let query = build_associators_query::<ResultClass, AssocClass>(object_path, options)?;
+
+        self.raw_query(query)
+    }
+
+    /// Query all the association instances of type `AssocClass` which refer to the given object.
+    /// Unlike [`WMIConnection::associators`], which returns the objects at the other end of the associations,
+    /// this returns the association instances themselves.
+    ///
+    /// See <https://learn.microsoft.com/en-us/windows/win32/wmisdk/references-of-statement> for more info.
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use serde::Deserialize;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_DiskDrive {
+    ///     __Path: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Win32_DiskDriveToDiskPartition {
+    ///     Antecedent: String,
+    ///     Dependent: String,
+    /// }
+    ///
+    /// let disk = con.get::<Win32_DiskDrive>()?;
+    /// let results = con.references::<Win32_DiskDriveToDiskPartition>(&disk.__Path)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn references<AssocClass>(&self, object_path: &str) -> WMIResult<Vec<AssocClass>>
+    where
+        AssocClass: de::DeserializeOwned,
+    {
+        self.references_inner(object_path, None)
+    }
+
+    /// Like [`WMIConnection::references`], but only returns associations where the given object plays the given `role`
+    /// (which is the name of the association's property referring to the object, like `Antecedent` or `Dependent`).
+    ///
+    /// ```edition2018
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// # use serde::Deserialize;
+    /// # let con = WMIConnection::new(COMLibrary::new()?)?;
+    /// # #[derive(Deserialize, Debug)]
+    /// # struct Win32_DiskDrive {
+    /// #     __Path: String,
+    /// # }
+    /// # #[derive(Deserialize, Debug)]
+    /// # struct Win32_DiskDriveToDiskPartition {
+    /// #     Dependent: String,
+    /// # }
+    /// let disk = con.get::<Win32_DiskDrive>()?;
+    /// let results = con.references_with_role::<Win32_DiskDriveToDiskPartition>(&disk.__Path, "Antecedent")?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn references_with_role<AssocClass>(
+        &self,
+        object_path: &str,
+        role: &str,
+    ) -> WMIResult<Vec<AssocClass>>
+    where
+        AssocClass: de::DeserializeOwned,
+    {
+        self.references_inner(object_path, Some(role))
+    }
+
+    fn references_inner<AssocClass>(
+        &self,
+        object_path: &str,
+        role: Option<&str>,
+    ) -> WMIResult<Vec<AssocClass>>
     where
-        ResultClass: de::DeserializeOwned,
         AssocClass: de::DeserializeOwned,
     {
-        let (class_name, _fields) = struct_name_and_fields::<ResultClass>()?;
         let (association_class, _) = struct_name_and_fields::<AssocClass>()?;
 
-        // See more at:
-        // https://docs.microsoft.com/en-us/windows/desktop/wmisdk/associators-of-statement
-        let query = format!(
-            "ASSOCIATORS OF {{{object_path}}} WHERE AssocClass = {association_class} ResultClass = {class_name}",
-            object_path = object_path,
-            association_class = association_class,
-            class_name = class_name
+        let mut query = format!(
+            "REFERENCES OF {{{object_path}}} WHERE ResultClass = {association_class}",
+            object_path = object_path,
+            association_class = association_class,
+        );
+
+        if let Some(role) = role {
+            query.push_str(&format!(" Role = {}", role));
+        }
+
+        self.raw_query(query)
+    }
+}
+
+#[allow(non_snake_case)]
+#[allow(non_camel_case_types)]
+#[allow(dead_code)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use windows::Win32::System::Wmi::WBEM_E_INVALID_QUERY;
+
+    use crate::tests::fixtures::*;
+    use crate::{Variant, WMIError};
+
+    #[test]
+    fn it_works() {
+        let wmi_con = wmi_con();
+
+        let enumerator = wmi_con
+            .exec_query_native_wrapper("SELECT * FROM Win32_OperatingSystem")
+            .unwrap();
+
+        for res in enumerator {
+            let w = res.unwrap();
+            let mut props = w.list_properties().unwrap();
+
+            props.sort();
+
+            assert_eq!(props.len(), 64);
+            assert_eq!(props[..2], ["BootDevice", "BuildNumber"]);
+            assert_eq!(props[props.len() - 2..], ["Version", "WindowsDirectory"]);
+
+            let result = serde_json::to_string_pretty(&w);
+
+            assert!(result.is_ok());
+            assert!(result.unwrap().len() > 2)
+        }
+    }
+
+    #[test]
+    fn it_fails_gracefully() {
+        let wmi_con = wmi_con();
+
+        let enumerator = wmi_con
+            .exec_query_native_wrapper("SELECT NoSuchField FROM Win32_OperatingSystem")
+            .unwrap();
+
+        for res in enumerator {
+            assert!(res.is_err())
+        }
+    }
+
+    #[test]
+    fn it_fails_gracefully_with_invalid_sql() {
+        let wmi_con = wmi_con();
+
+        let enumerator = wmi_con.exec_query_native_wrapper("42").unwrap();
+
+        // Show how to detect which error had occurred.
+        for res in enumerator {
+            match res {
+                Ok(_) => assert!(false),
+                Err(wmi_err) => match wmi_err {
+                    WMIError::HResultError { hres, .. } => {
+                        assert_eq!(hres, WBEM_E_INVALID_QUERY.0);
+                    }
+                    _ => assert!(false),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn it_can_query_a_struct() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_OperatingSystem {
+            Caption: String,
+        }
+
+        let results = wmi_con.query::<Win32_OperatingSystem>().unwrap();
+
+        for os in results {
+            assert!(os.Caption.starts_with("Microsoft Windows"));
+        }
+    }
+
+    #[test]
+    fn it_can_query_a_single_object() {
+        let wmi_con = wmi_con();
+
+        let os: HashMap<String, Variant> = wmi_con
+            .raw_query_one("SELECT * FROM Win32_OperatingSystem")
+            .unwrap();
+
+        assert!(matches!(os.get("Caption"), Some(Variant::String(_))));
+
+        let process: HashMap<String, Variant> = wmi_con
+            .raw_query_one("SELECT Name FROM Win32_Process")
+            .unwrap();
+
+        assert!(process.contains_key("Name"));
+
+        let res: WMIResult<HashMap<String, Variant>> =
+            wmi_con.raw_query_one("SELECT * FROM Win32_Process WHERE ProcessId = 4294967295");
+
+        assert!(matches!(res, Err(WMIError::ResultEmpty)));
+    }
+
+    #[test]
+    fn it_can_iterate_over_query_results() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            Name: String,
+        }
+
+        let mut procs = wmi_con.query_iter::<Win32_Process>().unwrap();
+
+        let first_proc = procs.next().unwrap().unwrap();
+
+        assert!(!first_proc.Name.is_empty());
+    }
+
+    #[test]
+    fn it_can_query_a_class_by_name() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct File {
+            Name: String,
+            Extension: String,
+        }
+
+        let mut filters = HashMap::new();
+        filters.insert(
+            "Name".to_owned(),
+            FilterValue::Str(r#"C:\Windows\System32\kernel32.dll"#),
+        );
+
+        let files = wmi_con
+            .query_class::<File>("CIM_DataFile", Some(&filters))
+            .unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].Name.to_lowercase(),
+            r#"c:\windows\system32\kernel32.dll"#
+        );
+        assert_eq!(files[0].Extension.to_lowercase(), "dll");
+    }
+
+    #[test]
+    fn it_can_query_recursively() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct __NAMESPACE {
+            Name: String,
+        }
+
+        let results = wmi_con.query_recursive::<__NAMESPACE>().unwrap();
+
+        let mut namespaces: Vec<_> = results.iter().map(|(ns, _)| ns.as_str()).collect();
+        namespaces.dedup();
+
+        assert!(namespaces.contains(&""));
+        assert!(namespaces.len() > 1);
+    }
+
+    #[test]
+    fn it_returns_the_same_results_with_batching() {
+        let wmi_con = wmi_con();
+
+        let query =
+            r#"SELECT Name FROM Win32_Directory WHERE Drive = "C:" AND Path = "\\Windows\\""#;
+
+        let names = |batch_size| -> Vec<String> {
+            wmi_con
+                .exec_query_native_wrapper(query)
+                .unwrap()
+                .with_batch_size(batch_size)
+                .map(|obj| {
+                    obj.unwrap()
+                        .get_property("Name")
+                        .unwrap()
+                        .try_into()
+                        .unwrap()
+                })
+                .collect()
+        };
+
+        let one_at_a_time = names(1);
+
+        assert!(one_at_a_time.len() > 1);
+        assert_eq!(names(QUERY_BATCH_SIZE), one_at_a_time);
+        assert_eq!(names(7), one_at_a_time);
+    }
+
+    #[test]
+    fn it_can_query_top_results() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            Name: String,
+        }
+
+        let procs = wmi_con.query_top::<Win32_Process>(3).unwrap();
+        assert_eq!(procs.len(), 3);
+        assert!(procs.iter().all(|p| !p.Name.is_empty()));
+
+        let mut filters = HashMap::new();
+        filters.insert("Name".to_owned(), FilterValue::StrLike("%"));
+
+        let procs = wmi_con
+            .filtered_query_top::<Win32_Process>(&filters, 3)
+            .unwrap();
+        assert_eq!(procs.len(), 3);
+
+        let procs = wmi_con.query_top::<Win32_Process>(0).unwrap();
+        assert!(procs.is_empty());
+    }
+
+    #[test]
+    fn it_can_check_if_a_class_requires_elevation() {
+        let wmi_con = wmi_con();
+
+        assert!(!wmi_con.requires_elevation("Win32_OperatingSystem").unwrap());
+
+        match wmi_con.requires_elevation("Win32_NoSuchClass") {
+            Err(WMIError::HResultError { hres, .. }) => assert_eq!(hres, WBEM_E_INVALID_CLASS.0),
+            res => panic!("Unexpected result {:?}", res),
+        }
+
+        for class_name in [
+            "",
+            "1Win32_OperatingSystem",
+            "Win32_OperatingSystem WHERE Caption IS NULL",
+            "Win32_OperatingSystem, Win32_Process",
+        ] {
+            assert!(
+                matches!(
+                    wmi_con.requires_elevation(class_name),
+                    Err(WMIError::SerdeError(_))
+                ),
+                "{:?} should be rejected",
+                class_name
+            );
+        }
+    }
+
+    #[test]
+    fn it_can_query_with_amended_qualifiers() {
+        use windows::Win32::System::Wmi::{WBEM_E_NOT_FOUND, WBEM_FLAG_USE_AMENDED_QUALIFIERS};
+
+        let wmi_con = wmi_con();
+        let query = "SELECT * FROM meta_class WHERE __CLASS = 'Win32_OperatingSystem'";
+
+        let classes: Vec<_> = wmi_con
+            .exec_query_native_wrapper_with_flags(
+                query,
+                WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_USE_AMENDED_QUALIFIERS,
+            )
+            .unwrap()
+            .collect::<WMIResult<_>>()
+            .unwrap();
+
+        assert_eq!(classes.len(), 1);
+        assert!(matches!(
+            classes[0].get_qualifier("Description"),
+            Ok(Variant::String(_))
+        ));
+
+        let classes: Vec<_> = wmi_con
+            .exec_query_native_wrapper(query)
+            .unwrap()
+            .collect::<WMIResult<_>>()
+            .unwrap();
+
+        match classes[0].get_qualifier("Description") {
+            Err(WMIError::HResultError { hres, .. }) => assert_eq!(hres, WBEM_E_NOT_FOUND.0),
+            res => panic!("Unexpected result {:?}", res),
+        }
+
+        // Amended qualifiers only affect class definitions, so instances are returned unchanged.
+        let query = "SELECT Caption FROM Win32_OperatingSystem";
+        let results: Vec<HashMap<String, Variant>> = wmi_con
+            .raw_query_with_flags(
+                query,
+                WBEM_FLAG_FORWARD_ONLY | WBEM_FLAG_USE_AMENDED_QUALIFIERS,
+            )
+            .unwrap();
+        let default_results: Vec<HashMap<String, Variant>> = wmi_con.raw_query(query).unwrap();
+
+        assert_eq!(results, default_results);
+    }
+
+    #[test]
+    fn it_can_query_with_explicit_default_flags() {
+        let wmi_con = wmi_con();
+        let query = "SELECT Caption FROM Win32_OperatingSystem";
+
+        let flags =
+            WBEM_GENERIC_FLAG_TYPE(WBEM_FLAG_FORWARD_ONLY.0 | WBEM_FLAG_RETURN_IMMEDIATELY.0);
+
+        let objects: Vec<_> = wmi_con
+            .exec_query_native_wrapper_with_flags(query, flags)
+            .unwrap()
+            .collect::<WMIResult<_>>()
+            .unwrap();
+        assert_eq!(objects.len(), 1);
+
+        let results: Vec<HashMap<String, Variant>> =
+            wmi_con.raw_query_with_flags(query, flags).unwrap();
+        let default_results: Vec<HashMap<String, Variant>> = wmi_con.raw_query(query).unwrap();
+
+        assert_eq!(results, default_results);
+    }
+
+    #[test]
+    fn it_reads_supported_properties_the_same_when_querying_leniently() {
+        let wmi_con = wmi_con();
+
+        // WMI classes rarely have properties with unsupported `VARIANT` types, so this only checks that
+        // lenient queries don't change supported properties. Reading unsupported types as placeholders is tested
+        // by `it_convert_unsupported_variant_type_leniently` and `it_desr_unsupported_variant` in `variant.rs`.
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_OperatingSystem {
+            Caption: String,
+            FreePhysicalMemory: Option<u64>,
+        }
+
+        let results: Vec<Win32_OperatingSystem> = wmi_con
+            .raw_query_lenient("SELECT * FROM Win32_OperatingSystem")
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].Caption.contains("Microsoft "));
+        assert!(results[0].FreePhysicalMemory.is_some());
+
+        let results: Vec<HashMap<String, Variant>> = wmi_con
+            .raw_query_lenient("SELECT * FROM Win32_OperatingSystem")
+            .unwrap();
+        let strict_results: Vec<HashMap<String, Variant>> = wmi_con
+            .raw_query("SELECT * FROM Win32_OperatingSystem")
+            .unwrap();
+
+        assert_eq!(results[0].len(), strict_results[0].len());
+    }
+
+    #[test]
+    fn it_can_enumerate_instances_shallow_and_deep() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct CIM_LogicalDisk {
+            __CLASS: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_LogicalDisk {
+            __CLASS: String,
+        }
+
+        let deep: Vec<CIM_LogicalDisk> = wmi_con.enumerate_instances(false).unwrap();
+        let shallow: Vec<CIM_LogicalDisk> = wmi_con.enumerate_instances(true).unwrap();
+
+        // All the instances belong to derived classes.
+        assert!(!deep.is_empty());
+        assert!(shallow.is_empty());
+        assert!(deep.iter().any(|disk| disk.__CLASS == "Win32_LogicalDisk"));
+
+        let logical_disks: Vec<Win32_LogicalDisk> = wmi_con.enumerate_instances(true).unwrap();
+        assert!(logical_disks
+            .iter()
+            .all(|disk| disk.__CLASS == "Win32_LogicalDisk"));
+        assert!(logical_disks.len() <= deep.len());
+
+        let query_results: Vec<CIM_LogicalDisk> = wmi_con.query().unwrap();
+        assert_eq!(query_results.len(), deep.len());
+    }
+
+    #[test]
+    fn it_can_count_objects() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            ProcessId: u32,
+        }
+
+        let count = wmi_con.count::<Win32_Process>(None).unwrap();
+        let processes: Vec<Win32_Process> = wmi_con.query().unwrap();
+
+        // Processes can start or exit between the queries.
+        assert!(count > 0);
+        assert!(count.abs_diff(processes.len()) < 10);
+
+        let mut filters = HashMap::new();
+        filters.insert(
+            "ProcessId".to_owned(),
+            FilterValue::Number(std::process::id() as i64),
         );
 
-        self.raw_query(query)
-    }
-}
+        assert_eq!(wmi_con.count::<Win32_Process>(Some(&filters)).unwrap(), 1);
 
-#[allow(non_snake_case)]
-#[allow(non_camel_case_types)]
-#[allow(dead_code)]
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde::Deserialize;
-    use std::collections::HashMap;
-    use windows::Win32::System::Wmi::WBEM_E_INVALID_QUERY;
+        filters.insert("ProcessId".to_owned(), FilterValue::Number(u32::MAX as i64));
 
-    use crate::tests::fixtures::*;
-    use crate::{Variant, WMIError};
+        assert_eq!(wmi_con.count::<Win32_Process>(Some(&filters)).unwrap(), 0);
+    }
 
     #[test]
-    fn it_works() {
+    fn it_can_check_if_objects_exist() {
         let wmi_con = wmi_con();
 
-        let enumerator = wmi_con
-            .exec_query_native_wrapper("SELECT * FROM Win32_OperatingSystem")
-            .unwrap();
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            ProcessId: u32,
+        }
 
-        for res in enumerator {
-            let w = res.unwrap();
-            let mut props = w.list_properties().unwrap();
+        let mut filters = HashMap::new();
+        filters.insert(
+            "ProcessId".to_owned(),
+            FilterValue::Number(std::process::id() as i64),
+        );
 
-            props.sort();
+        assert!(wmi_con.exists::<Win32_Process>(&filters).unwrap());
 
-            assert_eq!(props.len(), 64);
-            assert_eq!(props[..2], ["BootDevice", "BuildNumber"]);
-            assert_eq!(props[props.len() - 2..], ["Version", "WindowsDirectory"]);
+        filters.insert("ProcessId".to_owned(), FilterValue::Number(u32::MAX as i64));
 
-            let result = serde_json::to_string_pretty(&w);
+        assert!(!wmi_con.exists::<Win32_Process>(&filters).unwrap());
 
-            assert!(result.is_ok());
-            assert!(result.unwrap().len() > 2)
-        }
+        assert!(wmi_con.exists::<Win32_Process>(&HashMap::new()).unwrap());
     }
 
     #[test]
-    fn it_fails_gracefully() {
+    fn it_can_resolve_association_endpoints() {
         let wmi_con = wmi_con();
 
-        let enumerator = wmi_con
-            .exec_query_native_wrapper("SELECT NoSuchField FROM Win32_OperatingSystem")
+        #[derive(Deserialize, Debug)]
+        struct Win32_GroupUser {
+            GroupComponent: String,
+            PartComponent: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_Group {
+            __Path: String,
+            Name: String,
+            SID: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_Account {
+            Caption: String,
+            SID: String,
+        }
+
+        let mut filters = HashMap::new();
+        filters.insert("SID".to_owned(), FilterValue::Str("S-1-5-32-544"));
+        let admin_group: Win32_Group = wmi_con
+            .filtered_query(&filters)
+            .unwrap()
+            .into_iter()
+            .next()
             .unwrap();
 
-        for res in enumerator {
-            assert!(res.is_err())
+        #[derive(Deserialize, Debug)]
+        struct Association {
+            __Path: String,
         }
-    }
 
-    #[test]
-    fn it_fails_gracefully_with_invalid_sql() {
-        let wmi_con = wmi_con();
+        let associations: Vec<Association> = wmi_con
+            .raw_query(format!(
+                "REFERENCES OF {{{}}} WHERE ResultClass = Win32_GroupUser",
+                admin_group.__Path
+            ))
+            .unwrap();
+        assert!(!associations.is_empty());
 
-        let enumerator = wmi_con.exec_query_native_wrapper("42").unwrap();
+        for association in associations {
+            let (group, account) = wmi_con
+                .resolve_association_endpoints::<Win32_GroupUser, Win32_Group, Win32_Account>(
+                    &association.__Path,
+                )
+                .unwrap();
 
-        // Show how to detect which error had occurred.
-        for res in enumerator {
-            match res {
-                Ok(_) => assert!(false),
-                Err(wmi_err) => match wmi_err {
-                    WMIError::HResultError { hres } => {
-                        assert_eq!(hres, WBEM_E_INVALID_QUERY.0);
-                    }
-                    _ => assert!(false),
-                },
-            }
+            assert_eq!(group.SID, admin_group.SID);
+            assert_eq!(group.Name, admin_group.Name);
+            assert!(!account.Caption.is_empty());
+            assert!(account.SID.starts_with("S-1-"));
         }
+
+        let res = wmi_con.resolve_association_endpoints::<Win32_Group, Win32_Group, Win32_Group>(
+            &admin_group.__Path,
+        );
+        assert!(matches!(res, Err(WMIError::SerdeError(_))));
     }
 
     #[test]
-    fn it_can_query_a_struct() {
-        let wmi_con = wmi_con();
+    fn it_calls_the_query_hook() {
+        let mut wmi_con = wmi_con();
+
+        let seen_queries = std::rc::Rc::new(std::cell::RefCell::new(vec![]));
+
+        let hook_seen_queries = seen_queries.clone();
+        wmi_con.set_query_hook(Box::new(move |query| {
+            hook_seen_queries.borrow_mut().push(query.to_string());
+            query.replace("$class", "WIN32_OPERATINGSYSTEM")
+        }));
 
         #[derive(Deserialize, Debug)]
         struct Win32_OperatingSystem {
             Caption: String,
         }
 
-        let results = wmi_con.query::<Win32_OperatingSystem>().unwrap();
+        // The query is only valid after it was rewritten by the hook.
+        let results: Vec<Win32_OperatingSystem> =
+            wmi_con.raw_query("SELECT Caption FROM $class").unwrap();
 
-        for os in results {
-            assert!(os.Caption.starts_with("Microsoft Windows"));
-        }
+        let os: Win32_OperatingSystem = wmi_con.get().unwrap();
+        assert_eq!(results[0].Caption, os.Caption);
+
+        let seen_queries = seen_queries.borrow();
+        assert_eq!(seen_queries.len(), 2);
+        assert_eq!(seen_queries[0], "SELECT Caption FROM $class");
+        assert_eq!(
+            seen_queries[1].trim_end(),
+            "SELECT Caption FROM Win32_OperatingSystem"
+        );
     }
 
     #[test]
@@ -718,6 +2194,26 @@ mod tests {
         assert_eq!(query, select_part);
     }
 
+    #[test]
+    fn it_builds_correct_query_with_embedded_object_properties() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct __InstanceCreationEvent {
+            #[serde(rename = "TargetInstance.Name")]
+            name: String,
+            #[serde(rename = "TargetInstance.ProcessId")]
+            process_id: u32,
+            TIME_CREATED: u64,
+        }
+
+        let query = build_query::<__InstanceCreationEvent>(None).unwrap();
+        let select_part =
+            r#"SELECT TargetInstance.Name,TargetInstance.ProcessId,TIME_CREATED FROM __InstanceCreationEvent "#
+                .to_owned();
+
+        assert_eq!(query, select_part);
+    }
+
     #[test]
     fn it_builds_correct_notification_query_without_filters() {
         #[derive(Deserialize, Debug)]
@@ -759,12 +2255,130 @@ mod tests {
         filters.insert("C9".to_owned(), FilterValue::StringLike("d".to_owned()));
 
         let query = build_query::<Win32_OperatingSystem>(Some(&filters)).unwrap();
+
+        assert_eq!(
+            explain_query::<Win32_OperatingSystem>(Some(&filters)).unwrap(),
+            query
+        );
+
         let select_part = r#"SELECT Caption FROM Win32_OperatingSystem "#.to_owned();
         let where_part = r#"WHERE C1 = "a" AND C2 = "b" AND C3 = 42 AND C4 = false AND C5 = "with \" and \\ chars" AND C6 ISA "Class" AND C7 ISA "Win32_OperatingSystem" AND C8 LIKE "c" AND C9 LIKE "d""#;
 
         assert_eq!(query, select_part + where_part);
     }
 
+    #[test]
+    fn it_builds_correct_ordered_query() {
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            #[allow(dead_code)]
+            Name: String,
+            #[allow(dead_code)]
+            ProcessId: u32,
+        }
+
+        let query = build_query_ordered::<Win32_Process>(None, &[]).unwrap();
+        assert_eq!(query, build_query::<Win32_Process>(None).unwrap());
+
+        let query =
+            build_query_ordered::<Win32_Process>(None, &[("ProcessId", SortOrder::Desc)]).unwrap();
+        assert_eq!(
+            query,
+            "SELECT Name,ProcessId FROM Win32_Process ORDER BY ProcessId DESC"
+        );
+
+        let mut filters = HashMap::new();
+        filters.insert("Name".to_owned(), FilterValue::Str("cargo.exe"));
+
+        let query = build_query_ordered::<Win32_Process>(
+            Some(&filters),
+            &[("Name", SortOrder::Asc), ("ProcessId", SortOrder::Desc)],
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            r#"SELECT Name,ProcessId FROM Win32_Process WHERE Name = "cargo.exe" ORDER BY Name ASC, ProcessId DESC"#
+        );
+    }
+
+    #[test]
+    fn it_fails_to_build_ordered_query_with_unknown_field() {
+        #[derive(Deserialize, Debug)]
+        struct Win32_Process {
+            #[allow(dead_code)]
+            Name: String,
+        }
+
+        let result = build_query_ordered::<Win32_Process>(None, &[("ProcessId", SortOrder::Asc)]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_builds_correct_associators_query() {
+        #[derive(Deserialize)]
+        struct Win32_Account {}
+
+        #[derive(Deserialize)]
+        struct Win32_GroupUser {}
+
+        let object_path = r#"Win32_Group.Domain="BUILTIN",Name="Users""#;
+        let base = r#"ASSOCIATORS OF {Win32_Group.Domain="BUILTIN",Name="Users"} WHERE AssocClass = Win32_GroupUser ResultClass = Win32_Account"#;
+
+        let role = Some("GroupComponent".to_string());
+        let result_role = Some("PartComponent".to_string());
+        let required_qualifier = Some("Association".to_string());
+
+        let cases = [
+            (AssociatorsOptions::default(), ""),
+            (
+                AssociatorsOptions {
+                    role: role.clone(),
+                    ..Default::default()
+                },
+                " Role = GroupComponent",
+            ),
+            (
+                AssociatorsOptions {
+                    result_role: result_role.clone(),
+                    ..Default::default()
+                },
+                " ResultRole = PartComponent",
+            ),
+            (
+                AssociatorsOptions {
+                    required_qualifier: required_qualifier.clone(),
+                    ..Default::default()
+                },
+                " RequiredQualifier = Association",
+            ),
+            (
+                AssociatorsOptions {
+                    role: role.clone(),
+                    result_role: result_role.clone(),
+                    required_qualifier: None,
+                },
+                " Role = GroupComponent ResultRole = PartComponent",
+            ),
+            (
+                AssociatorsOptions {
+                    role,
+                    result_role,
+                    required_qualifier,
+                },
+                " Role = GroupComponent ResultRole = PartComponent RequiredQualifier = Association",
+            ),
+        ];
+
+        for (options, expected_suffix) in cases {
+            let query =
+                build_associators_query::<Win32_Account, Win32_GroupUser>(object_path, &options)
+                    .unwrap();
+
+            assert_eq!(query, format!("{}{}", base, expected_suffix));
+        }
+    }
+
     #[test]
     fn it_builds_correct_notification_query() {
         #[derive(Deserialize, Debug)]
@@ -803,6 +2417,48 @@ mod tests {
         assert_eq!(query, select_part + within_part + where_part);
     }
 
+    #[test]
+    fn it_rejects_an_empty_within_interval() {
+        #[derive(Deserialize, Debug)]
+        struct Win32_ProcessStartTrace {}
+
+        let res = build_notification_query::<Win32_ProcessStartTrace>(None, Some(Duration::ZERO));
+
+        assert!(matches!(
+            res,
+            Err(WMIError::InvalidWithinIntervalError(within)) if within.is_zero()
+        ));
+
+        // Short intervals are still allowed.
+        let query = build_notification_query::<Win32_ProcessStartTrace>(
+            None,
+            Some(Duration::from_millis(500)),
+        )
+        .unwrap();
+
+        assert_eq!(query, "SELECT * FROM Win32_ProcessStartTrace WITHIN 0.5 ");
+    }
+
+    #[test]
+    fn it_builds_correct_notification_query_with_class_filter() {
+        #[derive(Deserialize, Debug)]
+        struct __Event {}
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_VolumeChangeEvent {}
+
+        let mut filters = HashMap::new();
+        filters.extend([FilterValue::class_is_a::<Win32_VolumeChangeEvent>().unwrap()]);
+        filters.insert("EventType".to_owned(), FilterValue::Number(2));
+
+        let query = build_notification_query::<__Event>(Some(&filters), None).unwrap();
+
+        assert_eq!(
+            query,
+            r#"SELECT * FROM __Event WHERE EventType = 2 AND __Class ISA "Win32_VolumeChangeEvent""#
+        );
+    }
+
     #[test]
     fn it_can_filter() {
         let wmi_con = wmi_con();
@@ -878,7 +2534,7 @@ mod tests {
 
             for res in results {
                 match res.get("Antecedent") {
-                    Some(Variant::String(s)) => assert_ne!(s, ""),
+                    Some(Variant::Reference(s)) => assert_ne!(s, ""),
                     _ => assert!(false),
                 }
             }
@@ -889,12 +2545,12 @@ mod tests {
 
         for res in results {
             match res.get("GroupComponent") {
-                Some(Variant::String(s)) => assert_ne!(s, ""),
+                Some(Variant::Reference(s)) => assert_ne!(s, ""),
                 _ => assert!(false),
             }
 
             match res.get("PartComponent") {
-                Some(Variant::String(s)) => assert_ne!(s, ""),
+                Some(Variant::Reference(s)) => assert_ne!(s, ""),
                 _ => assert!(false),
             }
         }
@@ -958,6 +2614,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_can_query_associators_with_options() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_Account {
+            __Path: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_GroupUser {}
+
+        let object_path = r#"Win32_Group.Domain="BUILTIN",Name="Administrators""#;
+
+        let members = wmi_con
+            .associators_with_options::<Win32_Account, Win32_GroupUser>(
+                object_path,
+                &AssociatorsOptions {
+                    role: Some("GroupComponent".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert!(members.len() >= 1);
+
+        // The group is not a member of itself.
+        let members_with_wrong_role = wmi_con
+            .associators_with_options::<Win32_Account, Win32_GroupUser>(
+                object_path,
+                &AssociatorsOptions {
+                    role: Some("PartComponent".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert!(members_with_wrong_role
+            .iter()
+            .all(|account| !account.__Path.contains("Administrators")));
+    }
+
+    #[test]
+    fn it_can_query_references() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_DiskDrive {
+            __Path: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_DiskDriveToDiskPartition {
+            Antecedent: String,
+            Dependent: String,
+        }
+
+        let disk = wmi_con.get::<Win32_DiskDrive>().unwrap();
+
+        let results = wmi_con
+            .references::<Win32_DiskDriveToDiskPartition>(&disk.__Path)
+            .unwrap();
+
+        assert!(results.len() >= 1);
+
+        for assoc in &results {
+            assert!(assoc.Antecedent.contains("Win32_DiskDrive"));
+            assert!(assoc.Dependent.contains("Win32_DiskPartition"));
+        }
+
+        let results_with_role = wmi_con
+            .references_with_role::<Win32_DiskDriveToDiskPartition>(&disk.__Path, "Antecedent")
+            .unwrap();
+
+        assert_eq!(results_with_role.len(), results.len());
+
+        let results_with_other_role = wmi_con
+            .references_with_role::<Win32_DiskDriveToDiskPartition>(&disk.__Path, "Dependent")
+            .unwrap();
+
+        assert!(results_with_other_role.is_empty());
+    }
+
     #[test]
     fn it_can_query_correct_variant_types() {
         let wmi_con = wmi_con();
@@ -1135,6 +2874,46 @@ mod tests {
         assert!(os.Caption.contains("Microsoft Windows"));
     }
 
+    #[test]
+    fn con_get_return_an_object_by_reference() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_DiskDriveToDiskPartition {
+            Antecedent: String,
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct Win32_DiskDrive {
+            __Path: String,
+        }
+
+        let results: Vec<HashMap<String, Variant>> = wmi_con
+            .raw_query("SELECT * FROM Win32_DiskDriveToDiskPartition")
+            .unwrap();
+
+        assert!(!results.is_empty());
+
+        for res in results {
+            let antecedent = &res["Antecedent"];
+            assert!(matches!(antecedent, Variant::Reference(_)));
+
+            let disk: Win32_DiskDrive = wmi_con.get_by_reference(antecedent).unwrap();
+            assert!(disk.__Path.contains("Win32_DiskDrive"));
+        }
+
+        // References can still be deserialized into a `String`.
+        let results: Vec<Win32_DiskDriveToDiskPartition> = wmi_con.query().unwrap();
+
+        for res in results {
+            assert!(res.Antecedent.contains("Win32_DiskDrive"));
+        }
+
+        assert!(wmi_con
+            .get_by_reference::<Win32_DiskDrive>(&Variant::String("Win32_DiskDrive".to_string()))
+            .is_err());
+    }
+
     #[test]
     fn con_get_return_a_raw_object_by_path_from_actual_path() {
         let wmi_con = wmi_con();
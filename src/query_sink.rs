@@ -10,12 +10,14 @@ use windows::core::{implement, Result as WinResult, BSTR, HRESULT};
 use windows::Win32::Foundation::E_POINTER;
 use windows::Win32::System::Wmi::{
     IWbemClassObject, IWbemObjectSink, IWbemObjectSink_Impl, WBEM_STATUS_COMPLETE,
+    WBEM_S_PARTIAL_RESULTS,
 };
 
 #[derive(Default)]
 pub struct AsyncQueryResultStreamImpl {
     buf: VecDeque<WMIResult<IWbemClassWrapper>>,
     is_done: bool,
+    status: HRESULT,
     waker: Option<Waker>,
 }
 
@@ -35,8 +37,9 @@ impl AsyncQueryResultStreamImpl {
         }
     }
 
-    pub fn set_done(&mut self) {
+    pub fn set_done(&mut self, status: HRESULT) {
         self.is_done = true;
+        self.status = status;
 
         if let Some(waker) = self.waker.as_ref() {
             waker.wake_by_ref();
@@ -65,6 +68,28 @@ impl AsyncQueryResultStream {
             sink,
         }
     }
+
+    /// Cancel the async call, and return the results which were already received but not yet read from the stream,
+    /// in the order they were received.
+    pub fn cancel_and_drain(self) -> Vec<WMIResult<IWbemClassWrapper>> {
+        // Results can arrive until the call is cancelled, so the buffer is drained only after cancelling.
+        // The call is cancelled again when `self` is dropped, which has no effect.
+        let _r = unsafe { self.connection.svc.CancelAsyncCall(&self.sink) };
+
+        let mut inner = self.inner.0.lock().unwrap();
+        let remaining = inner.buf.drain(..).collect();
+
+        remaining
+    }
+
+    /// Returns `true` if WMI reported that some of the objects could not be returned
+    /// (the call completed with `WBEM_S_PARTIAL_RESULTS`).
+    ///
+    /// This is only known once the stream is done, and is `false` before that.
+    /// WMI only reports this status to async calls, so there is no equivalent for synchronous queries.
+    pub fn is_partial(&self) -> bool {
+        self.inner.status().0 == WBEM_S_PARTIAL_RESULTS.0
+    }
 }
 
 impl Drop for AsyncQueryResultStream {
@@ -90,9 +115,16 @@ impl AsyncQueryResultStreamInner {
         lock.extend(iter);
     }
 
-    fn set_done(&self) {
+    fn set_done(&self, status: HRESULT) {
         let mut lock = self.0.lock().unwrap();
-        lock.set_done();
+        lock.set_done(status);
+    }
+
+    /// The final status of the async call, as reported to the sink's `SetStatus`.
+    /// Will be `S_OK` if the call is not done yet.
+    pub fn status(&self) -> HRESULT {
+        let lock = self.0.lock().unwrap();
+        lock.status
     }
 }
 
@@ -185,7 +217,7 @@ impl IWbemObjectSink_Impl for QuerySink_Impl {
     fn SetStatus(
         &self,
         lFlags: i32,
-        _hResult: HRESULT,
+        hResult: HRESULT,
         _strParam: &BSTR,
         _pObjParam: Option<&IWbemClassObject>,
     ) -> WinResult<()> {
@@ -196,7 +228,7 @@ impl IWbemObjectSink_Impl for QuerySink_Impl {
 
         if lFlags == WBEM_STATUS_COMPLETE.0 {
             trace!("End of async result, closing transmitter");
-            self.stream.set_done();
+            self.stream.set_done(hResult);
         }
         Ok(())
     }
@@ -262,6 +294,41 @@ mod tests {
         assert_eq!(second.class().unwrap().as_str(), "Win32_OperatingSystem");
     }
 
+    #[async_std::test]
+    async fn async_it_should_drain_results_on_cancel() {
+        let con = wmi_con();
+        let stream = AsyncQueryResultStreamInner::new();
+        let sink = QuerySink {
+            stream: stream.clone(),
+        };
+        let p_sink: IWbemObjectSink = sink.into();
+        let mut stream = AsyncQueryResultStream::new(stream, con.clone(), p_sink.clone());
+
+        let raw_os = con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        unsafe {
+            p_sink
+                .Indicate(&[
+                    Some(raw_os.inner.clone()),
+                    Some(raw_os.inner.clone()),
+                    Some(raw_os.inner.clone()),
+                ])
+                .unwrap();
+        }
+
+        // Read one result, and leave the rest in the buffer.
+        let _first = stream.next().await.unwrap().unwrap();
+
+        let drained = stream.cancel_and_drain();
+
+        assert_eq!(drained.len(), 2);
+        for item in drained {
+            assert_eq!(item.unwrap().class().unwrap(), "Win32_OperatingSystem");
+        }
+    }
+
     #[async_std::test]
     async fn async_it_should_complete_after_set_status_call() {
         let con = wmi_con();
@@ -283,6 +350,33 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[async_std::test]
+    async fn async_it_should_report_partial_results() {
+        let con = wmi_con();
+        let stream = AsyncQueryResultStreamInner::new();
+        let sink = QuerySink {
+            stream: stream.clone(),
+        };
+        let p_sink: IWbemObjectSink = sink.into();
+        let mut stream = AsyncQueryResultStream::new(stream, con.clone(), p_sink.clone());
+
+        assert!(!stream.is_partial());
+
+        unsafe {
+            p_sink
+                .SetStatus(
+                    WBEM_STATUS_COMPLETE.0,
+                    HRESULT(WBEM_S_PARTIAL_RESULTS.0),
+                    &BSTR::new(),
+                    None,
+                )
+                .unwrap();
+        }
+
+        assert!(stream.next().await.is_none());
+        assert!(stream.is_partial());
+    }
+
     #[async_std::test]
     async fn async_it_should_return_e_pointer_after_indicate_call_with_null_pointer() {
         let con = wmi_con();
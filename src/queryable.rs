@@ -0,0 +1,226 @@
+use crate::{FilterValue, WMIConnection, WMIError, WMIResult};
+use serde::{de, Serialize};
+use std::collections::HashMap;
+
+/// The query and method execution surface of a WMI connection.
+///
+/// Code which only needs to query WMI can be generic over this trait instead of using [`WMIConnection`] directly,
+/// which allows unit testing it using the `MockWMIConnection` (available with the `mock` feature).
+///
+/// ```edition2021
+/// # fn main() -> wmi::WMIResult<()> {
+/// # use wmi::*;
+/// # use serde::Deserialize;
+/// #[derive(Deserialize, Debug)]
+/// struct Win32_OperatingSystem {
+///     Caption: String,
+/// }
+///
+/// fn os_caption(con: &impl WMIQueryable) -> WMIResult<String> {
+///     let os: Win32_OperatingSystem = con.get()?;
+///
+///     Ok(os.Caption)
+/// }
+///
+/// let con = WMIConnection::new(COMLibrary::new()?)?;
+/// println!("{}", os_caption(&con)?);
+/// #   Ok(())
+/// # }
+/// ```
+pub trait WMIQueryable {
+    /// Execute the given query and deserialize the results. See [`WMIConnection::raw_query`].
+    fn raw_query<T>(&self, query: &str) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned;
+
+    /// Query all the objects of type `T`, using the given filters. See [`WMIConnection::filtered_query`].
+    fn filtered_query<T>(&self, filters: &HashMap<String, FilterValue>) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned;
+
+    /// Get a single object by its path. See [`WMIConnection::get_by_path`].
+    fn get_by_path<T>(&self, object_path: &str) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned;
+
+    /// Execute a static method of the class `MethodClass`. See [`WMIConnection::exec_class_method`].
+    fn exec_class_method<MethodClass, In, Out>(
+        &self,
+        method: &str,
+        in_params: In,
+    ) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned;
+
+    /// Execute a method of the object at `object_path`. See [`WMIConnection::exec_instance_method`].
+    fn exec_instance_method<MethodClass, In, Out>(
+        &self,
+        method: &str,
+        object_path: &str,
+        in_params: In,
+    ) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned;
+
+    /// Query all the objects of type `T`. See [`WMIConnection::query`].
+    fn query<T>(&self) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        self.filtered_query(&HashMap::new())
+    }
+
+    /// Query the first object of type `T`. See [`WMIConnection::get`].
+    fn get<T>(&self) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        let results = self.query()?;
+
+        results.into_iter().next().ok_or(WMIError::ResultEmpty)
+    }
+}
+
+impl WMIQueryable for WMIConnection {
+    fn raw_query<T>(&self, query: &str) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        WMIConnection::raw_query(self, query)
+    }
+
+    fn filtered_query<T>(&self, filters: &HashMap<String, FilterValue>) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        WMIConnection::filtered_query(self, filters)
+    }
+
+    fn get_by_path<T>(&self, object_path: &str) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        WMIConnection::get_by_path(self, object_path)
+    }
+
+    fn exec_class_method<MethodClass, In, Out>(&self, method: &str, in_params: In) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned,
+    {
+        WMIConnection::exec_class_method::<MethodClass, In, Out>(self, method, in_params)
+    }
+
+    fn exec_instance_method<MethodClass, In, Out>(
+        &self,
+        method: &str,
+        object_path: &str,
+        in_params: In,
+    ) -> WMIResult<Out>
+    where
+        MethodClass: de::DeserializeOwned,
+        In: Serialize,
+        Out: de::DeserializeOwned,
+    {
+        WMIConnection::exec_instance_method::<MethodClass, In, Out>(
+            self,
+            method,
+            object_path,
+            in_params,
+        )
+    }
+
+    fn query<T>(&self) -> WMIResult<Vec<T>>
+    where
+        T: de::DeserializeOwned,
+    {
+        WMIConnection::query(self)
+    }
+
+    fn get<T>(&self) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        WMIConnection::get(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::wmi_con;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_Process {
+        __Path: String,
+        ProcessId: u32,
+        Name: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_OperatingSystem {
+        Caption: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct GetOwnerOutput {
+        ReturnValue: u32,
+        User: Option<String>,
+    }
+
+    struct ProcessInfo {
+        name: String,
+        owner: String,
+        os_caption: String,
+    }
+
+    // Only depends on the trait, so it can also be called with a test double.
+    fn current_process_info(con: &impl WMIQueryable) -> WMIResult<ProcessInfo> {
+        let pid = std::process::id();
+
+        let filters = HashMap::from([("ProcessId".to_owned(), FilterValue::Number(pid as i64))]);
+        let processes: Vec<Win32_Process> = con.filtered_query(&filters)?;
+        let process = processes.into_iter().next().ok_or(WMIError::ResultEmpty)?;
+
+        let same_process: Vec<Win32_Process> = con.raw_query(&format!(
+            "SELECT * FROM Win32_Process WHERE ProcessId = {}",
+            pid
+        ))?;
+        assert_eq!(same_process[0].__Path, process.__Path);
+
+        let same_process: Win32_Process = con.get_by_path(&process.__Path)?;
+        assert_eq!(same_process.ProcessId, pid);
+
+        let owner: GetOwnerOutput =
+            con.exec_instance_method::<Win32_Process, _, _>("GetOwner", &process.__Path, ())?;
+
+        let os: Win32_OperatingSystem = con.get()?;
+        let all_os: Vec<Win32_OperatingSystem> = con.query()?;
+        assert_eq!(all_os.len(), 1);
+
+        assert_eq!(owner.ReturnValue, 0);
+
+        Ok(ProcessInfo {
+            name: process.Name,
+            owner: owner.User.unwrap_or_default(),
+            os_caption: os.Caption,
+        })
+    }
+
+    #[test]
+    fn it_can_be_used_generically_with_a_real_connection() {
+        let wmi_con = wmi_con();
+
+        let info = current_process_info(&wmi_con).unwrap();
+
+        assert!(info.name.ends_with(".exe"));
+        assert!(!info.owner.is_empty());
+        assert!(info.os_caption.contains("Microsoft"));
+    }
+}
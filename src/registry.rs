@@ -0,0 +1,212 @@
+use crate::{result_enumerator::IWbemClassWrapper, WMIConnection, WMIError, WMIResult};
+use serde::de;
+use std::collections::HashMap;
+
+type FromObjectFn<D> = Box<dyn Fn(IWbemClassWrapper) -> WMIResult<Box<D>>>;
+
+/// A registry of WMI classes, used to deserialize objects into a trait object (`Box<D>`) based on their class.
+///
+/// This is an open-ended version of deserializing into an enum with a variant for each class
+/// (see [`WMIConnection::get_by_path`]), which is useful when the set of classes is not known in advance.
+///
+/// Objects are dispatched by their exact class (the `__CLASS` property), so subclasses must be registered separately.
+///
+/// ```edition2021
+/// # fn main() -> wmi::WMIResult<()> {
+/// # use wmi::*;
+/// # use wmi::registry::ClassRegistry;
+/// # use serde::Deserialize;
+/// # let con = WMIConnection::new(COMLibrary::new()?)?;
+/// trait Describe {
+///     fn describe(&self) -> String;
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Win32_OperatingSystem {
+///     Caption: String,
+/// }
+///
+/// impl Describe for Win32_OperatingSystem {
+///     fn describe(&self) -> String {
+///         format!("OS: {}", self.Caption)
+///     }
+/// }
+///
+/// let mut registry = ClassRegistry::<dyn Describe>::new();
+/// registry.register_class("Win32_OperatingSystem", |os: Win32_OperatingSystem| Box::new(os));
+///
+/// let os = con.get_by_path_dyn(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#, &registry)?;
+/// println!("{}", os.describe());
+/// #   Ok(())
+/// # }
+/// ```
+pub struct ClassRegistry<D: ?Sized> {
+    classes: HashMap<String, FromObjectFn<D>>,
+}
+
+impl<D: ?Sized> Default for ClassRegistry<D> {
+    fn default() -> Self {
+        Self {
+            classes: HashMap::new(),
+        }
+    }
+}
+
+impl<D: ?Sized + 'static> ClassRegistry<D> {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a class, which will be deserialized into `T` and then converted into a `Box<D>` using `into_dyn`
+    /// (which is usually just `Box::new`).
+    ///
+    /// Registering a class again replaces the previous registration. Class names are case-insensitive, like in WMI.
+    pub fn register_class<T, F>(&mut self, class_name: &str, into_dyn: F)
+    where
+        T: de::DeserializeOwned + 'static,
+        F: Fn(T) -> Box<D> + 'static,
+    {
+        let from_object: FromObjectFn<D> =
+            Box::new(move |object| object.into_desr::<T>().map(&into_dyn));
+
+        self.classes
+            .insert(class_name.to_ascii_lowercase(), from_object);
+    }
+
+    /// Returns `true` if the class was registered.
+    pub fn is_registered(&self, class_name: &str) -> bool {
+        self.classes.contains_key(&class_name.to_ascii_lowercase())
+    }
+
+    /// Deserialize a WMI object using the registration of its class.
+    ///
+    /// Returns [`WMIError::UnregisteredClassError`] if the object's class was not registered.
+    pub fn from_object(&self, object: IWbemClassWrapper) -> WMIResult<Box<D>> {
+        let class_name = object.class()?;
+
+        let from_object = self
+            .classes
+            .get(&class_name.to_ascii_lowercase())
+            .ok_or(WMIError::UnregisteredClassError(class_name))?;
+
+        from_object(object)
+    }
+}
+
+impl WMIConnection {
+    /// Get a WMI object by path, and deserialize it into a trait object based on its class,
+    /// using the given [`ClassRegistry`].
+    pub fn get_by_path_dyn<D: ?Sized + 'static>(
+        &self,
+        object_path: &str,
+        registry: &ClassRegistry<D>,
+    ) -> WMIResult<Box<D>> {
+        let object = self.get_raw_by_path(object_path)?;
+
+        registry.from_object(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::wmi_con;
+    use serde::Deserialize;
+
+    trait Account {
+        fn kind(&self) -> &'static str;
+        fn caption(&self) -> &str;
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_UserAccount {
+        Caption: String,
+    }
+
+    impl Account for Win32_UserAccount {
+        fn kind(&self) -> &'static str {
+            "user"
+        }
+
+        fn caption(&self) -> &str {
+            &self.Caption
+        }
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_Group {
+        __Path: String,
+        Caption: String,
+    }
+
+    impl Account for Win32_Group {
+        fn kind(&self) -> &'static str {
+            "group"
+        }
+
+        fn caption(&self) -> &str {
+            &self.Caption
+        }
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_Account {
+        __Path: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Win32_GroupUser {}
+
+    #[test]
+    fn it_can_get_by_path_into_trait_objects() {
+        let wmi_con = wmi_con();
+
+        let mut registry = ClassRegistry::<dyn Account>::new();
+        registry.register_class("Win32_UserAccount", |account: Win32_UserAccount| {
+            Box::new(account)
+        });
+        registry.register_class("win32_group", |group: Win32_Group| Box::new(group));
+
+        assert!(registry.is_registered("Win32_Group"));
+        assert!(!registry.is_registered("Win32_SystemAccount"));
+
+        let group: Box<dyn Account> = wmi_con
+            .get_by_path_dyn(
+                r#"Win32_Group.Domain="BUILTIN",Name="Administrators""#,
+                &registry,
+            )
+            .unwrap();
+        assert_eq!(group.kind(), "group");
+        assert!(group.caption().ends_with("Administrators"));
+
+        let admin_group: Win32_Group = wmi_con
+            .get_by_path(r#"Win32_Group.Domain="BUILTIN",Name="Administrators""#)
+            .unwrap();
+
+        let members: Vec<Win32_Account> = wmi_con
+            .associators::<_, Win32_GroupUser>(&admin_group.__Path)
+            .unwrap();
+
+        let mut users = 0;
+
+        for member in members {
+            match wmi_con.get_by_path_dyn(&member.__Path, &registry) {
+                Ok(account) => {
+                    if account.kind() == "user" {
+                        users += 1;
+                    }
+                    assert!(!account.caption().is_empty());
+                }
+                // Other kinds of accounts (like `Win32_SystemAccount`) are not registered.
+                Err(WMIError::UnregisteredClassError(class_name)) => {
+                    assert_ne!(class_name, "Win32_UserAccount")
+                }
+                Err(e) => panic!("Unexpected error {:?}", e),
+            }
+        }
+
+        // The built-in Administrator account is always a member of the group.
+        assert!(users >= 1);
+    }
+}
@@ -1,25 +1,77 @@
 use crate::{
-    connection::WMIConnection, de::wbem_class_de::from_wbem_class_obj,
-    safearray::safe_array_to_vec_of_strings, Variant, WMIError, WMIResult,
+    connection::WMIConnection,
+    de::wbem_class_de::{from_wbem_class_obj, Deserializer},
+    safearray::safe_array_to_vec_of_strings,
+    Variant, WMIError, WMIResult,
 };
 use log::trace;
 use serde::{
     de,
     ser::{Error, SerializeMap},
-    Serialize,
+    Deserialize, Serialize,
 };
-use std::ptr;
-use windows::core::VARIANT;
+use std::{
+    collections::{HashMap, VecDeque},
+    ptr,
+    time::Duration,
+};
+use windows::core::{BSTR, VARIANT};
 use windows::Win32::System::Ole::SafeArrayDestroy;
 use windows::Win32::System::Wmi::{
     IEnumWbemClassObject, IWbemClassObject, CIMTYPE_ENUMERATION, WBEM_FLAG_ALWAYS,
-    WBEM_FLAG_NONSYSTEM_ONLY, WBEM_INFINITE,
+    WBEM_FLAG_NONSYSTEM_ONLY, WBEM_FLAG_SYSTEM_ONLY, WBEM_INFINITE, WBEM_S_FALSE,
 };
 use windows::{
     core::{HSTRING, PCWSTR},
     Win32::System::Wmi::WBEM_CONDITION_FLAG_TYPE,
 };
 
+/// The [system properties](https://learn.microsoft.com/en-us/windows/win32/wmisdk/wmi-system-properties) of a WMI object,
+/// as returned by [`IWbemClassWrapper::system_properties`].
+///
+/// Properties which are `NULL` for some objects (like `__PATH` for an object which was not stored anywhere) are `Option`s.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SystemProperties {
+    /// `1` for a class and `2` for an instance (`__GENUS`).
+    #[serde(rename = "__GENUS")]
+    pub genus: i32,
+    /// The name of the class of the object (`__CLASS`).
+    #[serde(rename = "__CLASS")]
+    pub class: String,
+    /// The name of the immediate parent class (`__SUPERCLASS`).
+    #[serde(rename = "__SUPERCLASS")]
+    pub superclass: Option<String>,
+    /// The name of the top-level class from which the class is derived (`__DYNASTY`).
+    #[serde(rename = "__DYNASTY")]
+    pub dynasty: String,
+    /// The path of the object, relative to its namespace (`__RELPATH`).
+    #[serde(rename = "__RELPATH")]
+    pub relpath: Option<String>,
+    /// The number of non-system properties of the object (`__PROPERTY_COUNT`).
+    #[serde(rename = "__PROPERTY_COUNT")]
+    pub property_count: i32,
+    /// The class hierarchy of the class, from the immediate parent class to the top-level class (`__DERIVATION`).
+    #[serde(rename = "__DERIVATION", deserialize_with = "null_as_default")]
+    pub derivation: Vec<String>,
+    /// The name of the computer on which the object was created (`__SERVER`).
+    #[serde(rename = "__SERVER")]
+    pub server: Option<String>,
+    /// The namespace of the object (`__NAMESPACE`).
+    #[serde(rename = "__NAMESPACE")]
+    pub namespace: Option<String>,
+    /// The full path of the object (`__PATH`).
+    #[serde(rename = "__PATH")]
+    pub path: Option<String>,
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: Default + de::Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 /// A wrapper around a raw pointer to IWbemClassObject, which also takes care of releasing
 /// the object when dropped.
 ///
@@ -37,10 +89,64 @@ impl IWbemClassWrapper {
     /// Return the names of all the properties of the given object.
     ///
     pub fn list_properties(&self) -> WMIResult<Vec<String>> {
+        self.list_names(WBEM_FLAG_NONSYSTEM_ONLY)
+    }
+
+    /// Return the names of all the system properties of the given object, such as `__PATH`, `__CLASS` and `__NAMESPACE`.
+    ///
+    /// See [System Properties](https://learn.microsoft.com/en-us/windows/win32/wmisdk/wmi-system-properties).
+    pub fn list_system_properties(&self) -> WMIResult<Vec<String>> {
+        self.list_names(WBEM_FLAG_SYSTEM_ONLY)
+    }
+
+    /// Get all the system properties of the object (such as `__CLASS`, `__DYNASTY` and `__DERIVATION`).
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let class = wmi_con.get_raw_by_path("Win32_Process")?;
+    /// let system_properties = class.system_properties()?;
+    ///
+    /// assert_eq!(system_properties.dynasty, "CIM_ManagedSystemElement");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn system_properties(&self) -> WMIResult<SystemProperties> {
+        self.clone().into_desr()
+    }
+
+    /// Return the number of non-system properties of the object, using the `__PROPERTY_COUNT` system property.
+    ///
+    /// This is cheaper than `list_properties()?.len()`, since the names of the properties are not read.
+    pub fn property_count(&self) -> WMIResult<u32> {
+        let count: i32 = self.get_property("__PROPERTY_COUNT")?.try_into()?;
+
+        u32::try_from(count).map_err(|_| {
+            WMIError::ConvertVariantError(format!("Invalid __PROPERTY_COUNT {}", count))
+        })
+    }
+
+    /// Return the class hierarchy of the object, from the immediate parent class to the top-level class,
+    /// using the `__DERIVATION` system property.
+    ///
+    /// This is empty for top-level classes (and their instances).
+    pub fn derivation(&self) -> WMIResult<Vec<String>> {
+        match self.get_property("__DERIVATION")? {
+            Variant::Null | Variant::Empty => Ok(vec![]),
+            Variant::Array(classes) => classes.into_iter().map(String::try_from).collect(),
+            other => Err(WMIError::ConvertVariantError(format!(
+                "Invalid __DERIVATION {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn list_names(&self, flags: WBEM_CONDITION_FLAG_TYPE) -> WMIResult<Vec<String>> {
         let p_names = unsafe {
             self.inner.GetNames(
                 None,
-                WBEM_CONDITION_FLAG_TYPE(WBEM_FLAG_ALWAYS.0 | WBEM_FLAG_NONSYSTEM_ONLY.0),
+                WBEM_CONDITION_FLAG_TYPE(WBEM_FLAG_ALWAYS.0 | flags.0),
                 ptr::null_mut(),
             )
         }?;
@@ -52,7 +158,55 @@ impl IWbemClassWrapper {
         res
     }
 
+    /// Return the names of all the methods of the given object (which should be a class definition).
+    ///
+    /// See [IWbemClassObject::BeginMethodEnumeration](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemclassobject-beginmethodenumeration).
+    pub fn list_methods(&self) -> WMIResult<Vec<String>> {
+        let mut methods = vec![];
+
+        unsafe { self.inner.BeginMethodEnumeration(0)? };
+
+        loop {
+            let mut name = BSTR::new();
+
+            let res = unsafe {
+                self.inner
+                    .NextMethod(0, &mut name, ptr::null_mut(), ptr::null_mut())
+            };
+
+            if let Err(e) = res {
+                unsafe { self.inner.EndMethodEnumeration()? };
+                return Err(e.into());
+            }
+
+            // `NextMethod` returns `WBEM_S_NO_MORE_DATA` (which is a success code) when the enumeration is done.
+            if name.is_empty() {
+                break;
+            }
+
+            methods.push(name.to_string());
+        }
+
+        unsafe { self.inner.EndMethodEnumeration()? };
+
+        Ok(methods)
+    }
+
     pub fn get_property(&self, property_name: &str) -> WMIResult<Variant> {
+        self.get_property_with(property_name, Variant::from_variant)
+    }
+
+    /// Like [`IWbemClassWrapper::get_property`], but returns a [`Variant::Unsupported`] placeholder
+    /// if the property's `VARIANT` type is not supported (see [`Variant::from_variant_lenient`]).
+    pub fn get_property_lenient(&self, property_name: &str) -> WMIResult<Variant> {
+        self.get_property_with(property_name, Variant::from_variant_lenient)
+    }
+
+    fn get_property_with(
+        &self,
+        property_name: &str,
+        from_variant: fn(&VARIANT) -> WMIResult<Variant>,
+    ) -> WMIResult<Variant> {
         let name_prop = HSTRING::from(property_name);
 
         let mut vt_prop = VARIANT::default();
@@ -68,13 +222,120 @@ impl IWbemClassWrapper {
                 None,
             )?;
 
-            let property_value = Variant::from_variant(&vt_prop)?
-                .convert_into_cim_type(CIMTYPE_ENUMERATION(cim_type))?;
+            let property_value =
+                from_variant(&vt_prop)?.convert_into_cim_type(CIMTYPE_ENUMERATION(cim_type))?;
 
             Ok(property_value)
         }
     }
 
+    /// Get the value of a qualifier of the object (for a class definition, these are the class qualifiers,
+    /// such as `provider` or `dynamic`).
+    ///
+    /// Returns a `WBEM_E_NOT_FOUND` error if the qualifier does not exist.
+    pub fn get_qualifier(&self, qualifier_name: &str) -> WMIResult<Variant> {
+        let name_prop = HSTRING::from(qualifier_name);
+
+        let mut vt_prop = VARIANT::default();
+
+        unsafe {
+            let qualifier_set = self.inner.GetQualifierSet()?;
+
+            qualifier_set.Get(
+                PCWSTR::from_raw(name_prop.as_ptr()),
+                0,
+                &mut vt_prop,
+                ptr::null_mut(),
+            )?;
+        }
+
+        Variant::from_variant(&vt_prop)
+    }
+
+    /// Get all the qualifiers of the object, mapping the qualifier names to their values.
+    ///
+    /// For a class definition, these are the class qualifiers, such as `dynamic` (a `bool`), `provider` and `UUID` (strings).
+    /// Note that amended qualifiers (like `Description`) are only available if the class was retrieved with the
+    /// `WBEM_FLAG_USE_AMENDED_QUALIFIERS` flag.
+    ///
+    /// See [IWbemQualifierSet::GetNames](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemqualifierset-getnames).
+    pub fn get_qualifiers(&self) -> WMIResult<HashMap<String, Variant>> {
+        let qualifier_set = unsafe { self.inner.GetQualifierSet()? };
+
+        let p_names = unsafe { qualifier_set.GetNames(0)? };
+
+        let names = unsafe { safe_array_to_vec_of_strings(unsafe { &*p_names }) };
+
+        unsafe { SafeArrayDestroy(p_names) }?;
+
+        names?
+            .into_iter()
+            .map(|name| {
+                let name_prop = HSTRING::from(&name);
+                let mut vt_prop = VARIANT::default();
+
+                unsafe {
+                    qualifier_set.Get(
+                        PCWSTR::from_raw(name_prop.as_ptr()),
+                        0,
+                        &mut vt_prop,
+                        ptr::null_mut(),
+                    )?;
+                }
+
+                Ok((name, Variant::from_variant(&vt_prop)?))
+            })
+            .collect()
+    }
+
+    /// Set the value of a property of the object.
+    ///
+    /// Setting a property to [`Variant::Null`] clears its value.
+    ///
+    /// See [IWbemClassObject::Put](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemclassobject-put).
+    pub fn put_property(&self, property_name: &str, value: impl Into<Variant>) -> WMIResult<()> {
+        let name_prop = HSTRING::from(property_name);
+
+        let value: Option<VARIANT> = match value.into() {
+            // A null pointer sets the property to NULL.
+            Variant::Null => None,
+            value => Some(value.try_into()?),
+        };
+
+        let p_value = value
+            .as_ref()
+            .map_or(ptr::null(), |value| value as *const _);
+
+        unsafe {
+            self.inner
+                .Put(PCWSTR::from_raw(name_prop.as_ptr()), 0, p_value, 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a new instance of the class, where `self` is a class definition
+    /// (which can be obtained using [`WMIConnection::get_raw_by_path`] with the class name).
+    ///
+    /// See [IWbemClassObject::SpawnInstance](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemclassobject-spawninstance).
+    pub fn spawn_instance(&self) -> WMIResult<IWbemClassWrapper> {
+        let inst = unsafe { self.inner.SpawnInstance(0)? };
+
+        Ok(IWbemClassWrapper::new(inst))
+    }
+
+    /// Create an independent copy of the object, so that changing the properties of one does not affect the other
+    /// (for example, to use the same in-parameters object as a template for several method calls).
+    ///
+    /// Note that [`Clone::clone`] only clones the interface pointer, and the clones refer to the same object.
+    ///
+    /// See [IWbemClassObject::Clone](https://learn.microsoft.com/en-us/windows/win32/api/wbemcli/nf-wbemcli-iwbemclassobject-clone).
+    pub fn clone_object(&self) -> WMIResult<IWbemClassWrapper> {
+        let inst = unsafe { self.inner.Clone()? };
+
+        Ok(IWbemClassWrapper::new(inst))
+    }
+
     pub fn path(&self) -> WMIResult<String> {
         self.get_property("__Path").and_then(Variant::try_into)
     }
@@ -83,12 +344,80 @@ impl IWbemClassWrapper {
         self.get_property("__Class").and_then(Variant::try_into)
     }
 
+    /// Deserialize the object into `T`, which can be a struct (whose fields are read as properties of the object),
+    /// or a map like `HashMap<String, Variant>`.
+    ///
+    /// Maps only contain the object's non-system properties, so keys like `__PATH` and `__CLASS` are not included
+    /// (use [`IWbemClassWrapper::into_desr_with_system_properties`] to include them).
+    /// A struct can still read system properties using fields with the same name (like `__Path`).
     pub fn into_desr<T>(self) -> WMIResult<T>
     where
         T: de::DeserializeOwned,
     {
         from_wbem_class_obj(self).map_err(WMIError::from)
     }
+
+    /// Like [`IWbemClassWrapper::into_desr`], but properties with an unsupported `VARIANT` type are read as
+    /// [`Variant::Unsupported`] placeholders instead of failing (see [`Deserializer::lenient`]).
+    ///
+    /// The placeholders are deserialized as `None` into `Option` fields.
+    pub fn into_desr_lenient<T>(self) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        let mut deserializer = Deserializer::from_wbem_class_obj(self).lenient();
+        T::deserialize(&mut deserializer)
+    }
+
+    /// Like [`IWbemClassWrapper::into_desr`], but when deserializing into a map,
+    /// the object's system properties (such as `__PATH`, `__CLASS` and `__NAMESPACE`) are included as well.
+    ///
+    /// The system properties are keyed by their names as reported by WMI (see [`IWbemClassWrapper::list_system_properties`]).
+    pub fn into_desr_with_system_properties<T>(self) -> WMIResult<T>
+    where
+        T: de::DeserializeOwned,
+    {
+        let mut deserializer = Deserializer::from_wbem_class_obj(self).with_system_properties();
+        T::deserialize(&mut deserializer)
+    }
+
+    /// Read all the (non-system) properties of the object into a map, keyed by the property names.
+    ///
+    /// This is the same as deserializing the object into a `HashMap<String, Variant>` using [`IWbemClassWrapper::into_desr`].
+    pub fn to_map(&self) -> WMIResult<HashMap<String, Variant>> {
+        self.clone().into_desr()
+    }
+
+    /// Like [`IWbemClassWrapper::to_map`], but the object's system properties (such as `__PATH` and `__CLASS`) are included as well.
+    pub fn to_map_with_system(&self) -> WMIResult<HashMap<String, Variant>> {
+        self.clone().into_desr_with_system_properties()
+    }
+
+    /// Convert the object to a JSON object, with a key for every (non-system) property.
+    ///
+    /// Numbers and booleans are converted to JSON numbers and booleans, arrays to JSON arrays,
+    /// `NULL` values to `null`, and embedded objects to JSON objects (which also include their `__CLASS`).
+    /// Strings, datetimes and references are converted to JSON strings.
+    ///
+    /// Requires the `serde_json` feature.
+    ///
+    /// ```edition2021
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// let con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// let os = con.get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)?.into_json()?;
+    ///
+    /// println!("{}", os["Caption"]);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serde_json")]
+    pub fn into_json(self) -> WMIResult<serde_json::Value> {
+        let properties: serde_json::Map<String, serde_json::Value> = self.into_desr()?;
+
+        Ok(serde_json::Value::Object(properties))
+    }
 }
 
 impl Serialize for IWbemClassWrapper {
@@ -106,9 +435,16 @@ impl Serialize for IWbemClassWrapper {
     }
 }
 
+/// An iterator over the results of a query (or a notification query).
+///
+/// Objects are fetched from WMI in batches (see [`QueryResultEnumerator::with_batch_size`]),
+/// and are returned one at a time.
 pub struct QueryResultEnumerator<'a> {
     _wmi_con: &'a WMIConnection,
     p_enumerator: IEnumWbemClassObject,
+    batch_size: usize,
+    buf: VecDeque<Option<IWbemClassObject>>,
+    is_done: bool,
 }
 
 impl<'a> QueryResultEnumerator<'a> {
@@ -116,42 +452,96 @@ impl<'a> QueryResultEnumerator<'a> {
         Self {
             _wmi_con: wmi_con,
             p_enumerator,
+            batch_size: 1,
+            buf: VecDeque::new(),
+            is_done: false,
         }
     }
-}
 
-impl<'a> Iterator for QueryResultEnumerator<'a> {
-    type Item = WMIResult<IWbemClassWrapper>;
+    /// Set the number of objects requested from WMI on each call to `Next`.
+    ///
+    /// Larger batches reduce the number of calls made to WMI, but `Next` will wait until a full batch is available
+    /// (or the enumeration is complete), so this should be kept at `1` for notification queries.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut objs = [None; 1];
+    /// Returns `true` if the enumeration is complete and all the objects were returned.
+    pub fn is_done(&self) -> bool {
+        self.is_done && self.buf.is_empty()
+    }
+
+    /// Like [`Iterator::next`], but waits at most `timeout` for the next object.
+    ///
+    /// Returns `None` both when the timeout expired and when the enumeration is complete,
+    /// which can be distinguished using [`QueryResultEnumerator::is_done`].
+    pub fn next_timeout(&mut self, timeout: Duration) -> Option<WMIResult<IWbemClassWrapper>> {
+        let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+
+        self.next_with_timeout(timeout)
+    }
+
+    fn next_with_timeout(&mut self, timeout: i32) -> Option<WMIResult<IWbemClassWrapper>> {
+        if self.buf.is_empty() && !self.is_done {
+            if let Err(e) = self.fetch_batch(timeout) {
+                return Some(Err(e));
+            }
+        }
+
+        let obj = self.buf.pop_front()?;
+        let pcls_ptr = obj.ok_or(WMIError::NullPointerResult);
+
+        match pcls_ptr {
+            Err(e) => Some(Err(e)),
+            Ok(pcls_ptr) => Some(Ok(IWbemClassWrapper::new(pcls_ptr))),
+        }
+    }
+
+    fn fetch_batch(&mut self, timeout: i32) -> WMIResult<()> {
+        let mut objs = vec![None; self.batch_size];
         let mut return_value = 0;
 
         let res = unsafe {
             self.p_enumerator
-                .Next(WBEM_INFINITE, &mut objs, &mut return_value)
+                .Next(timeout, &mut objs, &mut return_value)
         };
 
         if let Err(e) = res.ok() {
-            return Some(Err(e.into()));
+            let e = WMIError::from(e);
+
+            // The enumeration cannot continue after WMI runs out of resources,
+            // so end it instead of calling `Next` again (which would return the same error).
+            if e.is_resource_exhausted() {
+                self.is_done = true;
+            }
+
+            return Err(e);
         }
 
-        if return_value == 0 {
-            return None;
+        // `WBEM_S_FALSE` is returned when fewer objects than requested were returned because the enumeration is complete.
+        // When the timeout expired first, `WBEM_S_TIMEDOUT` is returned instead, and `Next` can be called again.
+        if res.0 == WBEM_S_FALSE.0 {
+            self.is_done = true;
         }
 
         trace!(
-            "Got enumerator {:?} and obj {:?}",
+            "Got enumerator {:?} and {} objects",
             self.p_enumerator,
-            &objs[0]
+            return_value
         );
 
-        let [obj] = objs;
-        let pcls_ptr = obj.ok_or(WMIError::NullPointerResult);
+        objs.truncate(return_value as usize);
+        self.buf.extend(objs);
 
-        match pcls_ptr {
-            Err(e) => Some(Err(e)),
-            Ok(pcls_ptr) => Some(Ok(IWbemClassWrapper::new(pcls_ptr))),
-        }
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for QueryResultEnumerator<'a> {
+    type Item = WMIResult<IWbemClassWrapper>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_timeout(WBEM_INFINITE)
     }
 }
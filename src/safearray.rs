@@ -1,17 +1,22 @@
 use crate::{
     utils::{WMIError, WMIResult},
+    variant::StringDecoding,
     Variant,
 };
 use std::{iter::Iterator, ptr::null_mut};
 use windows::core::BSTR;
 use windows::Win32::System::Com::SAFEARRAY;
-use windows::Win32::System::Ole::{SafeArrayAccessData, SafeArrayUnaccessData};
+use windows::Win32::System::Ole::{
+    SafeArrayAccessData, SafeArrayGetDim, SafeArrayGetLBound, SafeArrayGetUBound,
+    SafeArrayUnaccessData,
+};
 use windows::Win32::System::Variant::*;
 
 #[derive(Debug)]
 pub struct SafeArrayAccessor<'a, T> {
     arr: &'a SAFEARRAY,
     p_data: *mut T,
+    element_count: usize,
 }
 
 /// An accessor to SafeArray, which:
@@ -37,29 +42,77 @@ impl<'a, T> SafeArrayAccessor<'a, T> {
     pub unsafe fn new(arr: &'a SAFEARRAY) -> WMIResult<Self> {
         let mut p_data = null_mut();
 
-        if arr.cDims != 1 {
-            return Err(WMIError::UnimplementedArrayItem);
-        }
+        let dims = dimensions(arr)?;
+        let element_count = if dims.is_empty() {
+            0
+        } else {
+            dims.iter().product()
+        };
 
         unsafe { SafeArrayAccessData(arr, &mut p_data)? };
 
         Ok(Self {
             arr,
             p_data: p_data as *mut T,
+            element_count,
         })
     }
 
+    /// The number of items in the array (in all of its dimensions),
+    /// calculated from the bounds of the array (see `SafeArrayGetLBound` and `SafeArrayGetUBound`).
+    pub fn len(&self) -> usize {
+        self.element_count
+    }
+
+    /// Returns `true` if the array has no items.
+    pub fn is_empty(&self) -> bool {
+        self.element_count == 0
+    }
+
     /// Return an iterator over the items of the array.
+    ///
+    /// For a multi-dimensional array, the items are returned in memory order,
+    /// where the first (leftmost) index changes the fastest.
     pub fn iter(&self) -> impl Iterator<Item = &'_ T> + '_ {
-        // Safety: We required the caller of `new` to ensure that the array is valid and contains only items of type T (and one dimensional).
-        // `SafeArrayAccessData` returns a pointer to the data of the array, which can be accessed for `arr.rgsabound[0].cElements` elements.
+        // Safety: We required the caller of `new` to ensure that the array is valid and contains only items of type T.
+        // `SafeArrayAccessData` returns a pointer to the data of the array, which can be accessed for the
+        // total number of elements in all the dimensions.
         // See: https://learn.microsoft.com/en-us/windows/win32/api/oleauto/nf-oleauto-safearrayaccessdata#examples
-        let element_count = self.arr.rgsabound[0].cElements;
-
-        (0..element_count).map(move |i| unsafe { &*self.p_data.offset(i as isize) })
+        (0..self.element_count).map(move |i| unsafe { &*self.p_data.add(i) })
     }
 }
 
+/// Returns the number of elements in each dimension of the array, from the leftmost dimension.
+fn dimensions(arr: &SAFEARRAY) -> WMIResult<Vec<usize>> {
+    let dim_count = unsafe { SafeArrayGetDim(arr) };
+
+    (1..=dim_count)
+        .map(|dim| {
+            let lower = unsafe { SafeArrayGetLBound(arr, dim)? };
+            let upper = unsafe { SafeArrayGetUBound(arr, dim)? };
+
+            // An empty dimension has an upper bound which is smaller than its lower bound.
+            Ok((upper - lower + 1).max(0) as usize)
+        })
+        .collect()
+}
+
+/// Convert the items of a multi-dimensional array (in memory order) to nested arrays,
+/// where the outermost array is indexed by the leftmost dimension.
+fn nest_items(items: &[Variant], dims: &[usize], strides: &[usize], offset: usize) -> Vec<Variant> {
+    (0..dims[0])
+        .map(|i| {
+            let offset = offset + i * strides[0];
+
+            if dims.len() == 1 {
+                items[offset].clone()
+            } else {
+                Variant::Array(nest_items(items, &dims[1..], &strides[1..], offset))
+            }
+        })
+        .collect()
+}
+
 impl<'a, T> Drop for SafeArrayAccessor<'a, T> {
     fn drop(&mut self) {
         unsafe {
@@ -89,6 +142,22 @@ pub unsafe fn safe_array_to_vec_of_strings(arr: &SAFEARRAY) -> WMIResult<Vec<Str
 ///
 /// The caller must ensure that the array is valid and contains elements on the specified type.
 pub unsafe fn safe_array_to_vec(arr: &SAFEARRAY, item_type: VARENUM) -> WMIResult<Vec<Variant>> {
+    safe_array_to_vec_with_decoding(arr, item_type, StringDecoding::Lossy)
+}
+
+/// Like [`safe_array_to_vec`], but using the given [`StringDecoding`] for arrays of strings.
+///
+/// Multi-dimensional arrays are converted to nested [`Variant::Array`]s, where the outermost array is indexed by the leftmost dimension
+/// (so a `2x3` array is converted to 2 arrays of 3 items each).
+///
+/// # Safety
+///
+/// The caller must ensure that the array is valid and contains elements on the specified type.
+pub unsafe fn safe_array_to_vec_with_decoding(
+    arr: &SAFEARRAY,
+    item_type: VARENUM,
+    decoding: StringDecoding,
+) -> WMIResult<Vec<Variant>> {
     fn copy_type_to_vec<T, F>(arr: &SAFEARRAY, variant_builder: F) -> WMIResult<Vec<Variant>>
     where
         T: Copy,
@@ -99,7 +168,7 @@ pub unsafe fn safe_array_to_vec(arr: &SAFEARRAY, item_type: VARENUM) -> WMIResul
         Ok(accessor.iter().map(|item| variant_builder(*item)).collect())
     }
 
-    match item_type {
+    let items = match item_type {
         VT_I1 => copy_type_to_vec(arr, Variant::I1),
         VT_I2 => copy_type_to_vec(arr, Variant::I2),
         VT_I4 => copy_type_to_vec(arr, Variant::I4),
@@ -115,10 +184,139 @@ pub unsafe fn safe_array_to_vec(arr: &SAFEARRAY, item_type: VARENUM) -> WMIResul
 
             accessor
                 .iter()
-                .map(|item| item.try_into().map(Variant::String).map_err(WMIError::from))
+                .map(|item| decoding.decode(item.as_wide()).map(Variant::String))
                 .collect()
         }
         // TODO: Add support for all other types of arrays.
         _ => Err(WMIError::UnimplementedArrayItem),
+    }?;
+
+    let dims = dimensions(arr)?;
+
+    if dims.len() <= 1 {
+        return Ok(items);
+    }
+
+    // The items are in memory order, where the leftmost index changes the fastest.
+    let strides: Vec<usize> = dims
+        .iter()
+        .scan(1, |stride, dim| {
+            let current = *stride;
+            *stride *= dim;
+            Some(current)
+        })
+        .collect();
+
+    Ok(nest_items(&items, &dims, &strides, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use windows::Win32::System::Com::SAFEARRAYBOUND;
+    use windows::Win32::System::Ole::{
+        SafeArrayCreate, SafeArrayCreateVector, SafeArrayDestroy, SafeArrayPutElement,
+    };
+
+    #[test]
+    fn it_returns_the_len_of_arrays() {
+        unsafe {
+            for (element_count, lower_bound) in [(0, 0), (0, 5), (3, 0), (3, -2)] {
+                let bound = SAFEARRAYBOUND {
+                    cElements: element_count,
+                    lLbound: lower_bound,
+                };
+
+                let arr = SafeArrayCreate(VT_I4, 1, &bound);
+                assert!(!arr.is_null());
+
+                let accessor = SafeArrayAccessor::<i32>::new(&*arr).unwrap();
+
+                assert_eq!(accessor.len(), element_count as usize);
+                assert_eq!(accessor.is_empty(), element_count == 0);
+                assert_eq!(accessor.iter().count(), element_count as usize);
+
+                drop(accessor);
+                SafeArrayDestroy(arr).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn it_converts_64_bit_integer_arrays() {
+        let i8_values = [i64::MIN, -1, i64::from(i32::MAX) + 1, i64::MAX];
+        let ui8_values = [0, u64::from(u32::MAX) + 1, u64::MAX];
+
+        unsafe {
+            let arr = SafeArrayCreateVector(VT_I8, 0, i8_values.len() as u32);
+            assert!(!arr.is_null());
+
+            for (i, value) in i8_values.iter().enumerate() {
+                let index = i as i32;
+                SafeArrayPutElement(arr, &index, value as *const i64 as *const _).unwrap();
+            }
+
+            let items = safe_array_to_vec(&*arr, VT_I8).unwrap();
+            SafeArrayDestroy(arr).unwrap();
+
+            let values = Vec::<i64>::deserialize(Variant::Array(items)).unwrap();
+            assert_eq!(values, i8_values);
+
+            let arr = SafeArrayCreateVector(VT_UI8, 0, ui8_values.len() as u32);
+            assert!(!arr.is_null());
+
+            for (i, value) in ui8_values.iter().enumerate() {
+                let index = i as i32;
+                SafeArrayPutElement(arr, &index, value as *const u64 as *const _).unwrap();
+            }
+
+            let items = safe_array_to_vec(&*arr, VT_UI8).unwrap();
+            SafeArrayDestroy(arr).unwrap();
+
+            let values = Vec::<u64>::deserialize(Variant::Array(items)).unwrap();
+            assert_eq!(values, ui8_values);
+        }
+    }
+
+    #[test]
+    fn it_converts_multi_dimensional_arrays_to_nested_arrays() {
+        let bounds = [
+            SAFEARRAYBOUND {
+                cElements: 2,
+                lLbound: 0,
+            },
+            SAFEARRAYBOUND {
+                cElements: 3,
+                lLbound: 0,
+            },
+        ];
+
+        unsafe {
+            let arr = SafeArrayCreate(VT_I4, 2, bounds.as_ptr());
+            assert!(!arr.is_null());
+
+            for row in 0..2 {
+                for col in 0..3 {
+                    let indices = [row, col];
+                    let value = row * 10 + col;
+
+                    SafeArrayPutElement(arr, indices.as_ptr(), &value as *const i32 as *const _)
+                        .unwrap();
+                }
+            }
+
+            let items = safe_array_to_vec(&*arr, VT_I4).unwrap();
+
+            SafeArrayDestroy(arr).unwrap();
+
+            assert_eq!(
+                items,
+                vec![
+                    Variant::Array(vec![Variant::I4(0), Variant::I4(1), Variant::I4(2)]),
+                    Variant::Array(vec![Variant::I4(10), Variant::I4(11), Variant::I4(12)]),
+                ]
+            );
+        }
     }
 }
@@ -2,9 +2,9 @@
 //! to serialize a Rust struct into a HashMap mapping field name strings to [`Variant`] values
 use std::{any::type_name, collections::HashMap, fmt::Display};
 
-use crate::Variant;
+use crate::{result_enumerator::IWbemClassWrapper, Variant, WMIConnection, WMIError};
 use serde::{
-    ser::{Impossible, SerializeStruct},
+    ser::{Impossible, SerializeSeq, SerializeStruct},
     Serialize, Serializer,
 };
 use thiserror::Error;
@@ -35,18 +35,31 @@ macro_rules! serialize_variant {
     };
 }
 
-struct VariantSerializer {}
+/// Serializes a value into a [`Variant`].
+///
+/// Sequences are serialized into a [`Variant::Array`].
+/// When created with a connection, structs are serialized into a new instance of the WMI class with the struct's name,
+/// which is returned as a [`Variant::Object`].
+pub(crate) struct VariantSerializer<'a> {
+    wmi: Option<&'a WMIConnection>,
+}
 
-impl Serializer for VariantSerializer {
+impl<'a> VariantSerializer<'a> {
+    pub(crate) fn with_connection(wmi: &'a WMIConnection) -> Self {
+        Self { wmi: Some(wmi) }
+    }
+}
+
+impl<'a> Serializer for VariantSerializer<'a> {
     type Ok = Variant;
     type Error = VariantSerializerError;
 
-    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = VariantSeqSerializer<'a>;
     type SerializeTuple = Impossible<Self::Ok, Self::Error>;
     type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
     type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
     type SerializeMap = Impossible<Self::Ok, Self::Error>;
-    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = VariantInstanceSerializer<'a>;
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     serialize_variant!(serialize_bool, bool);
@@ -112,24 +125,21 @@ impl Serializer for VariantSerializer {
     serialize_variant_err_stub!(serialize_bytes, &[u8]);
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Err(VariantSerializerError::UnsupportedVariantType(
-            "None".to_string(),
-        ))
+        Ok(Variant::Null)
     }
 
-    fn serialize_some<T>(self, _value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + Serialize,
     {
-        Err(VariantSerializerError::UnsupportedVariantType(
-            type_name::<T>().to_string(),
-        ))
+        value.serialize(self)
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        Err(VariantSerializerError::UnsupportedVariantType(
-            "Sequence".to_string(),
-        ))
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(VariantSeqSerializer {
+            wmi: self.wmi,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
@@ -171,9 +181,19 @@ impl Serializer for VariantSerializer {
         name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Err(VariantSerializerError::UnsupportedVariantType(
-            name.to_string(),
-        ))
+        match self.wmi {
+            Some(wmi) => {
+                let class = wmi.get_raw_by_path(name)?;
+
+                Ok(VariantInstanceSerializer {
+                    wmi,
+                    instance: class.spawn_instance()?,
+                })
+            }
+            None => Err(VariantSerializerError::UnsupportedVariantType(
+                name.to_string(),
+            )),
+        }
     }
 
     fn serialize_struct_variant(
@@ -189,9 +209,63 @@ impl Serializer for VariantSerializer {
     }
 }
 
+/// Serializes the elements of a sequence into a [`Variant::Array`].
+pub(crate) struct VariantSeqSerializer<'a> {
+    wmi: Option<&'a WMIConnection>,
+    items: Vec<Variant>,
+}
+
+impl<'a> SerializeSeq for VariantSeqSerializer<'a> {
+    type Ok = Variant;
+    type Error = VariantSerializerError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let value = value.serialize(VariantSerializer { wmi: self.wmi })?;
+        self.items.push(value);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Variant::Array(self.items))
+    }
+}
+
+/// Serializes the fields of a struct into the properties of a new WMI instance.
+pub(crate) struct VariantInstanceSerializer<'a> {
+    wmi: &'a WMIConnection,
+    instance: IWbemClassWrapper,
+}
+
+impl<'a> SerializeStruct for VariantInstanceSerializer<'a> {
+    type Ok = Variant;
+    type Error = VariantSerializerError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let value = value.serialize(VariantSerializer::with_connection(self.wmi))?;
+        self.instance.put_property(key, value)?;
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Variant::Object(self.instance))
+    }
+}
+
 /// Serializes a struct to a HashMap of key-value pairs, with the key being the field name, and the value being the field value wrapped in a [`Variant`].
 ///
-/// VariantStructSerializer only supports serializing fields with basic Rust data types: `i32`, `()`, etc., as well as any of the former in a newtype.
+/// VariantStructSerializer supports serializing fields with basic Rust data types: `i32`, `()`, etc., as well as any of the former in a newtype,
+/// sequences (like `Vec<String>`) of these types, and `Option`s (where `None` is serialized as [`Variant::Null`]).
+///
+/// When created using [`VariantStructSerializer::with_connection`], fields which are structs are also supported,
+/// and are serialized as new instances of the WMI class with the same name as the struct.
 ///
 /// ```edition2021
 /// use serde::Serialize;
@@ -216,6 +290,7 @@ impl Serializer for VariantSerializer {
 /// ```
 #[derive(Default)]
 pub struct VariantStructSerializer {
+    wmi: Option<WMIConnection>,
     variant_map: HashMap<String, Variant>,
 }
 
@@ -227,11 +302,24 @@ pub enum VariantSerializerError {
     ExpectedStruct,
     #[error("{0} cannot be serialized to a Variant.")]
     UnsupportedVariantType(String),
+    #[error(transparent)]
+    WMIError(#[from] WMIError),
 }
 
 impl VariantStructSerializer {
     pub fn new() -> Self {
         Self {
+            wmi: None,
+            variant_map: HashMap::new(),
+        }
+    }
+
+    /// Create a serializer which can also serialize nested structs, using the connection to create new WMI instances.
+    ///
+    /// The serializer keeps a clone of the connection (which shares its underlying COM objects).
+    pub fn with_connection(wmi: &WMIConnection) -> Self {
+        Self {
+            wmi: Some(wmi.clone()),
             variant_map: HashMap::new(),
         }
     }
@@ -387,12 +475,15 @@ impl SerializeStruct for VariantStructSerializer {
     where
         T: ?Sized + Serialize,
     {
-        let variant = value.serialize(VariantSerializer {});
+        let variant = value.serialize(VariantSerializer {
+            wmi: self.wmi.as_ref(),
+        });
         match variant {
             Ok(value) => {
                 self.variant_map.insert(key.to_string(), value);
                 Ok(())
             }
+            Err(VariantSerializerError::WMIError(e)) => Err(e.into()),
             Err(_) => Err(VariantSerializerError::UnsupportedVariantType(
                 type_name::<T>().to_string(),
             )),
@@ -544,4 +635,111 @@ mod tests {
 
         assert_eq!(field_map, expected_field_map);
     }
+
+    #[derive(Serialize)]
+    struct SeqTest {
+        strings: Vec<String>,
+        numbers: Vec<u32>,
+    }
+
+    #[test]
+    fn it_serialize_seq() {
+        let test_struct = SeqTest {
+            strings: vec!["a".to_string(), "b".to_string()],
+            numbers: vec![],
+        };
+
+        let expected_field_map: HashMap<String, Variant> = [
+            (
+                "strings".to_string(),
+                Variant::Array(vec![
+                    Variant::from("a".to_string()),
+                    Variant::from("b".to_string()),
+                ]),
+            ),
+            ("numbers".to_string(), Variant::Array(vec![])),
+        ]
+        .into_iter()
+        .collect();
+
+        let field_map = test_struct
+            .serialize(VariantStructSerializer::new())
+            .unwrap();
+
+        assert_eq!(field_map, expected_field_map);
+    }
+
+    #[derive(Serialize)]
+    #[allow(non_camel_case_types, non_snake_case)]
+    struct __NAMESPACE {
+        Name: String,
+    }
+
+    #[derive(Serialize)]
+    struct NestedTest {
+        namespace: __NAMESPACE,
+    }
+
+    #[test]
+    fn it_serialize_nested_struct_with_connection() {
+        let wmi_con = crate::tests::fixtures::wmi_con();
+
+        let test_struct = NestedTest {
+            namespace: __NAMESPACE {
+                Name: "Test".to_string(),
+            },
+        };
+
+        assert!(test_struct
+            .serialize(VariantStructSerializer::new())
+            .is_err());
+
+        let mut field_map = test_struct
+            .serialize(VariantStructSerializer::with_connection(&wmi_con))
+            .unwrap();
+
+        match field_map.remove("namespace").unwrap() {
+            Variant::Object(instance) => assert_eq!(
+                instance.get_property("Name").unwrap(),
+                Variant::from("Test".to_string())
+            ),
+            other => panic!("Unexpected variant: {:?}", other),
+        }
+    }
+
+    #[derive(Serialize)]
+    #[allow(non_camel_case_types, non_snake_case)]
+    struct Win32_ProcessStartup {
+        ShowWindow: Option<u16>,
+    }
+
+    #[derive(Serialize)]
+    #[allow(non_snake_case)]
+    struct OptionTest {
+        ProcessStartupInformation: Win32_ProcessStartup,
+    }
+
+    #[test]
+    fn it_serialize_option() {
+        let wmi_con = crate::tests::fixtures::wmi_con();
+
+        for (show_window, expected) in [(None, Variant::Null), (Some(1), Variant::UI2(1))] {
+            let test_struct = OptionTest {
+                ProcessStartupInformation: Win32_ProcessStartup {
+                    ShowWindow: show_window,
+                },
+            };
+
+            let mut field_map = test_struct
+                .serialize(VariantStructSerializer::with_connection(&wmi_con))
+                .unwrap();
+
+            match field_map.remove("ProcessStartupInformation").unwrap() {
+                Variant::Object(instance) => {
+                    assert_eq!(instance.get_property("ShowWindow").unwrap(), expected)
+                }
+                other => panic!("Unexpected variant: {:?}", other),
+            }
+        }
+    }
 }
@@ -0,0 +1,192 @@
+//! Helpers for controlling Windows services using the [`Win32_Service`] class.
+//!
+//! The `StartService` and `StopService` methods only request a state change,
+//! which may complete after the method returns. Use [`WMIConnection::service_state`] to check the current state.
+//!
+//! A non-zero return value of these methods is returned as [`WMIError::MethodReturnValueError`].
+//! For example, `2` is "Access Denied", `5` is "Service Cannot Accept Control" and `10` is "Service Already Running".
+//! See [`StartService`] for the full list.
+//!
+//! [`Win32_Service`]: https://learn.microsoft.com/en-us/windows/win32/cimwin32prov/win32-service
+//! [`StartService`]: https://learn.microsoft.com/en-us/windows/win32/cimwin32prov/startservice-method-in-class-win32-service
+
+use crate::{FilterValue, WMIConnection, WMIError, WMIResult};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The state of a service, as reported by the `State` property of `Win32_Service`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServiceState {
+    Stopped,
+    StartPending,
+    StopPending,
+    Running,
+    ContinuePending,
+    PausePending,
+    Paused,
+    Unknown(String),
+}
+
+impl From<String> for ServiceState {
+    fn from(state: String) -> Self {
+        match state.as_str() {
+            "Stopped" => Self::Stopped,
+            "Start Pending" => Self::StartPending,
+            "Stop Pending" => Self::StopPending,
+            "Running" => Self::Running,
+            "Continue Pending" => Self::ContinuePending,
+            "Pause Pending" => Self::PausePending,
+            "Paused" => Self::Paused,
+            _ => Self::Unknown(state),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Win32_Service {
+    __Path: String,
+    State: String,
+}
+
+#[derive(Deserialize)]
+struct ServiceControlOutput {
+    ReturnValue: u32,
+}
+
+///
+/// ### Service control methods
+///
+impl WMIConnection {
+    /// Request the service with the given name to start.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIError, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// match wmi_con.start_service("W32Time") {
+    ///     Ok(()) => println!("Starting W32Time"),
+    ///     Err(WMIError::MethodReturnValueError { return_value, .. }) => {
+    ///         println!("Failed to start W32Time: {}", return_value)
+    ///     }
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_service(&self, name: &str) -> WMIResult<()> {
+        self.exec_service_method(name, "StartService")
+    }
+
+    /// Request the service with the given name to stop.
+    pub fn stop_service(&self, name: &str) -> WMIResult<()> {
+        self.exec_service_method(name, "StopService")
+    }
+
+    /// Get the current state of the service with the given name.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # use wmi::services::ServiceState;
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let state = wmi_con.service_state("Winmgmt")?;
+    /// assert_eq!(state, ServiceState::Running);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn service_state(&self, name: &str) -> WMIResult<ServiceState> {
+        Ok(self.get_service(name)?.State.into())
+    }
+
+    fn get_service(&self, name: &str) -> WMIResult<Win32_Service> {
+        let mut filters = HashMap::new();
+        filters.insert("Name".to_owned(), FilterValue::String(name.to_owned()));
+
+        self.filtered_query::<Win32_Service>(&filters)?
+            .into_iter()
+            .next()
+            .ok_or(WMIError::ResultEmpty)
+    }
+
+    fn exec_service_method(&self, name: &str, method: &str) -> WMIResult<()> {
+        let service = self.get_service(name)?;
+
+        let output: ServiceControlOutput =
+            self.exec_instance_method::<Win32_Service, _, _>(method, &service.__Path, ())?;
+
+        match output.ReturnValue {
+            0 => Ok(()),
+            return_value => Err(WMIError::MethodReturnValueError {
+                method: method.to_owned(),
+                return_value,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::wmi_con;
+    use std::{thread::sleep, time::Duration};
+
+    // A benign service which is usually available.
+    const TEST_SERVICE: &str = "W32Time";
+
+    // Return value of `StartService` and `StopService` when not running as admin.
+    const ACCESS_DENIED: u32 = 2;
+
+    fn wait_for_state(wmi_con: &WMIConnection, expected: ServiceState) {
+        for _ in 0..50 {
+            if wmi_con.service_state(TEST_SERVICE).unwrap() == expected {
+                return;
+            }
+            sleep(Duration::from_millis(100));
+        }
+
+        assert_eq!(wmi_con.service_state(TEST_SERVICE).unwrap(), expected);
+    }
+
+    #[test]
+    fn it_can_get_service_state() {
+        let wmi_con = wmi_con();
+
+        assert_eq!(
+            wmi_con.service_state("Winmgmt").unwrap(),
+            ServiceState::Running
+        );
+        assert!(matches!(
+            wmi_con.service_state("NoSuchService"),
+            Err(WMIError::ResultEmpty)
+        ));
+    }
+
+    #[test]
+    fn it_can_toggle_a_service() {
+        let wmi_con = wmi_con();
+
+        let (first, second) = match wmi_con.service_state(TEST_SERVICE).unwrap() {
+            ServiceState::Running => (ServiceState::Stopped, ServiceState::Running),
+            _ => (ServiceState::Running, ServiceState::Stopped),
+        };
+
+        let toggle = |state: &ServiceState| match state {
+            ServiceState::Running => wmi_con.start_service(TEST_SERVICE),
+            _ => wmi_con.stop_service(TEST_SERVICE),
+        };
+
+        match toggle(&first) {
+            // This test requires running as admin.
+            Err(WMIError::MethodReturnValueError {
+                return_value: ACCESS_DENIED,
+                ..
+            }) => return,
+            res => res.unwrap(),
+        }
+        wait_for_state(&wmi_con, first);
+
+        toggle(&second).unwrap();
+        wait_for_state(&wmi_con, second);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{WMIConnection, WMIError, WMIResult};
+use serde::{de, ser, Deserialize};
+use std::{fmt, str::FromStr};
+
+/// A security identifier (SID), which supports parsing from the string form used by WMI (like `S-1-5-32-544`).
+///
+/// This is the format of the `SID` property of classes like `Win32_Account` and `Win32_SID`.
+///
+/// ```edition2021
+/// # use wmi::Sid;
+/// let administrators: Sid = "S-1-5-32-544".parse().unwrap();
+///
+/// assert_eq!(administrators.as_string(), "S-1-5-32-544");
+/// ```
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Sid(String);
+
+impl Sid {
+    /// The string form of the SID.
+    pub fn as_string(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Sid {
+    type Err = WMIError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('-');
+
+        // A SID has a revision and an identifier authority, followed by zero or more sub-authorities.
+        let is_valid = matches!(parts.next(), Some("S" | "s"))
+            && parts.clone().count() >= 2
+            && parts.all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+
+        if !is_valid {
+            return Err(WMIError::ConvertSidError(s.into()));
+        }
+
+        Ok(Self(s.to_uppercase()))
+    }
+}
+
+impl fmt::Display for Sid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SidVisitor;
+
+impl<'de> de::Visitor<'de> for SidVisitor {
+    type Value = Sid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a SID in string format")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        value.parse().map_err(|err| E::custom(format!("{}", err)))
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Sid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(SidVisitor)
+    }
+}
+
+impl ser::Serialize for Sid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// The account a SID belongs to, as returned by [`WMIConnection::resolve_sid`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename = "Win32_SID")]
+pub struct SidAccount {
+    /// The name of the account, like `Administrators`.
+    #[serde(rename = "AccountName")]
+    pub name: String,
+    /// The domain of the account, like `BUILTIN`.
+    #[serde(rename = "ReferencedDomainName")]
+    pub domain: String,
+}
+
+impl WMIConnection {
+    /// Resolve a SID to the account it belongs to, using the `Win32_SID` class.
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, Sid, WMIConnection, WMIResult};
+    /// # fn main() -> WMIResult<()> {
+    /// # let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    /// let administrators: Sid = "S-1-5-32-544".parse()?;
+    ///
+    /// let account = wmi_con.resolve_sid(&administrators)?;
+    /// println!("{}\\{}", account.domain, account.name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve_sid(&self, sid: &Sid) -> WMIResult<SidAccount> {
+        self.get_by_path(&format!(r#"Win32_SID.SID="{}""#, sid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::wmi_con;
+
+    #[test]
+    fn it_parses_sids() {
+        let sid: Sid = "S-1-5-32-544".parse().unwrap();
+        assert_eq!(sid.as_string(), "S-1-5-32-544");
+        assert_eq!(sid.to_string(), "S-1-5-32-544");
+
+        assert_eq!(sid, "s-1-5-32-544".parse().unwrap());
+        assert_ne!(sid, "S-1-5-32-545".parse().unwrap());
+
+        assert!("S-1".parse::<Sid>().is_err());
+        assert!("S-1-5-".parse::<Sid>().is_err());
+        assert!("X-1-5-32".parse::<Sid>().is_err());
+        assert!("Administrators".parse::<Sid>().is_err());
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(non_camel_case_types, non_snake_case)]
+    struct Win32_Group {
+        SID: Sid,
+    }
+
+    #[test]
+    fn it_desr_and_resolves_sid() {
+        let wmi_con = wmi_con();
+
+        let administrators: Sid = "S-1-5-32-544".parse().unwrap();
+
+        let groups: Vec<Win32_Group> = wmi_con
+            .raw_query(
+                r#"SELECT SID FROM Win32_Group WHERE LocalAccount = TRUE AND SID = "S-1-5-32-544""#,
+            )
+            .unwrap();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].SID, administrators);
+
+        let account = wmi_con.resolve_sid(&administrators).unwrap();
+
+        assert!(!account.name.is_empty());
+        assert!(!account.domain.is_empty());
+    }
+}
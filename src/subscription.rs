@@ -0,0 +1,296 @@
+use crate::{instance::PutInstanceMode, WMIConnection, WMIError, WMIResult};
+use serde::Serialize;
+use windows::Win32::System::Wmi::WBEM_E_ALREADY_EXISTS;
+
+/// The consumer which handles the events of a permanent subscription.
+///
+/// See [Monitoring and Responding to Events with Standard Consumers](https://learn.microsoft.com/en-us/windows/win32/wmisdk/monitoring-and-responding-to-events-with-standard-consumers).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventConsumer {
+    /// A [`CommandLineEventConsumer`](https://learn.microsoft.com/en-us/windows/win32/wmisdk/commandlineeventconsumer),
+    /// which starts a process when an event is delivered.
+    CommandLine {
+        /// The command line to run, which can use event properties like `%TargetInstance.Name%`.
+        command_line_template: String,
+        /// The executable to run. If `None`, the first token of `command_line_template` is used.
+        executable_path: Option<String>,
+    },
+    /// A [`LogFileEventConsumer`](https://learn.microsoft.com/en-us/windows/win32/wmisdk/logfileeventconsumer),
+    /// which writes a line to a text file when an event is delivered.
+    LogFile {
+        /// The path of the log file.
+        filename: String,
+        /// The line to write, which can use event properties like `%TargetInstance.Name%`.
+        text: String,
+    },
+}
+
+/// The `__Path`s of the instances created by [`WMIConnection::create_permanent_subscription`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermanentSubscription {
+    /// The path of the `__EventFilter` instance.
+    pub filter_path: String,
+    /// The path of the `__EventConsumer` instance.
+    pub consumer_path: String,
+    /// The path of the `__FilterToConsumerBinding` instance.
+    pub binding_path: String,
+}
+
+#[derive(Serialize)]
+struct __EventFilter<'a> {
+    Name: &'a str,
+    Query: &'a str,
+    QueryLanguage: &'a str,
+    EventNamespace: &'a str,
+}
+
+#[derive(Serialize)]
+struct CommandLineEventConsumer<'a> {
+    Name: &'a str,
+    CommandLineTemplate: &'a str,
+    ExecutablePath: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct LogFileEventConsumer<'a> {
+    Name: &'a str,
+    Filename: &'a str,
+    Text: &'a str,
+}
+
+#[derive(Serialize)]
+struct __FilterToConsumerBinding<'a> {
+    Filter: &'a str,
+    Consumer: &'a str,
+}
+
+///
+/// ### Permanent event subscription methods
+///
+impl WMIConnection {
+    /// Create a [permanent event subscription](https://learn.microsoft.com/en-us/windows/win32/wmisdk/receiving-events-at-all-times),
+    /// which keeps delivering events to `consumer` after the current process exits (and after a reboot).
+    ///
+    /// An `__EventFilter` for `query` (which is evaluated in `event_namespace`, like `ROOT\CIMV2`) and an `__EventConsumer`
+    /// are created using `name`, and are bound together with a `__FilterToConsumerBinding`.
+    /// Use [`WMIConnection::delete_permanent_subscription`] with the returned paths to remove the subscription.
+    ///
+    /// Existing instances with the same name are updated. If creating the subscription fails,
+    /// the instances created by this call are deleted, but instances which existed before are kept.
+    ///
+    /// The connection must be to the `ROOT\subscription` namespace, and the process must run as an administrator
+    /// (otherwise, a `WBEM_E_ACCESS_DENIED` error is returned).
+    ///
+    /// ```edition2021
+    /// # use wmi::{COMLibrary, WMIConnection, WMIResult};
+    /// # use wmi::subscription::EventConsumer;
+    /// # #[cfg(not(feature = "test"))]
+    /// # fn main() {}
+    /// # #[cfg(feature = "test")]
+    /// # fn main() -> WMIResult<()> {
+    /// #   wmi::tests::ignore_access_denied(run())
+    /// # }
+    /// # fn run() -> WMIResult<()> {
+    /// let wmi_con = WMIConnection::with_namespace_path("ROOT\\subscription", COMLibrary::new()?)?;
+    ///
+    /// let consumer = EventConsumer::LogFile {
+    ///     filename: "C:\\Windows\\Temp\\new_processes.log".to_string(),
+    ///     text: "New process: %TargetInstance.Name%".to_string(),
+    /// };
+    ///
+    /// let subscription = wmi_con.create_permanent_subscription(
+    ///     "LogNewProcesses",
+    ///     "SELECT * FROM __InstanceCreationEvent WITHIN 5 WHERE TargetInstance ISA 'Win32_Process'",
+    ///     "ROOT\\CIMV2",
+    ///     &consumer,
+    /// )?;
+    ///
+    /// // Later, possibly in another process.
+    /// wmi_con.delete_permanent_subscription(&subscription)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_permanent_subscription(
+        &self,
+        name: &str,
+        query: &str,
+        event_namespace: &str,
+        consumer: &EventConsumer,
+    ) -> WMIResult<PermanentSubscription> {
+        let filter = __EventFilter {
+            Name: name,
+            Query: query,
+            QueryLanguage: "WQL",
+            EventNamespace: event_namespace,
+        };
+
+        let (filter_path, filter_created) = self.create_or_update_instance(&filter)?;
+
+        // On failure, only the instances created by this call are deleted, and existing ones are kept.
+        let rollback = |created: &[(&str, bool)]| {
+            for (path, was_created) in created {
+                if *was_created {
+                    let _r = self.delete_instance(path);
+                }
+            }
+        };
+
+        let (consumer_path, consumer_created) = match self.put_event_consumer(name, consumer) {
+            Ok(consumer) => consumer,
+            Err(e) => {
+                rollback(&[(&filter_path, filter_created)]);
+                return Err(e);
+            }
+        };
+
+        let binding = __FilterToConsumerBinding {
+            Filter: &filter_path,
+            Consumer: &consumer_path,
+        };
+
+        let binding_path = match self.create_or_update_instance(&binding) {
+            Ok((binding_path, _)) => binding_path,
+            Err(e) => {
+                rollback(&[
+                    (&consumer_path, consumer_created),
+                    (&filter_path, filter_created),
+                ]);
+                return Err(e);
+            }
+        };
+
+        Ok(PermanentSubscription {
+            filter_path,
+            consumer_path,
+            binding_path,
+        })
+    }
+
+    /// Delete the instances of a subscription created by [`WMIConnection::create_permanent_subscription`].
+    ///
+    /// The binding is deleted first, so no more events are delivered even if deleting the filter or the consumer fails.
+    pub fn delete_permanent_subscription(
+        &self,
+        subscription: &PermanentSubscription,
+    ) -> WMIResult<()> {
+        self.delete_instance(&subscription.binding_path)?;
+        self.delete_instance(&subscription.consumer_path)?;
+        self.delete_instance(&subscription.filter_path)?;
+
+        Ok(())
+    }
+
+    /// Create or update an instance, and return its path and whether it was created (and not updated).
+    fn create_or_update_instance<T>(&self, instance: &T) -> WMIResult<(String, bool)>
+    where
+        T: Serialize,
+    {
+        match self.put_instance(instance, PutInstanceMode::CreateOnly) {
+            Ok(path) => Ok((path, true)),
+            Err(WMIError::HResultError { hres, .. }) if hres == WBEM_E_ALREADY_EXISTS.0 => {
+                let path = self.put_instance(instance, PutInstanceMode::UpdateOnly)?;
+
+                Ok((path, false))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn put_event_consumer(
+        &self,
+        name: &str,
+        consumer: &EventConsumer,
+    ) -> WMIResult<(String, bool)> {
+        match consumer {
+            EventConsumer::CommandLine {
+                command_line_template,
+                executable_path,
+            } => {
+                let consumer = CommandLineEventConsumer {
+                    Name: name,
+                    CommandLineTemplate: command_line_template,
+                    ExecutablePath: executable_path.as_deref(),
+                };
+
+                self.create_or_update_instance(&consumer)
+            }
+            EventConsumer::LogFile { filename, text } => {
+                let consumer = LogFileEventConsumer {
+                    Name: name,
+                    Filename: filename,
+                    Text: text,
+                };
+
+                self.create_or_update_instance(&consumer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::ignore_access_denied;
+    use crate::{COMLibrary, WMIError};
+    use serde::Deserialize;
+    use windows::Win32::System::Wmi::WBEM_E_NOT_FOUND;
+
+    #[derive(Deserialize, Debug)]
+    struct __FilterToConsumerBinding {
+        Filter: String,
+        Consumer: String,
+    }
+
+    fn create_and_delete_subscription() -> WMIResult<()> {
+        let wmi_con = WMIConnection::with_namespace_path(
+            "ROOT\\subscription",
+            COMLibrary::without_security()?,
+        )?;
+
+        let consumer = EventConsumer::CommandLine {
+            command_line_template: "C:\\Windows\\System32\\cmd.exe /c exit".to_string(),
+            executable_path: None,
+        };
+
+        let subscription = wmi_con.create_permanent_subscription(
+            "WMI_RS_TEST_SUBSCRIPTION",
+            "SELECT * FROM __InstanceCreationEvent WITHIN 5 WHERE TargetInstance ISA 'Win32_Process'",
+            "ROOT\\CIMV2",
+            &consumer,
+        )?;
+
+        assert_eq!(
+            subscription.filter_path,
+            r#"__EventFilter.Name="WMI_RS_TEST_SUBSCRIPTION""#
+        );
+        assert_eq!(
+            subscription.consumer_path,
+            r#"CommandLineEventConsumer.Name="WMI_RS_TEST_SUBSCRIPTION""#
+        );
+
+        let binding: __FilterToConsumerBinding = wmi_con.get_by_path(&subscription.binding_path)?;
+        assert!(binding.Filter.ends_with(&subscription.filter_path));
+        assert!(binding.Consumer.ends_with(&subscription.consumer_path));
+
+        wmi_con.delete_permanent_subscription(&subscription)?;
+
+        for path in [
+            &subscription.binding_path,
+            &subscription.consumer_path,
+            &subscription.filter_path,
+        ] {
+            match wmi_con.get_raw_by_path(path) {
+                Err(WMIError::HResultError { hres, .. }) => assert_eq!(hres, WBEM_E_NOT_FOUND.0),
+                res => panic!("Unexpected result {:?}", res),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_can_create_and_delete_a_permanent_subscription() {
+        // Creating a permanent subscription requires running as admin.
+        ignore_access_denied(create_and_delete_subscription()).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    de::meta::struct_name_and_fields, result_enumerator::IWbemClassWrapper, Variant, WMIResult,
+};
+use serde::de;
+
+/// The properties of a list of WMI objects, arranged in rows with a fixed set of columns
+/// (for example, for CSV or table output).
+///
+/// The columns are chosen once, and every row has a value for each column, in the same order.
+/// Properties which are missing from an object (like a property which only exists on some subclasses) are [`Variant::Null`].
+///
+/// ```edition2021
+/// # fn main() -> wmi::WMIResult<()> {
+/// # use wmi::*;
+/// # let con = WMIConnection::new(COMLibrary::new()?)?;
+/// let disks = con.exec_query_native_wrapper("SELECT * FROM CIM_LogicalDisk")?;
+/// let table = Table::from_objects(disks.collect::<WMIResult<Vec<_>>>()?)?;
+///
+/// println!("{}", table.columns.join(","));
+/// for row in &table.rows {
+///     assert_eq!(row.len(), table.columns.len());
+/// }
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Variant>>,
+}
+
+impl Table {
+    /// Create a table with the properties of the first object as the columns.
+    pub fn from_objects(objects: impl IntoIterator<Item = IWbemClassWrapper>) -> WMIResult<Self> {
+        let mut objects = objects.into_iter().peekable();
+
+        let columns = match objects.peek() {
+            Some(first) => first.list_properties()?,
+            None => vec![],
+        };
+
+        Self::with_columns(columns, objects)
+    }
+
+    /// Create a table with the fields of `T` as the columns (similar to the fields selected by [`crate::build_query`]).
+    pub fn from_struct_fields<'de, T>(
+        objects: impl IntoIterator<Item = IWbemClassWrapper>,
+    ) -> WMIResult<Self>
+    where
+        T: de::Deserialize<'de>,
+    {
+        let (_, fields) = struct_name_and_fields::<T>()?;
+
+        Self::with_columns(
+            fields.iter().map(|field| field.to_string()).collect(),
+            objects,
+        )
+    }
+
+    /// Create a table with the given columns.
+    pub fn with_columns(
+        columns: Vec<String>,
+        objects: impl IntoIterator<Item = IWbemClassWrapper>,
+    ) -> WMIResult<Self> {
+        let rows = objects
+            .into_iter()
+            .map(|object| {
+                columns
+                    .iter()
+                    .map(|column| match object.get_property(column) {
+                        Err(e) if e.is_not_found() => Ok(Variant::Null),
+                        res => res,
+                    })
+                    .collect::<WMIResult<Vec<_>>>()
+            })
+            .collect::<WMIResult<_>>()?;
+
+        Ok(Self { columns, rows })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::*;
+    use serde::Deserialize;
+
+    #[test]
+    fn it_uses_the_same_columns_for_all_rows() {
+        let wmi_con = wmi_con();
+
+        let os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+        let computer_systems: Vec<_> = wmi_con
+            .exec_query_native_wrapper("SELECT * FROM Win32_ComputerSystem")
+            .unwrap()
+            .collect::<WMIResult<_>>()
+            .unwrap();
+
+        let objects = [vec![os.clone(), os], computer_systems].concat();
+        let row_count = objects.len();
+
+        let table = Table::from_objects(objects).unwrap();
+
+        assert!(table.columns.contains(&"Caption".to_string()));
+        assert!(table.columns.contains(&"BuildNumber".to_string()));
+        assert_eq!(table.rows.len(), row_count);
+
+        let build_number = table
+            .columns
+            .iter()
+            .position(|column| column == "BuildNumber")
+            .unwrap();
+
+        for row in &table.rows {
+            assert_eq!(row.len(), table.columns.len());
+        }
+
+        // `Win32_ComputerSystem` has no `BuildNumber` property.
+        assert!(matches!(table.rows[0][build_number], Variant::String(_)));
+        assert_eq!(table.rows[2][build_number], Variant::Null);
+    }
+
+    #[test]
+    fn it_uses_the_struct_fields_as_columns() {
+        let wmi_con = wmi_con();
+
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Win32_OperatingSystem {
+            Caption: String,
+            NotAProperty: String,
+        }
+
+        let os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        let table = Table::from_struct_fields::<Win32_OperatingSystem>([os]).unwrap();
+
+        assert_eq!(table.columns, ["Caption", "NotAProperty"]);
+        assert!(matches!(table.rows[0][0], Variant::String(_)));
+        assert_eq!(table.rows[0][1], Variant::Null);
+    }
+
+    #[test]
+    fn it_has_no_columns_without_objects() {
+        let table = Table::from_objects(Vec::<IWbemClassWrapper>::new()).unwrap();
+
+        assert!(table.columns.is_empty());
+        assert!(table.rows.is_empty());
+    }
+}
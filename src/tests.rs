@@ -1,4 +1,4 @@
-use crate::{COMLibrary, WMIConnection, WMIError, WMIResult};
+use crate::{COMLibrary, WMIConnection, WMIResult};
 
 pub mod fixtures {
     use super::*;
@@ -23,14 +23,8 @@ pub fn start_test_program() {
 }
 
 pub fn ignore_access_denied(result: WMIResult<()>) -> WMIResult<()> {
-    use windows::Win32::System::Wmi::WBEM_E_ACCESS_DENIED;
-
-    if let Err(e) = result {
-        if let WMIError::HResultError { hres } = e {
-            if hres != WBEM_E_ACCESS_DENIED.0 {
-                return Err(e);
-            }
-        }
+    match result {
+        Err(e) if !e.is_access_denied() => Err(e),
+        _ => Ok(()),
     }
-    Ok(())
 }
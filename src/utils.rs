@@ -1,14 +1,26 @@
 use serde::{de, ser};
 use std::fmt::{Debug, Display};
 use thiserror::Error;
+use windows::Win32::Foundation::{ERROR_TIMEOUT, E_ACCESSDENIED, E_OUTOFMEMORY, RPC_E_TIMEOUT};
+use windows::Win32::System::Wmi::{
+    WBEM_E_ACCESS_DENIED, WBEM_E_INVALID_CLASS, WBEM_E_INVALID_NAMESPACE, WBEM_E_NOT_FOUND,
+    WBEM_E_OUT_OF_MEMORY, WBEM_E_PROVIDER_TIMED_OUT, WBEM_E_QUOTA_VIOLATION, WBEM_E_TIMED_OUT,
+};
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum WMIError {
     /// You can find a useful resource for decoding error codes [here](https://docs.microsoft.com/en-us/windows/win32/wmisdk/wmi-error-constants)
     /// (or a github version [here](https://github.com/MicrosoftDocs/win32/blob/docs/desktop-src/WmiSdk/wmi-error-constants.md))
+    ///
+    /// The original [`windows::core::Error`] (which might carry a more detailed message) is kept as the `source`.
+    /// Match on this variant with `WMIError::HResultError { hres, .. }`.
     #[error("HRESULT Call failed with: {hres:#X}")]
-    HResultError { hres: i32 },
+    #[non_exhaustive]
+    HResultError {
+        hres: i32,
+        source: windows::core::Error,
+    },
     #[error(transparent)]
     ParseIntError(#[from] std::num::ParseIntError),
     #[error(transparent)]
@@ -32,8 +44,10 @@ pub enum WMIError {
     ConvertStringError(#[from] std::string::FromUtf16Error),
     #[error("Expected {0:?} to be at least 21 chars")]
     ConvertDatetimeError(String),
-    #[error("Expected {0:?} to be at 25 chars")]
+    #[error("Expected {0:?} to be a CIM_DATETIME interval (ddddddddHHMMSS.mmmmmm:000)")]
     ConvertDurationError(String),
+    #[error("Expected {0:?} to be a SID in string format")]
+    ConvertSidError(String),
     #[error("Length {0} was too long to convert")]
     ConvertLengthError(u64),
     #[error("{0}")]
@@ -48,12 +62,38 @@ pub enum WMIError {
     UnimplementedArrayItem,
     #[error("Invalid variant {0} during deserialization")]
     InvalidDeserializationVariantError(String),
+    /// The class of an object was not registered in the [`ClassRegistry`](crate::registry::ClassRegistry) used to deserialize it.
+    #[error("Class {0} is not registered")]
+    UnregisteredClassError(String),
+    /// The class could not be deleted by [`WMIConnection::delete_class`](crate::WMIConnection::delete_class)
+    /// because it has instances (`WBEM_E_CLASS_HAS_INSTANCES`).
+    #[error("Class {0} has instances")]
+    ClassHasInstancesError(String),
+    /// The class could not be deleted by [`WMIConnection::delete_class`](crate::WMIConnection::delete_class)
+    /// because it has subclasses (`WBEM_E_CLASS_HAS_CHILDREN`).
+    #[error("Class {0} has subclasses")]
+    ClassHasChildrenError(String),
+    /// A WMI method was executed successfully, but returned a non-zero `ReturnValue`.
+    /// The meaning of the value depends on the method.
+    #[error("Method {method} returned {return_value}")]
+    MethodReturnValueError { method: String, return_value: u32 },
+    /// The moniker passed to [`WMIConnection::from_moniker`](crate::WMIConnection::from_moniker) is invalid or not supported.
+    #[error("Invalid WMI moniker {0}")]
+    InvalidMonikerError(String),
+    /// The `WITHIN` polling interval of a notification query is zero (see [`build_notification_query`](crate::build_notification_query)).
+    #[error("Invalid WITHIN polling interval {0:?}, must be greater than zero")]
+    InvalidWithinIntervalError(std::time::Duration),
+    /// A query or filter is not supported by [`MockWMIConnection`](crate::mock::MockWMIConnection).
+    #[cfg(feature = "mock")]
+    #[error("Unsupported by the mock connection: {0}")]
+    MockUnsupportedError(String),
 }
 
 impl From<windows::core::Error> for WMIError {
     fn from(value: windows::core::Error) -> Self {
         Self::HResultError {
             hres: value.code().0,
+            source: value,
         }
     }
 }
@@ -72,5 +112,261 @@ impl ser::Error for WMIError {
     }
 }
 
+impl WMIError {
+    /// Returns the original [`windows::core::Error`], if this error was caused by a failed COM call.
+    pub fn windows_error(&self) -> Option<&windows::core::Error> {
+        match self {
+            Self::HResultError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the error was caused by missing permissions
+    /// (`WBEM_E_ACCESS_DENIED` or `E_ACCESSDENIED`), for example when subscribing to some events without admin rights.
+    pub fn is_access_denied(&self) -> bool {
+        self.has_hresult(&[WBEM_E_ACCESS_DENIED.0, E_ACCESSDENIED.0])
+    }
+
+    /// Returns `true` if the requested object, class or namespace does not exist
+    /// (`WBEM_E_NOT_FOUND`, `WBEM_E_INVALID_CLASS` or `WBEM_E_INVALID_NAMESPACE`).
+    pub fn is_not_found(&self) -> bool {
+        self.has_hresult(&[
+            WBEM_E_NOT_FOUND.0,
+            WBEM_E_INVALID_CLASS.0,
+            WBEM_E_INVALID_NAMESPACE.0,
+        ])
+    }
+
+    /// Returns `true` if the call timed out
+    /// (`WBEM_E_TIMED_OUT`, `WBEM_E_PROVIDER_TIMED_OUT`, `RPC_E_TIMEOUT` or `ERROR_TIMEOUT`).
+    pub fn is_timeout(&self) -> bool {
+        self.has_hresult(&[
+            WBEM_E_TIMED_OUT.0,
+            WBEM_E_PROVIDER_TIMED_OUT.0,
+            RPC_E_TIMEOUT.0,
+            ERROR_TIMEOUT.to_hresult().0,
+        ])
+    }
+
+    /// Returns `true` if WMI ran out of resources while handling the call
+    /// (`WBEM_E_QUOTA_VIOLATION`, `WBEM_E_OUT_OF_MEMORY` or `E_OUTOFMEMORY`).
+    ///
+    /// This usually happens with queries which return many (or large) objects. To avoid it:
+    /// - Select only the needed properties (a struct with fewer fields, or `SELECT Name, ProcessId` instead of `SELECT *`).
+    /// - Filter the results in the query (using a `WHERE` clause or `filtered_query`) instead of after deserializing them.
+    /// - Process the results lazily (using [`WMIConnection::exec_query_native_wrapper`](crate::WMIConnection::exec_query_native_wrapper))
+    ///   with a smaller batch size (see [`QueryResultEnumerator::with_batch_size`](crate::result_enumerator::QueryResultEnumerator::with_batch_size)).
+    ///
+    /// When a [`QueryResultEnumerator`](crate::result_enumerator::QueryResultEnumerator) returns such an error, the enumeration ends.
+    pub fn is_resource_exhausted(&self) -> bool {
+        self.has_hresult(&[
+            WBEM_E_QUOTA_VIOLATION.0,
+            WBEM_E_OUT_OF_MEMORY.0,
+            E_OUTOFMEMORY.0,
+        ])
+    }
+
+    fn has_hresult(&self, codes: &[i32]) -> bool {
+        match self {
+            Self::HResultError { hres, .. } => codes.contains(hres),
+            _ => false,
+        }
+    }
+
+    /// Returns the category, code and message of this error, as structured fields suitable for logging.
+    ///
+    /// For an [`WMIError::HResultError`], the category is derived from the facility of the `HRESULT`
+    /// (`"wmi"` for `WBEM_E_*` errors, `"win32"` for errors like `E_ACCESSDENIED`, and `"com"` otherwise),
+    /// and the message is the system's description of the error (when available).
+    ///
+    /// Other errors have no code, and their message is their `Display` output.
+    ///
+    /// ```edition2021
+    /// # fn main() -> wmi::WMIResult<()> {
+    /// # use wmi::*;
+    /// let wmi_con = WMIConnection::new(COMLibrary::new()?)?;
+    ///
+    /// if let Err(err) = wmi_con.get_raw_by_path("Win32_NoSuchClass") {
+    ///     let (category, code, message) = err.log_fields();
+    ///     eprintln!("category={} code={:?} message={:?}", category, code, message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn log_fields(&self) -> (&'static str, Option<i32>, String) {
+        let category = match self {
+            Self::HResultError { hres, .. } => match (*hres as u32 >> 16) & 0x1FFF {
+                FACILITY_ITF => "wmi",
+                FACILITY_WIN32 => "win32",
+                _ => "com",
+            },
+            Self::ParseIntError(_) | Self::ParseFloatError(_) => "parse",
+            #[cfg(feature = "chrono")]
+            Self::ParseDatetimeError(_) | Self::ParseDatetimeLocalError => "parse",
+            #[cfg(feature = "time")]
+            Self::ParseOffsetDatetimeError(_) => "parse",
+            Self::ConvertError(_)
+            | Self::ConvertVariantError(_)
+            | Self::ConvertBoolError(_)
+            | Self::ConvertStringError(_)
+            | Self::ConvertDatetimeError(_)
+            | Self::ConvertDurationError(_)
+            | Self::ConvertSidError(_)
+            | Self::ConvertLengthError(_)
+            | Self::UnimplementedArrayItem => "convert",
+            Self::SerdeError(_)
+            | Self::DeserializeValueError(_)
+            | Self::InvalidDeserializationVariantError(_) => "serde",
+            Self::ResultEmpty | Self::NullPointerResult => "result",
+            Self::UnregisteredClassError(_)
+            | Self::ClassHasInstancesError(_)
+            | Self::ClassHasChildrenError(_) => "class",
+            Self::MethodReturnValueError { .. } => "method",
+            Self::InvalidMonikerError(_) => "parse",
+            Self::InvalidWithinIntervalError(_) => "query",
+            #[cfg(feature = "mock")]
+            Self::MockUnsupportedError(_) => "mock",
+        };
+
+        let (code, message) = match self {
+            Self::HResultError { hres, source } => {
+                let message = source.message();
+                let message = if message.is_empty() {
+                    self.to_string()
+                } else {
+                    message
+                };
+
+                (Some(*hres), message)
+            }
+            _ => (None, self.to_string()),
+        };
+
+        (category, code, message)
+    }
+}
+
+/// The facility of `WBEM_E_*` errors (and other interface specific errors).
+const FACILITY_ITF: u32 = 4;
+/// The facility of Win32 errors (like `E_ACCESSDENIED`) which are converted to an `HRESULT`.
+const FACILITY_WIN32: u32 = 7;
+
 /// Alias type for `Result<T, WMIError>`
 pub type WMIResult<T> = Result<T, WMIError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::*;
+    use windows::core::HRESULT;
+
+    #[test]
+    fn it_preserves_the_original_windows_error() {
+        let wmi_con = wmi_con();
+
+        let err = wmi_con
+            .get_raw_by_path("Win32_NoSuchClass")
+            .map(|_| ())
+            .unwrap_err();
+
+        match &err {
+            WMIError::HResultError { hres, source } => {
+                assert_eq!(*hres, WBEM_E_INVALID_CLASS.0);
+                assert_eq!(source.code().0, *hres);
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+
+        assert_eq!(
+            err.windows_error().unwrap().code().0,
+            WBEM_E_INVALID_CLASS.0
+        );
+    }
+
+    #[test]
+    fn it_returns_log_fields_of_hresult_errors() {
+        let wmi_con = wmi_con();
+
+        let err = wmi_con
+            .get_raw_by_path("Win32_NoSuchClass")
+            .map(|_| ())
+            .unwrap_err();
+
+        let (category, code, message) = err.log_fields();
+
+        assert_eq!(category, "wmi");
+        assert_eq!(code, Some(WBEM_E_INVALID_CLASS.0));
+        assert!(!message.is_empty());
+
+        let err = WMIError::from(windows::core::Error::from(E_ACCESSDENIED));
+
+        let (category, code, message) = err.log_fields();
+
+        assert_eq!(category, "win32");
+        assert_eq!(code, Some(E_ACCESSDENIED.0));
+        assert_eq!(
+            message,
+            windows::core::Error::from(E_ACCESSDENIED).message()
+        );
+    }
+
+    fn hresult_error(hres: i32) -> WMIError {
+        WMIError::from(windows::core::Error::from(HRESULT(hres)))
+    }
+
+    #[test]
+    fn it_detects_access_denied() {
+        assert!(hresult_error(WBEM_E_ACCESS_DENIED.0).is_access_denied());
+        assert!(hresult_error(E_ACCESSDENIED.0).is_access_denied());
+
+        assert!(!hresult_error(WBEM_E_ACCESS_DENIED.0).is_not_found());
+        assert!(!hresult_error(WBEM_E_NOT_FOUND.0).is_access_denied());
+        assert!(!WMIError::ResultEmpty.is_access_denied());
+    }
+
+    #[test]
+    fn it_detects_not_found() {
+        assert!(hresult_error(WBEM_E_NOT_FOUND.0).is_not_found());
+        assert!(hresult_error(WBEM_E_INVALID_CLASS.0).is_not_found());
+        assert!(hresult_error(WBEM_E_INVALID_NAMESPACE.0).is_not_found());
+
+        assert!(!hresult_error(WBEM_E_NOT_FOUND.0).is_timeout());
+
+        let wmi_con = wmi_con();
+        let err = wmi_con
+            .get_raw_by_path("Win32_NoSuchClass")
+            .map(|_| ())
+            .unwrap_err();
+        assert!(err.is_not_found());
+    }
+
+    #[test]
+    fn it_detects_timeouts() {
+        assert!(hresult_error(WBEM_E_TIMED_OUT.0).is_timeout());
+        assert!(hresult_error(WBEM_E_PROVIDER_TIMED_OUT.0).is_timeout());
+        assert!(hresult_error(RPC_E_TIMEOUT.0).is_timeout());
+        assert!(hresult_error(ERROR_TIMEOUT.to_hresult().0).is_timeout());
+
+        assert!(!hresult_error(WBEM_E_TIMED_OUT.0).is_access_denied());
+    }
+
+    #[test]
+    fn it_detects_resource_exhaustion() {
+        assert!(hresult_error(WBEM_E_QUOTA_VIOLATION.0).is_resource_exhausted());
+        assert!(hresult_error(WBEM_E_OUT_OF_MEMORY.0).is_resource_exhausted());
+        assert!(hresult_error(E_OUTOFMEMORY.0).is_resource_exhausted());
+
+        assert!(!hresult_error(WBEM_E_QUOTA_VIOLATION.0).is_timeout());
+        assert!(!hresult_error(WBEM_E_ACCESS_DENIED.0).is_resource_exhausted());
+        assert!(!WMIError::ResultEmpty.is_resource_exhausted());
+    }
+
+    #[test]
+    fn it_returns_log_fields_of_other_errors() {
+        let (category, code, message) = WMIError::ResultEmpty.log_fields();
+
+        assert_eq!(category, "result");
+        assert_eq!(code, None);
+        assert_eq!(message, "No results returned");
+    }
+}
@@ -1,20 +1,28 @@
 use crate::{
-    result_enumerator::IWbemClassWrapper, safearray::safe_array_to_vec, WMIError, WMIResult,
+    result_enumerator::IWbemClassWrapper, safearray::safe_array_to_vec_with_decoding, WMIError,
+    WMIResult,
 };
 use serde::Serialize;
 use std::convert::TryFrom;
-use windows::core::{IUnknown, Interface, VARIANT};
-use windows::Win32::Foundation::{VARIANT_BOOL, VARIANT_FALSE, VARIANT_TRUE};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use windows::core::{IUnknown, Interface, BSTR, HSTRING, PCWSTR, PROPVARIANT, VARIANT};
+use windows::Win32::Foundation::{BOOL, VARIANT_BOOL, VARIANT_FALSE, VARIANT_TRUE};
 use windows::Win32::System::Variant::*;
 use windows::Win32::System::Wmi::{self, IWbemClassObject, CIMTYPE_ENUMERATION};
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(untagged)]
+#[non_exhaustive]
 pub enum Variant {
     Empty,
     Null,
 
     String(String),
+    /// The path of another object, from a property of type `CIM_REFERENCE` (like `Antecedent` and `Dependent` in association classes).
+    ///
+    /// Use [`WMIConnection::get_by_reference`](crate::WMIConnection::get_by_reference) to get the referenced object.
+    Reference(String),
 
     I1(i8),
     I2(i16),
@@ -36,6 +44,10 @@ pub enum Variant {
     /// Temporary variant used internally
     Unknown(IUnknownWrapper),
     Object(IWbemClassWrapper),
+
+    /// A placeholder for a value with an unsupported `VARIANT` type, holding the `VARTYPE` tag.
+    /// Only returned by [`Variant::from_variant_lenient`].
+    Unsupported(u16),
 }
 
 // The `cast_num` macro is used to convert a numerical variable to a variant of the given CIMTYPE.
@@ -72,12 +84,42 @@ macro_rules! cast_num {
     };
 }
 
+/// Controls how invalid UTF-16 (like an unpaired surrogate) is handled when converting a `BSTR` into a `String`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StringDecoding {
+    /// Replace invalid sequences with `U+FFFD REPLACEMENT CHARACTER`. This is the default.
+    #[default]
+    Lossy,
+    /// Fail with [`WMIError::ConvertStringError`], which is useful for detecting corrupted data.
+    Strict,
+}
+
+impl StringDecoding {
+    /// Decode a UTF-16 string.
+    pub fn decode(self, wide: &[u16]) -> WMIResult<String> {
+        match self {
+            StringDecoding::Lossy => Ok(String::from_utf16_lossy(wide)),
+            StringDecoding::Strict => Ok(String::from_utf16(wide)?),
+        }
+    }
+}
+
 impl Variant {
     /// Create a `Variant` instance from a raw `VARIANT`.
     ///
+    /// Strings are decoded using [`StringDecoding::Lossy`].
+    ///
     /// Note: this function is safe since manipulating a `VARIANT` by hand is an *unsafe* operation,
     /// so we can assume that the `VARIANT` is valid.
     pub fn from_variant(variant: &VARIANT) -> WMIResult<Variant> {
+        Self::from_variant_with_decoding(variant, StringDecoding::Lossy)
+    }
+
+    /// Like [`Variant::from_variant`], but using the given [`StringDecoding`] for strings (and arrays of strings).
+    pub fn from_variant_with_decoding(
+        variant: &VARIANT,
+        decoding: StringDecoding,
+    ) -> WMIResult<Variant> {
         let vt = variant.as_raw();
         let variant_type = unsafe { vt.Anonymous.Anonymous.vt };
 
@@ -93,7 +135,7 @@ impl Variant {
             let item_type = VARENUM(variant_type & VT_TYPEMASK.0);
 
             return Ok(Variant::Array(unsafe {
-                safe_array_to_vec(&*array, item_type)?
+                safe_array_to_vec_with_decoding(&*array, item_type, decoding)?
             }));
         }
 
@@ -101,7 +143,15 @@ impl Variant {
         // Rust can infer the return type of `vt.*Val()` calls,
         // but it's easier to read when the type is named explicitly.
         let variant_value = match VARENUM(variant_type) {
-            VT_BSTR => Variant::String(variant.to_string()),
+            VT_BSTR => {
+                // Borrow the `BSTR` owned by the `VARIANT` (`BSTR` is a transparent wrapper around the raw pointer),
+                // so the decoded `String` is the only allocation.
+                let bstr = unsafe {
+                    &*(std::ptr::addr_of!(vt.Anonymous.Anonymous.Anonymous.bstrVal) as *const BSTR)
+                };
+
+                Variant::String(decoding.decode(bstr.as_wide())?)
+            }
             VT_I1 => {
                 let num = unsafe { vt.Anonymous.Anonymous.Anonymous.cVal };
 
@@ -161,6 +211,11 @@ impl Variant {
 
                 Variant::UI8(num)
             }
+            VT_DATE => {
+                let date: f64 = unsafe { vt.Anonymous.Anonymous.Anonymous.date };
+
+                Variant::String(ole_date_to_cim_datetime(date)?)
+            }
             VT_EMPTY => Variant::Empty,
             VT_NULL => Variant::Null,
             VT_UNKNOWN => {
@@ -170,12 +225,49 @@ impl Variant {
                 let ptr = ptr.cloned().ok_or(WMIError::NullPointerResult)?;
                 Variant::Unknown(IUnknownWrapper::new(ptr))
             }
+            VT_DISPATCH => {
+                let ptr = unsafe {
+                    IUnknown::from_raw_borrowed(&vt.Anonymous.Anonymous.Anonymous.pdispVal)
+                };
+                let ptr = ptr.cloned().ok_or(WMIError::NullPointerResult)?;
+
+                // Other kinds of `IDispatch` objects (like scripting objects) are kept as-is.
+                match ptr.cast::<IWbemClassObject>() {
+                    Ok(obj) => Variant::Object(IWbemClassWrapper::new(obj)),
+                    Err(_) => Variant::Unknown(IUnknownWrapper::new(ptr)),
+                }
+            }
             _ => return Err(WMIError::ConvertError(variant_type)),
         };
 
         Ok(variant_value)
     }
 
+    /// Like [`Variant::from_variant`], but instead of failing on a `VARIANT` of an unsupported type,
+    /// returns a [`Variant::Unsupported`] placeholder with the `VARIANT`'s type.
+    ///
+    /// This is useful when reading objects which might have a few unexpected properties,
+    /// and a single property should not fail the entire object.
+    pub fn from_variant_lenient(variant: &VARIANT) -> WMIResult<Variant> {
+        match Self::from_variant(variant) {
+            Err(WMIError::ConvertError(variant_type)) => Ok(Variant::Unsupported(variant_type)),
+            Err(WMIError::UnimplementedArrayItem) => {
+                let variant_type = unsafe { variant.as_raw().Anonymous.Anonymous.vt };
+
+                Ok(Variant::Unsupported(variant_type))
+            }
+            res => res,
+        }
+    }
+
+    /// Returns the path of the referenced object, if this is a [`Variant::Reference`].
+    pub fn as_reference(&self) -> Option<&str> {
+        match self {
+            Variant::Reference(path) => Some(path),
+            _ => None,
+        }
+    }
+
     /// Convert the variant it to a specific type.
     pub fn convert_into_cim_type(self, cim_type: CIMTYPE_ENUMERATION) -> WMIResult<Self> {
         if cim_type == Wmi::CIM_EMPTY {
@@ -241,7 +333,8 @@ impl Variant {
                     Wmi::CIM_SINT16 => Variant::I2(s.parse()?),
                     Wmi::CIM_UINT8 => Variant::UI1(s.parse()?),
                     Wmi::CIM_SINT8 => Variant::I1(s.parse()?),
-                    // Since Variant cannot natively represent a CIM_DATETIME or a CIM_REFERENCE (or any other), we keep it as a string.
+                    Wmi::CIM_REFERENCE => Variant::Reference(s),
+                    // Since Variant cannot natively represent a CIM_DATETIME (or any other), we keep it as a string.
                     _ => Variant::String(s),
                 }
             }
@@ -263,7 +356,9 @@ impl Variant {
                     )));
                 }
             }
+            Variant::Reference(s) => Variant::Reference(s),
             Variant::Object(o) => Variant::Object(o),
+            Variant::Unsupported(variant_type) => Variant::Unsupported(variant_type),
         };
 
         Ok(converted_variant)
@@ -276,7 +371,9 @@ impl TryFrom<Variant> for VARIANT {
         match value {
             Variant::Empty => Ok(VARIANT::new()),
 
-            Variant::String(string) => Ok(VARIANT::from(string.as_str())),
+            Variant::String(string) | Variant::Reference(string) => {
+                Ok(VARIANT::from(string.as_str()))
+            }
             Variant::I1(int8) => Ok(VARIANT::from(int8)),
             Variant::I2(int16) => Ok(VARIANT::from(int16)),
             Variant::I4(int32) => Ok(VARIANT::from(int32)),
@@ -292,23 +389,124 @@ impl TryFrom<Variant> for VARIANT {
             Variant::UI4(uint32) => Ok(VARIANT::from(uint32)),
             Variant::UI8(uint64) => Ok(VARIANT::from(uint64)),
 
+            Variant::Array(items) => variant_from_array(items),
+            Variant::Object(obj) => Ok(VARIANT::from(obj.inner.cast::<IUnknown>()?)),
+
             // windows-rs' VARIANT does not support creating these types of VARIANT at present
             Variant::Null => Err(WMIError::ConvertVariantError(
                 "Cannot convert Variant::Null to a Windows VARIANT".to_string(),
             )),
-            Variant::Array(_) => Err(WMIError::ConvertVariantError(
-                "Cannot convert Variant::Array to a Windows VARIANT".to_string(),
-            )),
             Variant::Unknown(_) => Err(WMIError::ConvertVariantError(
                 "Cannot convert Variant::Unknown to a Windows VARIANT".to_string(),
             )),
-            Variant::Object(_) => Err(WMIError::ConvertVariantError(
-                "Cannot convert Variant::Object to a Windows VARIANT".to_string(),
-            )),
+            Variant::Unsupported(variant_type) => Err(WMIError::ConvertError(variant_type)),
         }
     }
 }
 
+/// Convert a [`PROPVARIANT`] (used by newer Windows APIs, like the property system) to a `Variant`.
+///
+/// The `PROPVARIANT` is first converted to a `VARIANT` using [`PropVariantToVariant`](https://learn.microsoft.com/en-us/windows/win32/api/propvarutil/nf-propvarutil-propvarianttovariant),
+/// so only the types supported by both it and [`Variant::from_variant`] can be converted:
+/// numbers, booleans, strings (`VT_LPWSTR` is converted to `VT_BSTR`), and vectors of these types.
+impl TryFrom<&PROPVARIANT> for Variant {
+    type Error = WMIError;
+
+    fn try_from(value: &PROPVARIANT) -> WMIResult<Variant> {
+        let variant = VARIANT::try_from(value)?;
+
+        Variant::from_variant(&variant)
+    }
+}
+
+/// Convert a `Variant` to a [`PROPVARIANT`].
+///
+/// The `Variant` is first converted to a `VARIANT`, so the same restrictions apply (`Variant::Null` cannot be converted, for example).
+impl TryFrom<Variant> for PROPVARIANT {
+    type Error = WMIError;
+
+    fn try_from(value: Variant) -> WMIResult<PROPVARIANT> {
+        let variant = VARIANT::try_from(value)?;
+
+        Ok(PROPVARIANT::try_from(&variant)?)
+    }
+}
+
+/// Convert the items of a `Variant::Array` to a `VT_ARRAY` VARIANT.
+///
+/// The type of the array is determined by the first item, and all the other items must be of the same type.
+/// Following [WMI's conventions](https://learn.microsoft.com/en-us/windows/win32/wmisdk/numbers),
+/// `sint8` and `real32` items are widened to `VT_I2` and `VT_R8`, and 64-bit integers are passed as strings.
+fn variant_from_array(items: Vec<Variant>) -> WMIResult<VARIANT> {
+    fn collect<T>(items: Vec<Variant>) -> WMIResult<Vec<T>>
+    where
+        T: TryFrom<Variant, Error = WMIError>,
+    {
+        items.into_iter().map(T::try_from).collect()
+    }
+
+    let variant = match items.first() {
+        // An empty array has no item type, so we use an empty array of strings.
+        None | Some(Variant::String(_)) => variant_from_string_array(&collect::<String>(items)?)?,
+        Some(Variant::I1(_)) => {
+            let items: Vec<i16> = collect::<i8>(items)?.into_iter().map(i16::from).collect();
+            unsafe { InitVariantFromInt16Array(&items)? }
+        }
+        Some(Variant::I2(_)) => unsafe { InitVariantFromInt16Array(&collect::<i16>(items)?)? },
+        Some(Variant::I4(_)) => unsafe { InitVariantFromInt32Array(&collect::<i32>(items)?)? },
+        Some(Variant::I8(_)) => {
+            let items: Vec<String> = collect::<i64>(items)?
+                .into_iter()
+                .map(|n| n.to_string())
+                .collect();
+            variant_from_string_array(&items)?
+        }
+        Some(Variant::R4(_)) => {
+            let items: Vec<f64> = collect::<f32>(items)?.into_iter().map(f64::from).collect();
+            unsafe { InitVariantFromDoubleArray(&items)? }
+        }
+        Some(Variant::R8(_)) => unsafe { InitVariantFromDoubleArray(&collect::<f64>(items)?)? },
+        Some(Variant::Bool(_)) => {
+            let items: Vec<BOOL> = collect::<bool>(items)?
+                .into_iter()
+                .map(BOOL::from)
+                .collect();
+            unsafe { InitVariantFromBooleanArray(&items)? }
+        }
+        Some(Variant::UI1(_)) => {
+            let items = collect::<u8>(items)?;
+            unsafe { InitVariantFromBuffer(items.as_ptr().cast(), items.len() as u32)? }
+        }
+        Some(Variant::UI2(_)) => unsafe { InitVariantFromUInt16Array(&collect::<u16>(items)?)? },
+        Some(Variant::UI4(_)) => unsafe { InitVariantFromUInt32Array(&collect::<u32>(items)?)? },
+        Some(Variant::UI8(_)) => {
+            let items: Vec<String> = collect::<u64>(items)?
+                .into_iter()
+                .map(|n| n.to_string())
+                .collect();
+            variant_from_string_array(&items)?
+        }
+        Some(other) => {
+            return Err(WMIError::ConvertVariantError(format!(
+                "Cannot convert an array of {:?} to a Windows VARIANT",
+                other
+            )))
+        }
+    };
+
+    Ok(variant)
+}
+
+fn variant_from_string_array(items: &[String]) -> WMIResult<VARIANT> {
+    // The wide strings must outlive the call, since `InitVariantFromStringArray` copies them.
+    // The copies are `BSTR`s allocated by the call and owned by the returned `VARIANT`,
+    // which frees them (using `VariantClear`) when dropped, so nothing else needs to be freed here.
+    let items: Vec<HSTRING> = items.iter().map(HSTRING::from).collect();
+    let items: Vec<PCWSTR> = items.iter().map(|item| PCWSTR(item.as_ptr())).collect();
+
+    Ok(unsafe { InitVariantFromStringArray(&items)? })
+}
+
 macro_rules! impl_try_from_variant {
     ($target_type:ty, $variant_type:ident) => {
         impl TryFrom<Variant> for $target_type {
@@ -347,7 +545,7 @@ macro_rules! bidirectional_variant_convert {
     };
 }
 
-bidirectional_variant_convert!(String, String);
+impl_wrap_type!(String, String);
 bidirectional_variant_convert!(i8, I1);
 bidirectional_variant_convert!(i16, I2);
 bidirectional_variant_convert!(i32, I4);
@@ -360,6 +558,53 @@ bidirectional_variant_convert!(f32, R4);
 bidirectional_variant_convert!(f64, R8);
 bidirectional_variant_convert!(bool, Bool);
 
+impl TryFrom<Variant> for String {
+    type Error = WMIError;
+
+    /// A [`Variant::Reference`] is also converted to its path, like a [`Variant::String`].
+    fn try_from(value: Variant) -> Result<String, Self::Error> {
+        match value {
+            Variant::String(s) | Variant::Reference(s) => Ok(s),
+            other => Err(WMIError::ConvertVariantError(format!(
+                "Variant {:?} cannot be turned into a {}",
+                &other,
+                stringify!(String)
+            ))),
+        }
+    }
+}
+
+impl TryFrom<Variant> for char {
+    type Error = WMIError;
+
+    /// A `CIM_CHAR16` value is a single UTF-16 code unit, which is represented as a [`Variant::String`]
+    /// with a single character, or as a [`Variant::UI2`] / [`Variant::I2`] code point.
+    fn try_from(value: Variant) -> Result<char, Self::Error> {
+        let c = match &value {
+            Variant::String(s) => {
+                let mut chars = s.chars();
+
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => None,
+                }
+            }
+            Variant::UI2(n) => char::from_u32(*n as u32),
+            Variant::I2(n) => char::from_u32(*n as u16 as u32),
+            _ => None,
+        };
+
+        match c {
+            Some(c) if c.len_utf16() == 1 => Ok(c),
+            _ => Err(WMIError::ConvertVariantError(format!(
+                "Variant {:?} cannot be turned into a {}",
+                &value,
+                stringify!(char)
+            ))),
+        }
+    }
+}
+
 impl From<()> for Variant {
     fn from(_value: ()) -> Self {
         Variant::Empty
@@ -381,11 +626,160 @@ impl TryFrom<Variant> for () {
     }
 }
 
+/// A wrapper around a [`Variant`] which implements `Eq` and `Hash`, so it can be used as a key in a `HashMap` or a `HashSet`.
+///
+/// Floats (`R4` and `R8`) are compared and hashed using their bit patterns, so a `NaN` is equal to itself
+/// (if it has the same bit pattern), but `0.0` and `-0.0` are different keys.
+/// Objects are compared and hashed by identity (the underlying COM pointer), like in [`Variant`]'s `PartialEq`.
+///
+/// ```edition2021
+/// # use std::collections::HashMap;
+/// # use wmi::Variant;
+/// # use wmi::variant::HashableVariant;
+/// let mut counts = HashMap::new();
+///
+/// for value in [Variant::UI4(1), Variant::String("a".to_string()), Variant::UI4(1)] {
+///     *counts.entry(HashableVariant::from(value)).or_insert(0) += 1;
+/// }
+///
+/// assert_eq!(counts[&HashableVariant::from(Variant::UI4(1))], 2);
+/// ```
+#[derive(Debug)]
+pub struct HashableVariant(pub Variant);
+
+impl From<Variant> for HashableVariant {
+    fn from(variant: Variant) -> Self {
+        Self(variant)
+    }
+}
+
+impl From<HashableVariant> for Variant {
+    fn from(variant: HashableVariant) -> Self {
+        variant.0
+    }
+}
+
+impl PartialEq for HashableVariant {
+    fn eq(&self, other: &Self) -> bool {
+        fn eq_by_bits(a: &Variant, b: &Variant) -> bool {
+            match (a, b) {
+                (Variant::R4(a), Variant::R4(b)) => a.to_bits() == b.to_bits(),
+                (Variant::R8(a), Variant::R8(b)) => a.to_bits() == b.to_bits(),
+                (Variant::Array(a), Variant::Array(b)) => {
+                    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| eq_by_bits(a, b))
+                }
+                (a, b) => a == b,
+            }
+        }
+
+        eq_by_bits(&self.0, &other.0)
+    }
+}
+
+impl Eq for HashableVariant {}
+
+impl Hash for HashableVariant {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        fn hash_variant<H: Hasher>(variant: &Variant, state: &mut H) {
+            mem::discriminant(variant).hash(state);
+
+            match variant {
+                Variant::Empty | Variant::Null => {}
+                Variant::String(s) | Variant::Reference(s) => s.hash(state),
+                Variant::I1(n) => n.hash(state),
+                Variant::I2(n) => n.hash(state),
+                Variant::I4(n) => n.hash(state),
+                Variant::I8(n) => n.hash(state),
+                Variant::R4(f) => f.to_bits().hash(state),
+                Variant::R8(f) => f.to_bits().hash(state),
+                Variant::Bool(b) => b.hash(state),
+                Variant::UI1(n) => n.hash(state),
+                Variant::UI2(n) => n.hash(state),
+                Variant::UI4(n) => n.hash(state),
+                Variant::UI8(n) => n.hash(state),
+                Variant::Array(items) => {
+                    items.len().hash(state);
+
+                    for item in items {
+                        hash_variant(item, state);
+                    }
+                }
+                Variant::Unknown(unknown) => unknown.inner.as_raw().hash(state),
+                Variant::Object(object) => object.inner.as_raw().hash(state),
+                Variant::Unsupported(variant_type) => variant_type.hash(state),
+            }
+        }
+
+        hash_variant(&self.0, state)
+    }
+}
+
+/// Convert an OLE Automation date (a `VT_DATE`) into a `CIM_DATETIME` string,
+/// so it can be deserialized like other WMI dates (for example, into a [`WMIDateTime`](crate::WMIDateTime)).
+///
+/// An OLE date is the number of days since midnight, December 30, 1899, where the fractional part is the time of day
+/// (even for negative values, so `-1.25` is 6 AM on December 29, 1899).
+/// OLE dates have no time zone, so they are always converted with a `+000` offset.
+fn ole_date_to_cim_datetime(date: f64) -> WMIResult<String> {
+    // The range of valid OLE dates is January 1, 100 to December 31, 9999.
+    if !(-657_434.0..2_958_466.0).contains(&date) {
+        return Err(WMIError::ConvertVariantError(format!(
+            "{} is not a valid OLE Automation date",
+            date
+        )));
+    }
+
+    const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+    let mut days = date.trunc() as i64;
+    // Rounded to microseconds, since that's the precision of `CIM_DATETIME`.
+    let mut micros = ((date - date.trunc()).abs() * MICROS_PER_DAY as f64).round() as i64;
+
+    if micros == MICROS_PER_DAY {
+        days += 1;
+        micros = 0;
+    }
+
+    // December 30, 1899 is 25,569 days before the Unix epoch.
+    let (year, month, day) = civil_from_days(days - 25_569);
+
+    let secs = micros / 1_000_000;
+
+    Ok(format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}.{:06}+000",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        micros % 1_000_000
+    ))
+}
+
+/// Convert a number of days since the Unix epoch into a (year, month, day) date in the proleptic Gregorian calendar.
+///
+/// See [`civil_from_days`](https://howardhinnant.github.io/date_algorithms.html#civil_from_days).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
 /// A wrapper around the [`IUnknown`] interface. \
 /// Used to retrive [`IWbemClassObject`][winapi::um::Wmi::IWbemClassObject]
 ///
 #[repr(transparent)]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IUnknownWrapper {
     inner: IUnknown,
 }
@@ -418,6 +812,9 @@ impl Serialize for IUnknownWrapper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use windows::Win32::Foundation::FILETIME;
 
     #[test]
     fn it_convert_into_cim_type_sint8() {
@@ -583,7 +980,11 @@ mod tests {
             r#"\\\\PC\\root\\cimv2:Win32_DiskDrive.DeviceID=\"\\\\\\\\.\\\\PHYSICALDRIVE0\""#;
         let variant = Variant::String(datetime.to_string());
         let converted = variant.convert_into_cim_type(cim_type).unwrap();
-        assert_eq!(converted, Variant::String(datetime.to_string()));
+        assert_eq!(converted, Variant::Reference(datetime.to_string()));
+        assert_eq!(converted.as_reference(), Some(datetime));
+
+        let path: String = converted.try_into().unwrap();
+        assert_eq!(path, datetime);
     }
 
     #[test]
@@ -658,4 +1059,345 @@ mod tests {
         let variant = Variant::from(num);
         assert_eq!(Variant::from_variant(&ms_variant).unwrap(), variant);
     }
+
+    #[test]
+    fn it_convert_variant_to_char() {
+        let c: char = Variant::String("C".to_string()).try_into().unwrap();
+        assert_eq!(c, 'C');
+
+        let c: char = Variant::String("ש".to_string()).try_into().unwrap();
+        assert_eq!(c, 'ש');
+
+        let c: char = Variant::UI2(0x41).try_into().unwrap();
+        assert_eq!(c, 'A');
+
+        let c: char = Variant::I2(0x41).try_into().unwrap();
+        assert_eq!(c, 'A');
+
+        for variant in [
+            Variant::String("".to_string()),
+            Variant::String("CD".to_string()),
+            // Not in the BMP, so it cannot be a single UTF-16 code unit.
+            Variant::String("😀".to_string()),
+            // An unpaired surrogate.
+            Variant::UI2(0xD800),
+            Variant::UI4(0x41),
+        ] {
+            let res: WMIResult<char> = variant.try_into();
+            assert!(matches!(res, Err(WMIError::ConvertVariantError(_))));
+        }
+    }
+
+    #[test]
+    fn it_can_use_variants_as_keys() {
+        let mut map = HashMap::new();
+
+        map.insert(HashableVariant::from(Variant::String("a".to_string())), 1);
+        map.insert(HashableVariant::from(Variant::UI4(42)), 2);
+        map.insert(HashableVariant::from(Variant::R8(f64::NAN)), 3);
+        map.insert(
+            HashableVariant::from(Variant::Array(vec![Variant::I8(1), Variant::I8(2)])),
+            4,
+        );
+
+        assert_eq!(
+            map[&HashableVariant::from(Variant::String("a".to_string()))],
+            1
+        );
+        assert_eq!(map[&HashableVariant::from(Variant::UI4(42))], 2);
+        assert_eq!(map[&HashableVariant::from(Variant::R8(f64::NAN))], 3);
+        assert_eq!(
+            map[&HashableVariant::from(Variant::Array(vec![Variant::I8(1), Variant::I8(2)]))],
+            4
+        );
+
+        // Different variant types are different keys, even when the values are the same.
+        assert!(!map.contains_key(&HashableVariant::from(Variant::I4(42))));
+        assert!(!map.contains_key(&HashableVariant::from(Variant::Reference("a".to_string()))));
+
+        assert_ne!(
+            HashableVariant::from(Variant::R8(0.0)),
+            HashableVariant::from(Variant::R8(-0.0))
+        );
+    }
+
+    #[test]
+    fn it_convert_string_array_to_variant() {
+        let variant = Variant::Array(vec![
+            Variant::from("a".to_string()),
+            Variant::from("b".to_string()),
+        ]);
+        let ms_variant = VARIANT::try_from(variant).unwrap();
+
+        assert_eq!(
+            Variant::from_variant(&ms_variant).unwrap(),
+            Variant::Array(vec![
+                Variant::from("a".to_string()),
+                Variant::from("b".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_does_not_leak_string_array_variants() {
+        #[derive(Deserialize)]
+        struct Win32_Process {
+            PrivatePageCount: u64,
+        }
+
+        let wmi_con = crate::tests::fixtures::wmi_con();
+        let private_bytes = || {
+            wmi_con
+                .raw_query::<Win32_Process>(format!(
+                    "SELECT PrivatePageCount FROM Win32_Process WHERE ProcessId = {}",
+                    std::process::id()
+                ))
+                .unwrap()[0]
+                .PrivatePageCount
+        };
+
+        // Each array holds ~300KB of strings, so leaking them would add up to more than 1GB.
+        let make_variant = || {
+            let items = (0..100)
+                .map(|i| Variant::String(i.to_string().repeat(1000)))
+                .collect();
+
+            VARIANT::try_from(Variant::Array(items)).unwrap()
+        };
+
+        // Warm up the allocators before measuring.
+        for _ in 0..100 {
+            drop(make_variant());
+        }
+
+        let before = private_bytes();
+
+        for _ in 0..5000 {
+            let variant = make_variant();
+            assert!(
+                matches!(Variant::from_variant(&variant), Ok(Variant::Array(arr)) if arr.len() == 100)
+            );
+        }
+
+        let after = private_bytes();
+
+        assert!(
+            after.saturating_sub(before) < 64 * 1024 * 1024,
+            "Private bytes grew from {} to {}",
+            before,
+            after
+        );
+    }
+
+    #[test]
+    fn it_convert_numeric_arrays_to_variant() {
+        let variant = Variant::Array(vec![Variant::UI4(1), Variant::UI4(2)]);
+        let ms_variant = VARIANT::try_from(variant).unwrap();
+        assert_eq!(
+            Variant::from_variant(&ms_variant).unwrap(),
+            Variant::Array(vec![Variant::UI4(1), Variant::UI4(2)])
+        );
+
+        let variant = Variant::Array(vec![Variant::UI1(1), Variant::UI1(2)]);
+        let ms_variant = VARIANT::try_from(variant).unwrap();
+        assert_eq!(
+            Variant::from_variant(&ms_variant).unwrap(),
+            Variant::Array(vec![Variant::UI1(1), Variant::UI1(2)])
+        );
+
+        // 64-bit integers are passed as strings.
+        let variant = Variant::Array(vec![Variant::I8(-1), Variant::I8(i64::MAX)]);
+        let ms_variant = VARIANT::try_from(variant).unwrap();
+        assert_eq!(
+            Variant::from_variant(&ms_variant).unwrap(),
+            Variant::Array(vec![
+                Variant::from("-1".to_string()),
+                Variant::from(i64::MAX.to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_fails_to_convert_mixed_array_to_variant() {
+        let variant = Variant::Array(vec![Variant::UI4(1), Variant::from("a".to_string())]);
+
+        assert!(VARIANT::try_from(variant).is_err());
+    }
+
+    #[test]
+    fn it_decodes_invalid_utf16_strings() {
+        // An unpaired surrogate between two valid characters.
+        let bstr = BSTR::from_wide(&[0x61, 0xD800, 0x62]).unwrap();
+        let variant = VARIANT::from(bstr);
+
+        assert_eq!(
+            Variant::from_variant(&variant).unwrap(),
+            Variant::String("a\u{FFFD}b".to_string())
+        );
+        assert_eq!(
+            Variant::from_variant_with_decoding(&variant, StringDecoding::Lossy).unwrap(),
+            Variant::String("a\u{FFFD}b".to_string())
+        );
+        assert!(matches!(
+            Variant::from_variant_with_decoding(&variant, StringDecoding::Strict),
+            Err(WMIError::ConvertStringError(_))
+        ));
+
+        let valid = VARIANT::from(BSTR::from("ab"));
+        assert_eq!(
+            Variant::from_variant_with_decoding(&valid, StringDecoding::Strict).unwrap(),
+            Variant::String("ab".to_string())
+        );
+    }
+
+    /// Create a `VT_DISPATCH` variant holding the given interface pointer.
+    fn dispatch_variant(ptr: IUnknown) -> VARIANT {
+        let variant = VARIANT::from(ptr);
+        let mut raw = *variant.as_raw();
+
+        let mut fields = unsafe { raw.Anonymous.Anonymous };
+        fields.vt = VT_DISPATCH.0;
+        raw.Anonymous.Anonymous = fields;
+
+        // The new variant takes over the reference owned by the original variant.
+        std::mem::forget(variant);
+        unsafe { VARIANT::from_raw(raw) }
+    }
+
+    #[test]
+    fn it_convert_dispatch_variants() {
+        #[derive(Deserialize, Debug)]
+        struct Win32_OperatingSystem {
+            Caption: String,
+        }
+
+        let wmi_con = crate::tests::fixtures::wmi_con();
+        let os = wmi_con
+            .get_raw_by_path(r#"\\.\root\cimv2:Win32_OperatingSystem=@"#)
+            .unwrap();
+
+        let ms_variant = dispatch_variant(os.inner.cast().unwrap());
+
+        let converted = Variant::from_variant(&ms_variant).unwrap();
+        assert_eq!(converted, Variant::Object(os.clone()));
+
+        let converted = Win32_OperatingSystem::deserialize(converted).unwrap();
+        assert!(converted.Caption.contains("Microsoft "));
+
+        // A scripting object, which is not a WMI object.
+        let locator: windows::Win32::System::Com::IDispatch = unsafe {
+            windows::Win32::System::Com::CoCreateInstance(
+                &Wmi::SWbemLocator,
+                None,
+                windows::Win32::System::Com::CLSCTX_INPROC_SERVER,
+            )
+            .unwrap()
+        };
+
+        let ms_variant = dispatch_variant(locator.into());
+
+        assert!(matches!(
+            Variant::from_variant(&ms_variant).unwrap(),
+            Variant::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn it_convert_to_and_from_propvariant() {
+        let prop_variant = PROPVARIANT::try_from(Variant::from("hello".to_string())).unwrap();
+        assert_eq!(
+            Variant::try_from(&prop_variant).unwrap(),
+            Variant::from("hello".to_string())
+        );
+
+        let prop_variant = PROPVARIANT::try_from(Variant::I4(-42)).unwrap();
+        assert_eq!(Variant::try_from(&prop_variant).unwrap(), Variant::I4(-42));
+
+        let prop_variant = PROPVARIANT::from(42u32);
+        assert_eq!(Variant::try_from(&prop_variant).unwrap(), Variant::UI4(42));
+
+        assert!(PROPVARIANT::try_from(Variant::Null).is_err());
+    }
+
+    fn change_type(variant: &VARIANT, vt: VARENUM) -> VARIANT {
+        let mut changed = VARIANT::default();
+
+        unsafe { VariantChangeType(&mut changed, variant, Default::default(), vt).unwrap() };
+
+        changed
+    }
+
+    #[test]
+    fn it_convert_ole_dates() {
+        for (date, expected) in [
+            (0.0, "18991230000000.000000+000"),
+            (-1.25, "18991229060000.000000+000"),
+            (36526.5, "20000101120000.000000+000"),
+            (45351.75, "20240229180000.000000+000"),
+        ] {
+            let ms_variant = change_type(&VARIANT::from(date), VT_DATE);
+
+            assert_eq!(
+                Variant::from_variant(&ms_variant).unwrap(),
+                Variant::String(expected.to_string())
+            );
+        }
+
+        // 2000-01-01 12:30:45 UTC.
+        let filetime: u64 = 125_911_584_000_000_000 + 45_045 * 10_000_000;
+        let ms_variant = unsafe {
+            InitVariantFromFileTime(&FILETIME {
+                dwLowDateTime: filetime as u32,
+                dwHighDateTime: (filetime >> 32) as u32,
+            })
+            .unwrap()
+        };
+
+        assert_eq!(
+            Variant::from_variant(&ms_variant).unwrap(),
+            Variant::String("20000101123045.000000+000".to_string())
+        );
+
+        #[cfg(feature = "chrono")]
+        {
+            let datetime =
+                crate::WMIDateTime::deserialize(Variant::from_variant(&ms_variant).unwrap())
+                    .unwrap();
+
+            assert_eq!(datetime.0.to_rfc3339(), "2000-01-01T12:30:45+00:00");
+        }
+
+        assert!(ole_date_to_cim_datetime(3_000_000.0).is_err());
+        assert!(ole_date_to_cim_datetime(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn it_convert_unsupported_variant_type_leniently() {
+        let ms_variant = change_type(&VARIANT::from(1.5f64), VT_CY);
+
+        match Variant::from_variant(&ms_variant) {
+            Err(WMIError::ConvertError(variant_type)) => assert_eq!(variant_type, VT_CY.0),
+            res => panic!("Unexpected result {:?}", res),
+        }
+
+        assert_eq!(
+            Variant::from_variant_lenient(&ms_variant).unwrap(),
+            Variant::Unsupported(VT_CY.0)
+        );
+
+        let ms_variant = VARIANT::from(42u32);
+        assert_eq!(
+            Variant::from_variant_lenient(&ms_variant).unwrap(),
+            Variant::UI4(42)
+        );
+    }
+
+    #[test]
+    fn it_desr_unsupported_variant() {
+        let variant = Variant::deserialize(Variant::Unsupported(VT_CY.0)).unwrap();
+        assert_eq!(variant, Variant::Unsupported(VT_CY.0));
+
+        let value = Option::<u32>::deserialize(Variant::Unsupported(VT_CY.0)).unwrap();
+        assert_eq!(value, None);
+    }
 }